@@ -2,6 +2,7 @@ import { web3, utils, BN } from '@project-serum/anchor';
 import {
   NATIVE_MINT,
   TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountInstruction,
   createInitializeMintInstruction,
   createMintToInstruction,
   getMinimumBalanceForRentExemptMint,
@@ -10,7 +11,7 @@ import {
 import { useWorkspace } from 'src/composables';
 import { findAtaDetails } from '.';
 import { PublicKey, SystemProgram } from '@solana/web3.js';
-import { fetchTokenAccountBalance } from './manageATA';
+import { checkIfInitializd, fetchTokenAccountBalance } from './manageATA';
 
 // ONLY FOR LOCALNET TESTING / ADMIN
 export const createCeniei = async (
@@ -80,6 +81,43 @@ export const mintCeniei = async (
   );
 };
 
+// the singleton config every market instruction checks the house and the pause switch on
+const findProgramConfig = async (): Promise<web3.PublicKey> => {
+  const { program } = useWorkspace();
+
+  const [programConfig] = await web3.PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode('program-config')],
+    program.value.programId
+  );
+  return programConfig;
+};
+
+// the market's LP share mint and the account holding the locked minimum liquidity
+const findLpAccounts = async (
+  marketState: web3.PublicKey,
+  beneficiary: web3.PublicKey
+): Promise<[web3.PublicKey, web3.PublicKey]> => {
+  const { program } = useWorkspace();
+
+  const [lpMint] = await web3.PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode('lp-mint'),
+      marketState.toBuffer(),
+      beneficiary.toBuffer(),
+    ],
+    program.value.programId
+  );
+  const [lpLock] = await web3.PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode('lp-lock'),
+      marketState.toBuffer(),
+      beneficiary.toBuffer(),
+    ],
+    program.value.programId
+  );
+  return [lpMint, lpLock];
+};
+
 // ONLY AVAILABLE IF THE CURRENT WALLET IS THE BENEFICIARY
 export const setupMarketPrereqs = async (
   beneficiary: string
@@ -144,9 +182,12 @@ export const initializeMarket = async (
   const fee_num = new BN(0);
   const fee_den = new BN(1000);
 
+  const [lpMint, lpLock] = await findLpAccounts(marketState, user);
+
   const tx = await program.value.methods
     .ammSetupInstruction(fee_num, fee_den)
     .accounts({
+      programConfig: await findProgramConfig(),
       tokenMint: cenieiMint,
       solMint: NATIVE_MINT,
 
@@ -155,6 +196,9 @@ export const initializeMarket = async (
       tokenVault: cenieiVault,
       solVault: solVault,
 
+      lpMint: lpMint,
+      lpLock: lpLock,
+
       beneficiary: user,
 
       systemProgram: web3.SystemProgram.programId,
@@ -167,7 +211,7 @@ export const initializeMarket = async (
   return ['0', '0'];
 };
 
-// ONLY AVAILABLE IF THE CURRENT WALLET IS THE BENEFICIARY
+// any wallet can provide liquidity, `beneficiary` is the house that owns the market
 export const addLiquidity = async (
   cenieiMint: web3.PublicKey,
   marketState: web3.PublicKey,
@@ -182,27 +226,39 @@ export const addLiquidity = async (
     throw 'wallet undefined';
   }
 
-  if (user.toBase58() !== beneficiary) {
-    throw 'Not the Admin';
-  }
-
   const [, userSolATA] = await findAtaDetails(NATIVE_MINT);
   const [, userCenieiATA] = await findAtaDetails(cenieiMint);
 
+  // the LP shares are minted to the provider's ATA, opened on the first deposit
+  const [lpMint, lpLock] = await findLpAccounts(
+    marketState,
+    new web3.PublicKey(beneficiary)
+  );
+  const [, userLpATA] = await findAtaDetails(lpMint);
+  const preInstructions =
+    (await checkIfInitializd(userLpATA)) === null
+      ? [createAssociatedTokenAccountInstruction(user, userLpATA, user, lpMint)]
+      : [];
+
   const tokenAmount = new BN(10000 * 10 ** 9);
   const solAmount = new BN(1 * web3.LAMPORTS_PER_SOL);
 
   const tx = await program.value.methods
     .addLiquidityInstruction(tokenAmount, solAmount)
     .accounts({
+      programConfig: await findProgramConfig(),
       marketState: marketState,
       tokenVault: cenieiVault,
       solVault: solVault,
-      beneficiary: user,
-      beneficiaryTokenAta: userCenieiATA,
-      beneficiarySolAta: userSolATA,
+      lpMint: lpMint,
+      lpLock: lpLock,
+      provider: user,
+      providerTokenAta: userCenieiATA,
+      providerSolAta: userSolATA,
+      providerLpAta: userLpATA,
       tokenProgram: TOKEN_PROGRAM_ID,
     })
+    .preInstructions(preInstructions)
     .rpc();
 
   console.log(`Added Liquidity to the market with signature: ${tx}`);
@@ -212,6 +268,43 @@ export const addLiquidity = async (
   return [solVaultBalance!.toString(), cenieiVaultBalance!.toString()];
 };
 
+// a swap fails rather than fill more than 1% below the quote or land a minute late
+const SWAP_SLIPPAGE_BPS = 100;
+const SWAP_DEADLINE_SECONDS = 60;
+
+// the least the swap may pay out, quoted off the vaults the way the program prices it
+const minAmountOut = async (
+  solToToken: boolean,
+  amount: BN,
+  marketState: web3.PublicKey,
+  cenieiVault: web3.PublicKey,
+  solVault: web3.PublicKey
+): Promise<BN> => {
+  const { program, provider } = useWorkspace();
+  const connection = provider.value.connection;
+
+  const market = await program.value.account.marketState.fetch(marketState);
+  const cenieiReserve = new BN(
+    (await connection.getTokenAccountBalance(cenieiVault)).value.amount
+  );
+  const solReserve = new BN(
+    (await connection.getTokenAccountBalance(solVault)).value.amount
+  );
+  const [reserveIn, reserveOut] = solToToken
+    ? [solReserve, cenieiReserve]
+    : [cenieiReserve, solReserve];
+
+  const fee = amount
+    .mul(market.feeNumerator as BN)
+    .div(market.feeDenominator as BN);
+  const amountInAfterFee = amount.sub(fee);
+  const amountOut = amountInAfterFee
+    .mul(reserveOut)
+    .div(reserveIn.add(amountInAfterFee));
+
+  return amountOut.muln(10000 - SWAP_SLIPPAGE_BPS).divn(10000);
+};
+
 export const convertCurrency = async (
   solToToken: boolean,
   amount: BN,
@@ -231,9 +324,19 @@ export const convertCurrency = async (
   const [, userSolATA] = await findAtaDetails(NATIVE_MINT);
   const [, userCenieiATA] = await findAtaDetails(cenieiMint);
 
+  const minOut = await minAmountOut(
+    solToToken,
+    amount,
+    marketState,
+    cenieiVault,
+    solVault
+  );
+  const deadline = new BN(Math.floor(Date.now() / 1000) + SWAP_DEADLINE_SECONDS);
+
   const txHash = await program.value.methods
-    .swapTokensInstruction(amount, solToToken)
+    .swapTokensInstruction(amount, solToToken, minOut, deadline)
     .accounts({
+      programConfig: await findProgramConfig(),
       marketState: marketState,
       tokenVault: cenieiVault,
       solVault: solVault,
@@ -103,7 +103,7 @@ export const useMarketStore = defineStore('market', {
         amount,
         this.cenieiMint,
         this.marketState,
-        this.cenieiMint,
+        this.cenieiVault,
         this.solVault,
         new web3.PublicKey(this.beneficiary)
       ).catch((e) => {
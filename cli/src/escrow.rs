@@ -0,0 +1,167 @@
+use anyhow::{bail, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_sdk::account::from_account;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::sysvar;
+
+use dungeon_nft_sdk::accounts::{decode, discriminator, TransactionState};
+use dungeon_nft_sdk::{instructions, pda, EscrowKeys, SolEscrowKeys, Stage};
+
+use crate::Context;
+
+// the node only returns accounts behind the escrow discriminator, one that still doesn't
+// decode (a legacy layout waiting on migrate_account) is reported and left out
+fn fetch_escrows(ctx: &Context) -> Result<Vec<(Pubkey, TransactionState)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
+            offset: 0,
+            bytes: MemcmpEncodedBytes::Bytes(discriminator::<TransactionState>().to_vec()),
+            encoding: None,
+        })]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let mut escrows = Vec::new();
+
+    for (address, account) in ctx
+        .client
+        .get_program_accounts_with_config(&dungeon_nft_sdk::ID, config)?
+    {
+        match decode::<TransactionState>(&account.data) {
+            Ok(state) => escrows.push((address, state)),
+            Err(err) => eprintln!("Skipping {}, it doesn't decode as an escrow: {}", address, err),
+        }
+    }
+
+    Ok(escrows)
+}
+
+fn is_lamport_escrow(state: &TransactionState) -> bool {
+    state.mint_of_token == Pubkey::default()
+}
+
+// a migrated escrow from before session ids, still at the session-less address
+fn is_legacy_escrow(address: &Pubkey, state: &TransactionState) -> bool {
+    *address
+        == pda::find_legacy_transaction_state(
+            &state.player,
+            &state.beneficiary,
+            &state.mint_of_token,
+        )
+        .0
+}
+
+fn stage_of(state: &TransactionState) -> Option<Stage> {
+    Stage::from(state.stage).ok()
+}
+
+// the validator's clock, which is what the program checks the deadline against
+fn cluster_time(ctx: &Context) -> Result<i64> {
+    let account = ctx.client.get_account(&sysvar::clock::id())?;
+    let clock: Clock = from_account(&account).ok_or_else(|| anyhow::anyhow!("bad clock sysvar"))?;
+    Ok(clock.unix_timestamp)
+}
+
+fn is_expired(state: &TransactionState, now: i64) -> bool {
+    stage_of(state) == Some(Stage::FundsDeposited) && now >= state.deadline
+}
+
+pub fn list_escrows(ctx: &Context, player: Option<Pubkey>, open: bool) -> Result<()> {
+    let now = cluster_time(ctx)?;
+
+    for (address, state) in fetch_escrows(ctx)? {
+        if matches!(player, Some(player) if player != state.player) {
+            continue;
+        }
+
+        let stage = stage_of(&state);
+        if open && matches!(stage, Some(stage) if stage.is_terminal()) {
+            continue;
+        }
+
+        println!("{}", address);
+        println!(
+            "  kind:     {}",
+            if is_lamport_escrow(&state) {
+                "lamports"
+            } else {
+                "token"
+            }
+        );
+        match stage {
+            Some(stage) => println!("  stage:    {:?}", stage),
+            None => println!("  stage:    unknown ({})", state.stage),
+        }
+        println!("  player:   {}", state.player);
+        println!("  house:    {}", state.beneficiary);
+        if !is_lamport_escrow(&state) {
+            println!("  mint:     {}", state.mint_of_token);
+        }
+        println!("  session:  {}", state.session_id);
+        println!(
+            "  stakes:   {} player / {} house",
+            state.player_stake, state.house_stake
+        );
+        if is_expired(&state, now) {
+            println!("  deadline: {} (expired, can be refunded)", state.deadline);
+        } else if stage == Some(Stage::FundsDeposited) {
+            println!("  deadline: {}", state.deadline);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn refund(ctx: &Context, escrow: Option<Pubkey>, all_expired: bool) -> Result<()> {
+    let now = cluster_time(ctx)?;
+
+    let targets: Vec<(Pubkey, TransactionState)> = match escrow {
+        Some(address) => {
+            let account = ctx.client.get_account(&address)?;
+            let state = decode::<TransactionState>(&account.data)?;
+            if !is_expired(&state, now) {
+                bail!("{} is not a funded escrow past its deadline", address);
+            }
+            vec![(address, state)]
+        }
+        None if all_expired => fetch_escrows(ctx)?
+            .into_iter()
+            .filter(|(_, state)| is_expired(state, now))
+            .collect(),
+        None => bail!("pass an escrow address or --all-expired"),
+    };
+
+    for (address, state) in targets {
+        // pullback is permissionless once the deadline passed, both sides get their stake back
+        let ix = if is_lamport_escrow(&state) {
+            instructions::sol_pullback(&SolEscrowKeys {
+                player: state.player,
+                beneficiary: state.beneficiary,
+                session_id: state.session_id,
+            })
+        } else if is_legacy_escrow(&address, &state) {
+            instructions::legacy_pullback(&state.player, &state.beneficiary, &state.mint_of_token)
+        } else {
+            instructions::pullback(&EscrowKeys {
+                player: state.player,
+                beneficiary: state.beneficiary,
+                mint_of_token: state.mint_of_token,
+                session_id: state.session_id,
+            })
+        };
+
+        match ctx.send(&[ix], &[]) {
+            Ok(signature) => println!("Refunded {} with signature: {}", address, signature),
+            Err(err) => eprintln!("Failed to refund {}: {}", address, err),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+//! Admin tasks for the DungeonNFT program that used to live in the browser behind
+//! "ONLY FOR LOCALNET TESTING / ADMIN": the CENIEI mint, the market, and escrows nobody settled.
+
+mod escrow;
+mod market;
+
+use anyhow::{anyhow, Context as _, Result};
+use clap::{Parser, Subcommand};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+#[derive(Parser)]
+#[clap(
+    name = "dungeon-admin",
+    version,
+    about = "Admin command line for the DungeonNFT program"
+)]
+struct Cli {
+    /// RPC endpoint, the default is where solana-test-validator listens
+    #[clap(long, short = 'u', default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// Keypair that signs and pays for every transaction
+    #[clap(long, short = 'k', default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the CENIEI mint with the keypair as mint authority
+    CreateMint {
+        #[clap(long, default_value_t = 9)]
+        decimals: u8,
+    },
+
+    /// Mint raw token units into an associated token account, creating it if needed
+    MintTo {
+        #[clap(long)]
+        mint: Pubkey,
+        #[clap(long)]
+        amount: u64,
+        /// Defaults to the keypair
+        #[clap(long)]
+        owner: Option<Pubkey>,
+    },
+
+    /// Open the token / wrapped SOL market with the keypair as the house
+    AmmSetup {
+        #[clap(long)]
+        token_mint: Pubkey,
+        #[clap(long, default_value_t = 0)]
+        fee_numerator: u64,
+        #[clap(long, default_value_t = 1000)]
+        fee_denominator: u64,
+    },
+
+    /// Give the keypair's market its LP mint, only needed once for a market migrated from
+    /// before LP shares. The next deposit gets shares for everything in the vaults
+    LpSetup {
+        #[clap(long)]
+        token_mint: Pubkey,
+    },
+
+    /// Deposit the keypair's tokens and lamports into a market, the lamports get wrapped first.
+    /// Once the pool is seeded both amounts are maxima, only the pair at the pool's ratio is taken
+    AddLiquidity {
+        /// The house that opened the market, defaults to the keypair
+        #[clap(long)]
+        market: Option<Pubkey>,
+        #[clap(long)]
+        token_mint: Pubkey,
+        #[clap(long)]
+        token_amount: u64,
+        #[clap(long)]
+        sol_amount: u64,
+    },
+
+    /// Burn the keypair's LP shares for its cut of both vaults, the SOL side stays wrapped
+    RemoveLiquidity {
+        /// The house that opened the market, defaults to the keypair
+        #[clap(long)]
+        market: Option<Pubkey>,
+        #[clap(long)]
+        token_mint: Pubkey,
+        #[clap(long)]
+        lp_amount: u64,
+        /// Fail unless at least this many token units come back
+        #[clap(long, default_value_t = 0)]
+        min_token_amount: u64,
+        /// Fail unless at least this many lamports come back
+        #[clap(long, default_value_t = 0)]
+        min_sol_amount: u64,
+    },
+
+    /// List every escrow with its stage
+    ListEscrows {
+        #[clap(long)]
+        player: Option<Pubkey>,
+        /// Skip the escrows that only wait to be closed
+        #[clap(long)]
+        open: bool,
+    },
+
+    /// Refund both stakes of funded escrows that ran past their deadline
+    Refund {
+        /// A single escrow state, token or lamport
+        escrow: Option<Pubkey>,
+        /// Every funded escrow past its deadline
+        #[clap(long, conflicts_with = "escrow")]
+        all_expired: bool,
+    },
+}
+
+pub struct Context {
+    pub client: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Context {
+    pub fn send(
+        &self,
+        instructions: &[Instruction],
+        extra_signers: &[&Keypair],
+    ) -> Result<Signature> {
+        let mut signers = vec![&self.payer];
+        signers.extend_from_slice(extra_signers);
+
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &signers,
+            self.client.get_latest_blockhash()?,
+        );
+
+        Ok(self.client.send_and_confirm_transaction(&tx)?)
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let keypair_path = expand_home(&cli.keypair);
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|err| anyhow!("{}", err))
+        .with_context(|| format!("reading keypair {}", keypair_path))?;
+
+    let ctx = Context {
+        client: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer,
+    };
+
+    match cli.command {
+        Command::CreateMint { decimals } => market::create_mint(&ctx, decimals),
+        Command::MintTo {
+            mint,
+            amount,
+            owner,
+        } => market::mint_to(
+            &ctx,
+            &mint,
+            &owner.unwrap_or_else(|| ctx.payer.pubkey()),
+            amount,
+        ),
+        Command::AmmSetup {
+            token_mint,
+            fee_numerator,
+            fee_denominator,
+        } => market::amm_setup(&ctx, &token_mint, fee_numerator, fee_denominator),
+        Command::LpSetup { token_mint } => market::lp_setup(&ctx, &token_mint),
+        Command::AddLiquidity {
+            market,
+            token_mint,
+            token_amount,
+            sol_amount,
+        } => market::add_liquidity(
+            &ctx,
+            &market.unwrap_or_else(|| ctx.payer.pubkey()),
+            &token_mint,
+            token_amount,
+            sol_amount,
+        ),
+        Command::RemoveLiquidity {
+            market,
+            token_mint,
+            lp_amount,
+            min_token_amount,
+            min_sol_amount,
+        } => market::remove_liquidity(
+            &ctx,
+            &market.unwrap_or_else(|| ctx.payer.pubkey()),
+            &token_mint,
+            lp_amount,
+            min_token_amount,
+            min_sol_amount,
+        ),
+        Command::ListEscrows { player, open } => escrow::list_escrows(&ctx, player, open),
+        Command::Refund {
+            escrow,
+            all_expired,
+        } => escrow::refund(&ctx, escrow, all_expired),
+    }
+}
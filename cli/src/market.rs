@@ -0,0 +1,208 @@
+use anyhow::Result;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+
+use dungeon_nft_sdk::{instructions, LiquidityProvider, MarketKeys};
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+
+use crate::Context;
+
+// the ata of `owner`, plus the instruction creating it when it isn't there yet
+fn find_or_create_ata(ctx: &Context, owner: &Pubkey, mint: &Pubkey) -> (Pubkey, Vec<Instruction>) {
+    let ata = get_associated_token_address(owner, mint);
+
+    match ctx.client.get_account(&ata) {
+        Ok(_) => (ata, vec![]),
+        Err(_) => (
+            ata,
+            vec![create_associated_token_account(
+                &ctx.payer.pubkey(),
+                owner,
+                mint,
+            )],
+        ),
+    }
+}
+
+pub fn create_mint(ctx: &Context, decimals: u8) -> Result<()> {
+    let mint = Keypair::new();
+    let authority = ctx.payer.pubkey();
+
+    let rent = ctx
+        .client
+        .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+
+    let signature = ctx.send(
+        &[
+            system_instruction::create_account(
+                &authority,
+                &mint.pubkey(),
+                rent,
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &authority,
+                Some(&authority),
+                decimals,
+            )?,
+        ],
+        &[&mint],
+    )?;
+
+    println!("CENIEI: {}", mint.pubkey());
+    println!("Created with signature: {}", signature);
+
+    Ok(())
+}
+
+pub fn mint_to(ctx: &Context, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Result<()> {
+    let (ata, mut ixs) = find_or_create_ata(ctx, owner, mint);
+
+    ixs.push(spl_token::instruction::mint_to(
+        &spl_token::id(),
+        mint,
+        &ata,
+        &ctx.payer.pubkey(),
+        &[],
+        amount,
+    )?);
+
+    let signature = ctx.send(&ixs, &[])?;
+    println!("Minted {} to {} with signature: {}", amount, ata, signature);
+
+    Ok(())
+}
+
+pub fn amm_setup(
+    ctx: &Context,
+    token_mint: &Pubkey,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<()> {
+    let keys = MarketKeys {
+        beneficiary: ctx.payer.pubkey(),
+    };
+
+    let signature = ctx.send(
+        &[instructions::amm_setup(
+            &keys,
+            token_mint,
+            &spl_token::native_mint::id(),
+            fee_numerator,
+            fee_denominator,
+        )],
+        &[],
+    )?;
+
+    println!("Market state: {}", keys.market_state());
+    println!("Token vault: {}", keys.token_vault());
+    println!("Sol vault: {}", keys.sol_vault());
+    println!("LP mint: {}", keys.lp_mint());
+    println!("Initialized the market with signature: {}", signature);
+
+    Ok(())
+}
+
+pub fn lp_setup(ctx: &Context, token_mint: &Pubkey) -> Result<()> {
+    let keys = MarketKeys {
+        beneficiary: ctx.payer.pubkey(),
+    };
+
+    let signature = ctx.send(&[instructions::lp_setup(&keys, token_mint)], &[])?;
+
+    println!("LP mint: {}", keys.lp_mint());
+    println!("Set up the market's LP mint with signature: {}", signature);
+
+    Ok(())
+}
+
+pub fn add_liquidity(
+    ctx: &Context,
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    token_amount: u64,
+    sol_amount: u64,
+) -> Result<()> {
+    let provider = ctx.payer.pubkey();
+    let keys = MarketKeys {
+        beneficiary: *market,
+    };
+
+    let token_ata = get_associated_token_address(&provider, token_mint);
+    let (sol_ata, mut ixs) = find_or_create_ata(ctx, &provider, &spl_token::native_mint::id());
+
+    // the market only holds wrapped SOL
+    ixs.push(system_instruction::transfer(
+        &provider,
+        &sol_ata,
+        sol_amount,
+    ));
+    ixs.push(spl_token::instruction::sync_native(
+        &spl_token::id(),
+        &sol_ata,
+    )?);
+
+    // the pool shares are minted into the provider's lp ata
+    let (lp_ata, lp_ixs) = find_or_create_ata(ctx, &provider, &keys.lp_mint());
+    ixs.extend(lp_ixs);
+
+    ixs.push(instructions::add_liquidity(
+        &keys,
+        &LiquidityProvider {
+            provider,
+            token_ata,
+            sol_ata,
+            lp_ata,
+        },
+        token_amount,
+        sol_amount,
+    ));
+
+    let signature = ctx.send(&ixs, &[])?;
+    println!("Added liquidity with signature: {}", signature);
+
+    Ok(())
+}
+
+pub fn remove_liquidity(
+    ctx: &Context,
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    lp_amount: u64,
+    min_token_amount: u64,
+    min_sol_amount: u64,
+) -> Result<()> {
+    let provider = ctx.payer.pubkey();
+    let keys = MarketKeys {
+        beneficiary: *market,
+    };
+
+    let (token_ata, mut ixs) = find_or_create_ata(ctx, &provider, token_mint);
+    let (sol_ata, sol_ixs) = find_or_create_ata(ctx, &provider, &spl_token::native_mint::id());
+    ixs.extend(sol_ixs);
+    let lp_ata = get_associated_token_address(&provider, &keys.lp_mint());
+
+    ixs.push(instructions::remove_liquidity(
+        &keys,
+        &LiquidityProvider {
+            provider,
+            token_ata,
+            sol_ata,
+            lp_ata,
+        },
+        lp_amount,
+        min_token_amount,
+        min_sol_amount,
+    ));
+
+    let signature = ctx.send(&ixs, &[])?;
+    println!("Removed liquidity with signature: {}", signature);
+
+    Ok(())
+}
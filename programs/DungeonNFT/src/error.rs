@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::math::MathError;
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Stage is invalid")]
@@ -7,4 +9,164 @@ pub enum ErrorCode {
 
     #[msg("Insufficient balance")]
     NotEnoughBalance,
+
+    #[msg("Signer is not the program admin")]
+    UnauthorizedAdmin,
+
+    #[msg("Signer is not the game referee")]
+    UnauthorizedReferee,
+
+    #[msg("Winner must be the player or the beneficiary of the escrow")]
+    InvalidWinner,
+
+    #[msg("Game result attestation is missing or malformed")]
+    InvalidAttestation,
+
+    #[msg("Game result attestation has expired")]
+    AttestationExpired,
+
+    #[msg("Game result attestation nonce does not match the escrow")]
+    AttestationNonceMismatch,
+
+    #[msg("Timeout is out of range")]
+    InvalidTimeout,
+
+    #[msg("Settlement deadline has passed")]
+    DeadlinePassed,
+
+    #[msg("Settlement deadline has not been reached yet")]
+    DeadlineNotReached,
+
+    #[msg("Fee can't be more than 1000 basis points")]
+    InvalidFee,
+
+    #[msg("Split ratio can't be 0 : 0")]
+    InvalidSplit,
+
+    #[msg("Signer is not the lobby creator")]
+    UnauthorizedCreator,
+
+    #[msg("Lobby player count is invalid")]
+    InvalidLobbySize,
+
+    #[msg("Payout table must have at most 8 places adding up to 10000 basis points")]
+    InvalidPayoutTable,
+
+    #[msg("Lobby is full")]
+    LobbyFull,
+
+    #[msg("Player has already joined the lobby")]
+    AlreadyJoined,
+
+    #[msg("Lobby winners must be distinct participants, one per payout place")]
+    InvalidLobbyWinners,
+
+    #[msg("Token account doesn't belong to the expected lobby participant")]
+    InvalidLobbyAccount,
+
+    #[msg("Signer is not the arbiter")]
+    UnauthorizedArbiter,
+
+    #[msg("Only the losing party can dispute the result")]
+    UnauthorizedDisputer,
+
+    #[msg("Challenge window is still open")]
+    ChallengeWindowOpen,
+
+    #[msg("Challenge window has closed")]
+    ChallengeWindowClosed,
+
+    #[msg("Mint is not registered for wagers")]
+    MintNotRegistered,
+
+    #[msg("Stake is outside the limits registered for the mint")]
+    StakeOutOfRange,
+
+    #[msg("Mint decimals don't match the registered decimals")]
+    MintDecimalsMismatch,
+
+    #[msg("Stake limits are invalid")]
+    InvalidStakeLimits,
+
+    #[msg("Mint registry is full")]
+    MintRegistryFull,
+
+    #[msg("Stake has already been deposited")]
+    AlreadyDeposited,
+
+    #[msg("Season window is invalid")]
+    InvalidSeason,
+
+    #[msg("Season is not running")]
+    SeasonNotActive,
+
+    #[msg("Beneficiary is not the house")]
+    UnauthorizedHouse,
+
+    #[msg("Signer is not the proposed admin")]
+    UnauthorizedPendingAdmin,
+
+    #[msg("Program is paused")]
+    ProgramPaused,
+
+    #[msg("Account is not a TransactionState or MarketState owned by this program")]
+    UnknownAccountLayout,
+
+    #[msg("Account already has the current layout")]
+    AccountAlreadyMigrated,
+
+    #[msg("Amount doesn't fit in a u64")]
+    MathOverflow,
+
+    #[msg("Amount must be more than zero")]
+    ZeroAmount,
+
+    #[msg("Pool holds nothing on one side")]
+    EmptyPool,
+
+    #[msg("Market fee must be a fraction below one")]
+    InvalidMarketFee,
+
+    #[msg("Pool doesn't hold enough liquidity")]
+    InsufficientLiquidity,
+
+    #[msg("Amount out is below the requested minimum")]
+    SlippageExceeded,
+
+    #[msg("Swap deadline has passed")]
+    Expired,
+
+    #[msg("Signer is not the program's upgrade authority")]
+    UnauthorizedUpgradeAuthority,
+
+    #[msg("Arbiter can still rule on the dispute")]
+    DisputeUnresolved,
+
+    #[msg("Deposit doesn't match the stake set up for the escrow")]
+    StakeMismatch,
+
+    #[msg("Only the player or the house can cancel the escrow")]
+    UnauthorizedCanceller,
+
+    #[msg("The market already has an LP mint")]
+    LpMintExists,
+}
+
+impl From<MathError> for ErrorCode {
+    fn from(error: MathError) -> Self {
+        match error {
+            MathError::Overflow => ErrorCode::MathOverflow,
+            MathError::ZeroAmount => ErrorCode::ZeroAmount,
+            MathError::EmptyPool => ErrorCode::EmptyPool,
+            MathError::InvalidFee => ErrorCode::InvalidMarketFee,
+            MathError::InsufficientLiquidity => ErrorCode::InsufficientLiquidity,
+        }
+    }
+}
+
+// lets the instructions use `?` on the math results
+impl From<MathError> for anchor_lang::error::Error {
+    fn from(error: MathError) -> Self {
+        ErrorCode::from(error).into()
+    }
 }
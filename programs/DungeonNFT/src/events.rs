@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+
+use crate::state;
+
+// every escrow, lobby, market, treasury and config state change, with enough in each event
+// to rebuild the accounts without reading them. mint_of_token is the default pubkey for
+// lamport escrows and the lamport treasury
+
+#[event]
+pub struct EscrowCreated {
+    pub transaction_state: Pubkey,
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub escrow_account: Pubkey,
+    pub session_id: u64,
+    pub player_stake: u64,
+    pub house_stake: u64,
+    pub result_nonce: u64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowDeposited {
+    pub transaction_state: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub player_stake: u64,
+    pub house_stake: u64,
+    pub escrow_balance: u64,
+    // only set once both sides have paid in
+    pub deadline: i64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowResultSubmitted {
+    pub transaction_state: Pubkey,
+    pub player_share: u64,
+    pub beneficiary_share: u64,
+    pub challenge_deadline: i64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowDisputed {
+    pub transaction_state: Pubkey,
+    pub disputer: Pubkey,
+    pub dispute_deadline: i64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowDisputeResolved {
+    pub transaction_state: Pubkey,
+    pub arbiter: Pubkey,
+    pub upheld: bool,
+    pub player_share: u64,
+    pub beneficiary_share: u64,
+    pub challenge_deadline: i64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowSettled {
+    pub transaction_state: Pubkey,
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub player_amount: u64,
+    pub beneficiary_amount: u64,
+    pub fee: u64,
+    pub treasury_fees_collected: u64,
+    // tokens sent to the escrow on top of the stakes, swept to the house
+    pub residue: u64,
+    pub escrow_balance: u64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowPulledBack {
+    pub transaction_state: Pubkey,
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub player_refund: u64,
+    pub house_refund: u64,
+    // tokens sent to the escrow on top of the stakes, swept to the house
+    pub residue: u64,
+    pub escrow_balance: u64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowCancelled {
+    pub transaction_state: Pubkey,
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub player_refund: u64,
+    pub house_refund: u64,
+    // tokens sent to the escrow on top of the deposits, swept to the house
+    pub residue: u64,
+    pub escrow_balance: u64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct EscrowClosed {
+    pub transaction_state: Pubkey,
+    pub player: Pubkey,
+    pub stage: u8,
+}
+
+#[event]
+pub struct MarketCreated {
+    pub market_state: Pubkey,
+    pub beneficiary: Pubkey,
+    pub token_mint: Pubkey,
+    pub sol_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub sol_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_numerator: u64,
+    pub fee_denominator: u64,
+}
+
+#[event]
+pub struct MarketLpSetup {
+    pub market_state: Pubkey,
+    pub beneficiary: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_lock: Pubkey,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub market_state: Pubkey,
+    pub provider: Pubkey,
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub lp_amount: u64,
+    pub token_vault_balance: u64,
+    pub sol_vault_balance: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub market_state: Pubkey,
+    pub provider: Pubkey,
+    pub lp_amount: u64,
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub token_vault_balance: u64,
+    pub sol_vault_balance: u64,
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub market_state: Pubkey,
+    pub player: Pubkey,
+    pub sol_to_token: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub token_vault_balance: u64,
+    pub sol_vault_balance: u64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub version: u8,
+    pub space: u64,
+}
+
+#[event]
+pub struct LobbyCreated {
+    pub lobby_state: Pubkey,
+    pub creator: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub lobby_vault: Pubkey,
+    pub lobby_id: u64,
+    pub entry_stake: u64,
+    pub max_players: u8,
+    pub payout_bps: Vec<u16>,
+    pub fee_bps: u16,
+    pub deadline: i64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct LobbyJoined {
+    pub lobby_state: Pubkey,
+    pub player: Pubkey,
+    pub players: u8,
+    pub lobby_balance: u64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct LobbyLocked {
+    pub lobby_state: Pubkey,
+    pub players: u8,
+    pub deadline: i64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct LobbySettled {
+    pub lobby_state: Pubkey,
+    // in finishing order, paid prizes[place]
+    pub winners: Vec<Pubkey>,
+    pub prizes: Vec<u64>,
+    pub fee: u64,
+    // tokens sent to the lobby on top of the entries, swept to the treasury
+    pub residue: u64,
+    pub treasury_fees_collected: u64,
+    pub stage: u8,
+}
+
+// every seat refunded its entry stake, by the creator or after the deadline
+#[event]
+pub struct LobbyRefunded {
+    pub lobby_state: Pubkey,
+    pub players: u8,
+    pub entry_stake: u64,
+    pub residue: u64,
+    pub treasury_fees_collected: u64,
+    pub stage: u8,
+}
+
+#[event]
+pub struct TreasuryCreated {
+    pub treasury_state: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub treasury_vault: Pubkey,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub treasury_state: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_fees_collected: u64,
+    pub total_withdrawn: u64,
+    pub treasury_balance: u64,
+}
+
+// the whole config after config_setup or any of the setters
+#[event]
+pub struct ConfigUpdated {
+    pub program_config: Pubkey,
+    pub admin: Pubkey,
+    pub house: Pubkey,
+    pub referee: Pubkey,
+    pub arbiter: Pubkey,
+    pub settlement_timeout: i64,
+    pub dispute_window: i64,
+    pub fee_bps: u16,
+}
+
+impl ConfigUpdated {
+    pub fn new(program_config: Pubkey, config: &state::ProgramConfig) -> Self {
+        ConfigUpdated {
+            program_config,
+            admin: config.admin,
+            house: config.house,
+            referee: config.referee,
+            arbiter: config.arbiter,
+            settlement_timeout: config.settlement_timeout,
+            dispute_window: config.dispute_window,
+            fee_bps: config.fee_bps,
+        }
+    }
+}
+
+#[event]
+pub struct AdminProposed {
+    pub program_config: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub program_config: Pubkey,
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct PauseChanged {
+    pub program_config: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct MintRegistryCreated {
+    pub mint_registry: Pubkey,
+}
+
+// also sent when a listed mint gets new limits
+#[event]
+pub struct MintRegistered {
+    pub mint_registry: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub min_stake: u64,
+    pub max_stake: u64,
+}
+
+#[event]
+pub struct MintDeregistered {
+    pub mint_registry: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct LeaderboardCreated {
+    pub leaderboard: Pubkey,
+    pub season: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+#[event]
+pub struct LeaderboardUpdated {
+    pub leaderboard: Pubkey,
+    pub season: u32,
+    pub player: Pubkey,
+    pub score: u64,
+    // the board after the update, best score first
+    pub entries: Vec<state::LeaderboardEntry>,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.program_config;
+    let previous_admin = config.admin;
+    config.admin = ctx.accounts.new_admin.key().clone();
+    config.pending_admin = Pubkey::default();
+
+    emit!(events::AdminChanged {
+        program_config: config.key(),
+        previous_admin,
+        admin: config.admin,
+    });
+
+    msg!("Admin changed to {}", config.admin);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.pending_admin == new_admin.key() @ error::ErrorCode::UnauthorizedPendingAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    new_admin: Signer<'info>,
+}
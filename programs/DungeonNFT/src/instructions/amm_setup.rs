@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::state;
+use crate::{error, events, math, state};
 
 pub fn amm_setup(ctx: Context<AMMSetup>, fee_numerator: u64, fee_denominator: u64) -> Result<()> {
+    math::check_fee(fee_numerator, fee_denominator)?;
+
     let market_state = &mut ctx.accounts.market_state;
     market_state.fee_numerator = fee_numerator;
     market_state.fee_denominator = fee_denominator;
@@ -11,6 +13,23 @@ pub fn amm_setup(ctx: Context<AMMSetup>, fee_numerator: u64, fee_denominator: u6
     market_state.state_bump = *ctx.bumps.get("market_state").unwrap();
     market_state.token_vault_bump = *ctx.bumps.get("token_vault").unwrap();
     market_state.sol_vault_bump = *ctx.bumps.get("sol_vault").unwrap();
+    market_state.lp_mint_bump = *ctx.bumps.get("lp_mint").unwrap();
+    market_state.lp_lock_bump = *ctx.bumps.get("lp_lock").unwrap();
+    market_state.has_lp_mint = true;
+    market_state.owner = ctx.accounts.beneficiary.key();
+    market_state.version = state::MarketState::VERSION;
+
+    emit!(events::MarketCreated {
+        market_state: market_state.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        sol_mint: ctx.accounts.sol_mint.key(),
+        token_vault: ctx.accounts.token_vault.key(),
+        sol_vault: ctx.accounts.sol_vault.key(),
+        lp_mint: ctx.accounts.lp_mint.key(),
+        fee_numerator,
+        fee_denominator,
+    });
 
     Ok(())
 }
@@ -35,6 +54,14 @@ pub struct AMMSetup<'info> {
     )]
     pub market_state: Account<'info, state::MarketState>,
 
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused,
+        constraint = program_config.house == beneficiary.key() @ error::ErrorCode::UnauthorizedHouse
+    )]
+    pub program_config: Account<'info, state::ProgramConfig>,
+
     #[account(
         init, 
         payer = beneficiary, 
@@ -63,6 +90,35 @@ pub struct AMMSetup<'info> {
     )]
     pub sol_vault: Account<'info, TokenAccount>,
 
+    // shares in the pool, only the market mints them
+    #[account(
+        init, 
+        payer = beneficiary, 
+        seeds = [
+            b"lp-mint".as_ref(), 
+            market_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump, 
+        mint::decimals = token_mint.decimals, 
+        mint::authority = market_state
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init, 
+        payer = beneficiary, 
+        seeds = [
+            b"lp-lock".as_ref(), 
+            market_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump, 
+        token::mint = lp_mint, 
+        token::authority = market_state
+    )]
+    pub lp_lock: Account<'info, TokenAccount>,
+
     #[account(mut)]
     pub beneficiary: Signer<'info>,
 
@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+// remaining accounts are the token accounts of every participant, in the order they joined
+pub fn cancel_lobby<'info>(ctx: Context<'_, '_, '_, 'info, CancelLobby<'info>>) -> Result<()> {
+    // once locked the seats are in play, only the referee or the timeout can end the lobby
+    utils::LobbyStage::transition(
+        &mut ctx.accounts.lobby_state.stage,
+        utils::LobbyStage::Cancelled,
+    )?;
+
+    let residue = refund_entries(
+        &ctx.accounts.lobby_state,
+        &ctx.accounts.lobby_vault,
+        ctx.accounts.treasury_vault.to_account_info(),
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_fees_collected = treasury_state
+        .total_fees_collected
+        .checked_add(residue)
+        .unwrap();
+
+    let lobby = &ctx.accounts.lobby_state;
+    emit!(events::LobbyRefunded {
+        lobby_state: lobby.key(),
+        players: lobby.players.len() as u8,
+        entry_stake: lobby.entry_stake,
+        residue,
+        treasury_fees_collected: treasury_state.total_fees_collected,
+        stage: lobby.stage,
+    });
+
+    msg!(
+        "Cancelled the lobby and refunded {} players",
+        ctx.accounts.lobby_state.players.len()
+    );
+
+    Ok(())
+}
+
+// pays every entry back to the token accounts in `player_token_accounts`, sweeps whatever
+// else was sent to the vault to the treasury and closes the vault, returns the swept amount
+pub fn refund_entries<'info>(
+    lobby: &Account<'info, state::LobbyState>,
+    lobby_vault: &Account<'info, TokenAccount>,
+    treasury_vault: AccountInfo<'info>,
+    creator: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    player_token_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    require!(
+        player_token_accounts.len() == lobby.players.len(),
+        error::ErrorCode::InvalidLobbyAccount
+    );
+
+    let residue = utils::residue(lobby_vault.amount, lobby.pot())?;
+
+    let lobby_id_bytes = lobby.lobby_id.to_le_bytes();
+    let state_bump_bytes = lobby.state_bump.to_le_bytes();
+    let inner = vec![
+        b"lobby-state".as_ref(),
+        lobby.creator.as_ref(),
+        lobby.mint_of_token.as_ref(),
+        lobby_id_bytes.as_ref(),
+        state_bump_bytes.as_ref(),
+    ];
+    let outer = vec![inner.as_slice()];
+
+    for (index, player) in lobby.players.iter().enumerate() {
+        let player_token_account_info = &player_token_accounts[index];
+        let player_token_account = Account::<TokenAccount>::try_from(player_token_account_info)?;
+        require!(
+            player_token_account.owner == *player
+                && player_token_account.mint == lobby.mint_of_token,
+            error::ErrorCode::InvalidLobbyAccount
+        );
+
+        utils::secure_transfer_cpi(
+            lobby.entry_stake,
+            lobby.to_account_info(),
+            lobby_vault.to_account_info(),
+            player_token_account_info.clone(),
+            token_program.clone(),
+            outer.as_ref(),
+        )?;
+    }
+
+    if residue > 0 {
+        utils::secure_transfer_cpi(
+            residue,
+            lobby.to_account_info(),
+            lobby_vault.to_account_info(),
+            treasury_vault,
+            token_program.clone(),
+            outer.as_ref(),
+        )?;
+    }
+
+    utils::close_account_cpi(
+        lobby_vault.to_account_info(),
+        creator,
+        lobby.to_account_info(),
+        token_program,
+        outer.as_ref(),
+    )?;
+
+    Ok(residue)
+}
+
+#[derive(Accounts)]
+pub struct CancelLobby<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-state".as_ref(),
+            lobby_state.creator.as_ref(),
+            lobby_state.mint_of_token.as_ref(),
+            lobby_state.lobby_id.to_le_bytes().as_ref(),
+        ],
+        bump = lobby_state.state_bump,
+        constraint = lobby_state.creator == creator.key() @ error::ErrorCode::UnauthorizedCreator
+    )]
+    lobby_state: Account<'info, state::LobbyState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-vault".as_ref(),
+            lobby_state.key().as_ref(),
+        ],
+        bump = lobby_state.vault_bump
+    )]
+    lobby_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-state".as_ref(),
+            lobby_state.mint_of_token.as_ref()
+        ],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-vault".as_ref(),
+            lobby_state.mint_of_token.as_ref()
+        ],
+        bump = treasury_state.vault_bump,
+    )]
+    treasury_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    token_program: Program<'info, Token>,
+}
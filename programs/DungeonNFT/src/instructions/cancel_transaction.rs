@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+// lets either side walk away before both have paid in, whoever already deposited
+// gets their stake back
+pub fn cancel_transaction(ctx: Context<CancelTransaction>) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::Cancelled,
+    )?;
+
+    // a donation would keep the escrow from closing, so it's swept like in pull_back
+    let residue = utils::residue(
+        ctx.accounts.escrow_account.amount,
+        ctx.accounts.transaction_state.deposited(),
+    )?;
+
+    let mint_of_token_public_key = ctx.accounts.mint_of_token.key().clone();
+    let session_id_bytes = ctx.accounts.transaction_state.session_id.to_le_bytes();
+    let state_bump_bytes = ctx.accounts.transaction_state.state_bump.to_le_bytes();
+    let inner = vec![
+        b"transaction-state".as_ref(),
+        ctx.accounts.player.key.as_ref(),
+        ctx.accounts.beneficiary.key.as_ref(),
+        mint_of_token_public_key.as_ref(),
+        session_id_bytes.as_ref(),
+        state_bump_bytes.as_ref(),
+    ];
+    let outer = vec![inner.as_slice()];
+
+    if ctx.accounts.transaction_state.player_deposited {
+        utils::secure_transfer_cpi(
+            ctx.accounts.transaction_state.player_stake,
+            ctx.accounts.transaction_state.to_account_info(),
+            ctx.accounts.escrow_account.to_account_info(),
+            ctx.accounts
+                .player_associated_token_account
+                .to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            outer.as_ref(),
+        )?;
+    }
+
+    // whatever was sent on top of the deposits goes to the house along with its own
+    let house_refund = if ctx.accounts.transaction_state.house_deposited {
+        ctx.accounts.transaction_state.house_stake
+    } else {
+        0
+    };
+    if house_refund + residue > 0 {
+        utils::secure_transfer_cpi(
+            house_refund + residue,
+            ctx.accounts.transaction_state.to_account_info(),
+            ctx.accounts.escrow_account.to_account_info(),
+            ctx.accounts
+                .beneficiary_associated_token_account
+                .to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            outer.as_ref(),
+        )?;
+    }
+
+    utils::close_account_cpi(
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts.player.to_account_info(),
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    let state = &ctx.accounts.transaction_state;
+    emit!(events::EscrowCancelled {
+        transaction_state: state.key(),
+        player: state.player,
+        beneficiary: state.beneficiary,
+        player_refund: if state.player_deposited {
+            state.player_stake
+        } else {
+            0
+        },
+        house_refund,
+        residue,
+        escrow_balance: 0,
+        stage: state.stage,
+    });
+
+    msg!(
+        "Cancelled session {} before it was fully funded",
+        ctx.accounts.transaction_state.session_id
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelTransaction<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow-account".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.escrow_bump
+    )]
+    escrow_account: Account<'info, TokenAccount>,
+
+    // the player or the house, so neither stake is stuck when the other side never pays in
+    #[account(
+        constraint = authority.key() == player.key()
+            || authority.key() == beneficiary.key() @ error::ErrorCode::UnauthorizedCanceller
+    )]
+    authority: Signer<'info>,
+
+    /// CHECK: Only receives the rent of the closed escrow, the address is part of the state's seeds
+    #[account(mut)]
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_of_token,
+        associated_token::authority = player
+    )]
+    player_associated_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::authority = beneficiary,
+        associated_token::mint = mint_of_token
+    )]
+    beneficiary_associated_token_account: Account<'info, TokenAccount>,
+
+    token_program: Program<'info, Token>,
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+// reclaims the rent of an escrow once its outcome has been read, anyone can crank it
+// since the lamports always go back to the player
+pub fn close_transaction(ctx: Context<CloseTransaction>) -> Result<()> {
+    let stage = utils::Stage::from(ctx.accounts.transaction_state.stage)?;
+
+    if !stage.is_terminal() {
+        msg!(
+            "Stage is invalid, state stage is {}",
+            ctx.accounts.transaction_state.stage
+        );
+        return Err(error::ErrorCode::StageInvalid.into());
+    }
+
+    emit!(events::EscrowClosed {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        player: ctx.accounts.player.key(),
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
+    msg!(
+        "Closed session {} in stage {}",
+        ctx.accounts.transaction_state.session_id,
+        ctx.accounts.transaction_state.stage
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseTransaction<'info> {
+    // shared by token and lamport escrows, only this program can create a TransactionState
+    #[account(
+        mut,
+        has_one = player,
+        close = player
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    player: AccountInfo<'info>,
+}
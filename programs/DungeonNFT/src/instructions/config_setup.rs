@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::{error, events, state, utils};
+
+pub fn config_setup(
+    ctx: Context<ConfigSetup>,
+    house: Pubkey,
+    referee: Pubkey,
+    arbiter: Pubkey,
+    settlement_timeout: i64,
+    dispute_window: i64,
+    fee_bps: u16,
+) -> Result<()> {
+    require!(settlement_timeout > 0, error::ErrorCode::InvalidTimeout);
+    require!(dispute_window >= 0, error::ErrorCode::InvalidTimeout);
+    require!(
+        fee_bps <= utils::MAX_FEE_BPS,
+        error::ErrorCode::InvalidFee
+    );
+
+    let config = &mut ctx.accounts.program_config;
+
+    config.admin = ctx.accounts.admin.key().clone();
+    config.pending_admin = Pubkey::default();
+    config.house = house;
+    config.paused = false;
+    config.referee = referee;
+    config.settlement_timeout = settlement_timeout;
+    config.fee_bps = fee_bps;
+    config.arbiter = arbiter;
+    config.dispute_window = dispute_window;
+    config.season = 0;
+    config.season_starts_at = 0;
+    config.season_ends_at = 0;
+    config.state_bump = *ctx.bumps.get("program_config").unwrap();
+
+    emit!(events::ConfigUpdated::new(
+        ctx.accounts.program_config.key(),
+        &ctx.accounts.program_config
+    ));
+
+    msg!(
+        "Initialized program config with house {} and referee {}",
+        house,
+        referee
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigSetup<'info> {
+    #[account(
+        init,
+        space = 8 + state::ProgramConfig::LEN,
+        payer = admin,
+        seeds = [b"program-config".as_ref()],
+        bump
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    // the loader's record of this program, only its upgrade authority can claim the config
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ error::ErrorCode::UnauthorizedUpgradeAuthority
+    )]
+    program_data: Account<'info, ProgramData>,
+
+    system_program: Program<'info, System>,
+}
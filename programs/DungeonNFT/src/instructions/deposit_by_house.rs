@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+// the house pays in on its own, the player can deposit before or after
+pub fn deposit_by_house(ctx: Context<DepositByHouse>, house_stake: u64) -> Result<()> {
+    let state = &ctx.accounts.transaction_state;
+
+    utils::Stage::expect(state.stage, utils::Stage::Initialized)?;
+    require!(!state.house_deposited, error::ErrorCode::AlreadyDeposited);
+    // the stakes were fixed when the escrow was set up
+    require!(
+        house_stake == state.house_stake,
+        error::ErrorCode::StakeMismatch
+    );
+
+    // a deregistered mint can't take new funding, the escrow can only be cancelled
+    let entry = ctx
+        .accounts
+        .mint_registry
+        .entry(&ctx.accounts.mint_of_token.key())
+        .ok_or(error::ErrorCode::MintNotRegistered)?;
+    require!(
+        entry.allows_stake(house_stake),
+        error::ErrorCode::StakeOutOfRange
+    );
+
+    utils::secure_transfer_cpi(
+        house_stake,
+        ctx.accounts.beneficiary.to_account_info(),
+        ctx.accounts
+            .beneficiary_associated_token_account
+            .to_account_info(),
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        &[],
+    )?;
+
+    let state = &mut ctx.accounts.transaction_state;
+    state.house_deposited = true;
+
+    if state.is_funded() {
+        utils::Stage::transition(&mut state.stage, utils::Stage::FundsDeposited)?;
+        state.fee_bps = ctx.accounts.program_config.fee_bps;
+        state.deadline = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.program_config.settlement_timeout)
+            .unwrap();
+    }
+
+    emit!(events::EscrowDeposited {
+        transaction_state: state.key(),
+        depositor: ctx.accounts.beneficiary.key(),
+        amount: house_stake,
+        player_stake: state.player_stake,
+        house_stake: state.house_stake,
+        escrow_balance: state.deposited(),
+        deadline: state.deadline,
+        stage: state.stage,
+    });
+
+    msg!(
+        "House funded the escrow account with {} tokens",
+        house_stake
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositByHouse<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow-account".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.escrow_bump
+    )]
+    escrow_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        seeds = [b"mint-registry".as_ref()],
+        bump = mint_registry.state_bump,
+    )]
+    mint_registry: Account<'info, state::MintRegistry>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    player: AccountInfo<'info>,
+    beneficiary: Signer<'info>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::authority = beneficiary,
+        associated_token::mint = mint_of_token
+    )]
+    beneficiary_associated_token_account: Account<'info, TokenAccount>,
+
+    token_program: Program<'info, Token>,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+// funded escrows in the mint can still be settled, unfunded ones can only be cancelled
+pub fn deregister_mint(ctx: Context<DeregisterMint>, mint: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.mint_registry;
+
+    let len_before = registry.entries.len();
+    registry.entries.retain(|entry| entry.mint != mint);
+    require!(
+        registry.entries.len() < len_before,
+        error::ErrorCode::MintNotRegistered
+    );
+
+    emit!(events::MintDeregistered {
+        mint_registry: registry.key(),
+        mint,
+    });
+
+    msg!("Removed mint {} from the registry", mint);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeregisterMint<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-registry".as_ref()],
+        bump = mint_registry.state_bump,
+    )]
+    mint_registry: Account<'info, state::MintRegistry>,
+
+    admin: Signer<'info>,
+}
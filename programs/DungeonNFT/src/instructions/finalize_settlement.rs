@@ -0,0 +1,246 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+use super::update_leaderboard::record_score;
+
+// pays out the recorded verdict once nobody can dispute it anymore, anyone can crank it
+pub fn finalize_settlement(ctx: Context<FinalizeSettlement>) -> Result<()> {
+    let disputed =
+        utils::Stage::from(ctx.accounts.transaction_state.stage)? == utils::Stage::Disputed;
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::EscrowComplete,
+    )?;
+
+    // a dispute the arbiter didn't rule on in time leaves the referee's verdict in place
+    if disputed {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.transaction_state.dispute_deadline,
+            error::ErrorCode::DisputeUnresolved
+        );
+    } else {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.transaction_state.challenge_deadline,
+            error::ErrorCode::ChallengeWindowOpen
+        );
+    }
+
+    let pot = ctx.accounts.transaction_state.pot();
+    let fee = utils::rake(pot, ctx.accounts.transaction_state.fee_bps);
+    let (player_amount, beneficiary_amount) = utils::split_pot(
+        pot - fee,
+        ctx.accounts.transaction_state.player_share,
+        ctx.accounts.transaction_state.beneficiary_share,
+    )?;
+    let residue = utils::residue(ctx.accounts.escrow_account.amount, pot)?;
+
+    let mint_of_token_public_key = ctx.accounts.mint_of_token.key().clone();
+    let session_id_bytes = ctx.accounts.transaction_state.session_id.to_le_bytes();
+    let state_bump_bytes = ctx.accounts.transaction_state.state_bump.to_le_bytes();
+    let inner = vec![
+        b"transaction-state".as_ref(),
+        ctx.accounts.player.key.as_ref(),
+        ctx.accounts.beneficiary.key.as_ref(),
+        mint_of_token_public_key.as_ref(),
+        session_id_bytes.as_ref(),
+        state_bump_bytes.as_ref(),
+    ];
+    let outer = vec![inner.as_slice()];
+
+    utils::secure_transfer_cpi(
+        fee,
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts.treasury_vault.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_fees_collected =
+        treasury_state.total_fees_collected.checked_add(fee).unwrap();
+
+    utils::secure_transfer_cpi(
+        player_amount,
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts
+            .player_associated_token_account
+            .to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    // whatever was sent on top of the stakes goes to the house with its share
+    utils::secure_transfer_cpi(
+        beneficiary_amount + residue,
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts
+            .beneficiary_associated_token_account
+            .to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    utils::close_account_cpi(
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts.player.to_account_info(),
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    // the player's share of the pot before the rake decides how the game went
+    let transaction_state = &ctx.accounts.transaction_state;
+    let (awarded, _) = utils::split_pot(
+        pot,
+        transaction_state.player_share,
+        transaction_state.beneficiary_share,
+    )?;
+    let season = ctx
+        .accounts
+        .program_config
+        .active_season(Clock::get()?.unix_timestamp);
+    ctx.accounts.player_profile.record_game(
+        &transaction_state.mint_of_token,
+        transaction_state.player_stake,
+        player_amount,
+        state::GameOutcome::of(awarded, transaction_state.player_stake),
+        season,
+    );
+
+    if season.is_some() {
+        let mut leaderboard =
+            Account::<state::Leaderboard>::try_from(&ctx.accounts.leaderboard)?;
+        record_score(&mut leaderboard, &ctx.accounts.player_profile);
+        leaderboard.exit(ctx.program_id)?;
+    }
+
+    emit!(events::EscrowSettled {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        player: ctx.accounts.player.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        mint_of_token: ctx.accounts.transaction_state.mint_of_token,
+        player_amount,
+        beneficiary_amount,
+        fee,
+        treasury_fees_collected: ctx.accounts.treasury_state.total_fees_collected,
+        residue,
+        escrow_balance: 0,
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
+    msg!(
+        "Paid out {} tokens to the player, {} tokens to the beneficiary and {} tokens to the treasury",
+        player_amount,
+        beneficiary_amount,
+        fee
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow-account".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.escrow_bump,
+    )]
+    escrow_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-state".as_ref(),
+            mint_of_token.key().as_ref()
+        ],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-vault".as_ref(),
+            mint_of_token.key().as_ref()
+        ],
+        bump = treasury_state.vault_bump,
+    )]
+    treasury_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"player-profile".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump = player_profile.state_bump
+    )]
+    player_profile: Account<'info, state::PlayerProfile>,
+
+    /// CHECK: The running season's board, it doesn't exist before the first season so it's
+    /// only deserialized while the season is active
+    #[account(
+        mut,
+        seeds = [
+            b"leaderboard".as_ref(),
+            program_config.season.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    leaderboard: AccountInfo<'info>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_of_token,
+        associated_token::authority = player
+    )]
+    player_associated_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::authority = beneficiary,
+        associated_token::mint = mint_of_token
+    )]
+    beneficiary_associated_token_account: Account<'info, TokenAccount>,
+
+    token_program: Program<'info, Token>,
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+pub fn join_lobby(ctx: Context<JoinLobby>) -> Result<()> {
+    let lobby = &ctx.accounts.lobby_state;
+
+    if utils::LobbyStage::from(lobby.stage)? != utils::LobbyStage::Open {
+        msg!("Stage is invalid, lobby stage is {}", lobby.stage);
+        return Err(error::ErrorCode::StageInvalid.into());
+    }
+
+    require!(
+        lobby.players.len() < lobby.max_players as usize,
+        error::ErrorCode::LobbyFull
+    );
+    require!(
+        !lobby.players.contains(ctx.accounts.player.key),
+        error::ErrorCode::AlreadyJoined
+    );
+
+    utils::secure_transfer_cpi(
+        lobby.entry_stake,
+        ctx.accounts.player.to_account_info(),
+        ctx.accounts.player_token_account.to_account_info(),
+        ctx.accounts.lobby_vault.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        &[],
+    )?;
+
+    let profile = &mut ctx.accounts.player_profile;
+    profile.player = ctx.accounts.player.key().clone();
+    profile.state_bump = *ctx.bumps.get("player_profile").unwrap();
+
+    let lobby = &mut ctx.accounts.lobby_state;
+    lobby.players.push(ctx.accounts.player.key().clone());
+
+    emit!(events::LobbyJoined {
+        lobby_state: lobby.key(),
+        player: ctx.accounts.player.key(),
+        players: lobby.players.len() as u8,
+        lobby_balance: lobby.pot(),
+        stage: lobby.stage,
+    });
+
+    msg!(
+        "Player joined the lobby, {} of {} seats taken",
+        lobby.players.len(),
+        lobby.max_players
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct JoinLobby<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-state".as_ref(),
+            lobby_state.creator.as_ref(),
+            lobby_state.mint_of_token.as_ref(),
+            lobby_state.lobby_id.to_le_bytes().as_ref(),
+        ],
+        bump = lobby_state.state_bump
+    )]
+    lobby_state: Account<'info, state::LobbyState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-vault".as_ref(),
+            lobby_state.key().as_ref(),
+        ],
+        bump = lobby_state.vault_bump
+    )]
+    lobby_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    player: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + state::PlayerProfile::LEN,
+        payer = player,
+        seeds = [
+            b"player-profile".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump
+    )]
+    player_profile: Account<'info, state::PlayerProfile>,
+
+    #[account(
+        mut,
+        token::mint = lobby_state.mint_of_token,
+        token::authority = player
+    )]
+    player_token_account: Account<'info, TokenAccount>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+}
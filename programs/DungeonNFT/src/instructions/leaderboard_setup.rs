@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn leaderboard_setup(
+    ctx: Context<LeaderboardSetup>,
+    season: u32,
+    starts_at: i64,
+    ends_at: i64,
+) -> Result<()> {
+    require!(starts_at < ends_at, error::ErrorCode::InvalidSeason);
+
+    // one season at a time, settlements only count towards the latest
+    let config = &mut ctx.accounts.program_config;
+    require!(
+        Clock::get()?.unix_timestamp >= config.season_ends_at && season > config.season,
+        error::ErrorCode::InvalidSeason
+    );
+    config.season = season;
+    config.season_starts_at = starts_at;
+    config.season_ends_at = ends_at;
+
+    let leaderboard = &mut ctx.accounts.leaderboard;
+
+    leaderboard.season = season;
+    leaderboard.starts_at = starts_at;
+    leaderboard.ends_at = ends_at;
+    leaderboard.entries = Vec::new();
+    leaderboard.state_bump = *ctx.bumps.get("leaderboard").unwrap();
+
+    emit!(events::LeaderboardCreated {
+        leaderboard: leaderboard.key(),
+        season,
+        starts_at,
+        ends_at,
+    });
+
+    msg!(
+        "Opened the season {} leaderboard from {} to {}",
+        season,
+        starts_at,
+        ends_at
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(season: u32)]
+pub struct LeaderboardSetup<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        init,
+        space = 8 + state::Leaderboard::LEN,
+        payer = admin,
+        seeds = [
+            b"leaderboard".as_ref(),
+            season.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    leaderboard: Account<'info, state::Leaderboard>,
+
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
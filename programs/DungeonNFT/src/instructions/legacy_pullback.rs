@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+// refunds an escrow opened before session ids once migrate_account brought it up to date,
+// those are still at the session-less seeds none of the other instructions derive
+pub fn legacy_pull_back(ctx: Context<LegacyPullBack>) -> Result<()> {
+    let next = if ctx.accounts.transaction_state.is_funded() {
+        utils::Stage::Expired
+    } else {
+        utils::Stage::Cancelled
+    };
+    utils::Stage::transition(&mut ctx.accounts.transaction_state.stage, next)?;
+
+    let residue = utils::residue(
+        ctx.accounts.escrow_account.amount,
+        ctx.accounts.transaction_state.deposited(),
+    )?;
+
+    let mint_of_token_public_key = ctx.accounts.mint_of_token.key().clone();
+    let state_bump_bytes = ctx.accounts.transaction_state.state_bump.to_le_bytes();
+    let inner = vec![
+        b"transaction-state".as_ref(),
+        ctx.accounts.player.key.as_ref(),
+        ctx.accounts.beneficiary.key.as_ref(),
+        mint_of_token_public_key.as_ref(),
+        state_bump_bytes.as_ref(),
+    ];
+    let outer = vec![inner.as_slice()];
+
+    let (player_refund, house_refund) = if ctx.accounts.transaction_state.is_funded() {
+        (
+            ctx.accounts.transaction_state.player_stake,
+            ctx.accounts.transaction_state.house_stake,
+        )
+    } else {
+        (0, 0)
+    };
+
+    if player_refund > 0 {
+        utils::secure_transfer_cpi(
+            player_refund,
+            ctx.accounts.transaction_state.to_account_info(),
+            ctx.accounts.escrow_account.to_account_info(),
+            ctx.accounts
+                .player_associated_token_account
+                .to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            outer.as_ref(),
+        )?;
+    }
+
+    // whatever was sent on top of the stakes goes to the house along with its own
+    if house_refund + residue > 0 {
+        utils::secure_transfer_cpi(
+            house_refund + residue,
+            ctx.accounts.transaction_state.to_account_info(),
+            ctx.accounts.escrow_account.to_account_info(),
+            ctx.accounts
+                .beneficiary_associated_token_account
+                .to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            outer.as_ref(),
+        )?;
+    }
+
+    utils::close_account_cpi(
+        ctx.accounts.escrow_account.to_account_info(),
+        ctx.accounts.player.to_account_info(),
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    emit!(events::EscrowPulledBack {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        player: ctx.accounts.player.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        player_refund,
+        house_refund,
+        residue,
+        escrow_balance: 0,
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
+    msg!(
+        "Refunded the legacy escrow {}",
+        ctx.accounts.transaction_state.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LegacyPullBack<'info> {
+    // the seeds from before session ids, a current escrow never derives to this address
+    #[account(
+        mut,
+        seeds = [
+            b"transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow-account".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            mint_of_token.key().as_ref(),
+        ],
+        bump = transaction_state.escrow_bump
+    )]
+    escrow_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Only receives the rent of the closed escrow, the address is part of the state's seeds
+    #[account(mut)]
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_of_token,
+        associated_token::authority = player
+    )]
+    player_associated_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::authority = beneficiary,
+        associated_token::mint = mint_of_token
+    )]
+    beneficiary_associated_token_account: Account<'info, TokenAccount>,
+
+    token_program: Program<'info, Token>,
+}
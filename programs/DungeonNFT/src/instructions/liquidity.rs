@@ -1,30 +1,49 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::{error, state, utils};
+use crate::{error, events, math, state, utils};
 
+// the amounts are upper bounds, what's taken is the largest pair at the pool's ratio
+// and the event reports it
 pub fn add_liquidity(
     ctx: Context<LiquidityOperation>,
-    token_amount: u64,
-    sol_amount: u64,
+    max_token_amount: u64,
+    max_sol_amount: u64,
 ) -> Result<()> {
-    let token_balance = ctx.accounts.beneficiary_token_ata.amount;
-    let sol_balance = ctx.accounts.beneficiary_sol_ata.amount;
+    let token_balance = ctx.accounts.provider_token_ata.amount;
+    let sol_balance = ctx.accounts.provider_sol_ata.amount;
 
     let curr_token_in_vault = ctx.accounts.token_vault.amount;
     let curr_sol_in_vault = ctx.accounts.sol_vault.amount;
 
-    let initial_liquidity_addition = curr_sol_in_vault == 0 && curr_token_in_vault == 0;
+    let lp_supply = ctx.accounts.lp_mint.supply;
 
-    let token_deposit_amount = token_amount;
-    let sol_deposit_amount;
+    // no shares out yet, so whatever sits in the vaults goes to the first provider
+    let initial_liquidity_addition = lp_supply == 0;
 
-    if initial_liquidity_addition {
-        sol_deposit_amount = sol_amount;
+    let (token_deposit_amount, sol_deposit_amount, lp_amount) = if initial_liquidity_addition {
+        (
+            max_token_amount,
+            max_sol_amount,
+            math::initial_shares(max_token_amount, max_sol_amount)?,
+        )
     } else {
-        let exchange_rate = curr_sol_in_vault.checked_div(curr_token_in_vault).unwrap();
-        sol_deposit_amount = token_deposit_amount.checked_mul(exchange_rate).unwrap();
-    }
+        // an emptied vault can't price the deposit, that's an EmptyPool error
+        let (token_deposit_amount, sol_deposit_amount) = math::balanced_deposit(
+            max_token_amount,
+            max_sol_amount,
+            curr_token_in_vault,
+            curr_sol_in_vault,
+        )?;
+        let lp_amount = math::proportional_shares(
+            token_deposit_amount,
+            sol_deposit_amount,
+            curr_token_in_vault,
+            curr_sol_in_vault,
+            lp_supply,
+        )?;
+        (token_deposit_amount, sol_deposit_amount, lp_amount)
+    };
 
     require!(
         sol_deposit_amount <= sol_balance,
@@ -38,15 +57,15 @@ pub fn add_liquidity(
     let state_bump_bytes = ctx.accounts.market_state.state_bump.to_le_bytes();
     let inner = vec![
         b"market-state".as_ref(),
-        ctx.accounts.beneficiary.key.as_ref(),
+        ctx.accounts.market_state.owner.as_ref(),
         state_bump_bytes.as_ref()
     ];
     let outer = vec![inner.as_slice()];
 
     utils::secure_transfer_cpi(
         token_deposit_amount,
-        ctx.accounts.beneficiary.to_account_info(),
-        ctx.accounts.beneficiary_token_ata.to_account_info(),
+        ctx.accounts.provider.to_account_info(),
+        ctx.accounts.provider_token_ata.to_account_info(),
         ctx.accounts.token_vault.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         outer.as_ref(),
@@ -54,13 +73,127 @@ pub fn add_liquidity(
 
     utils::secure_transfer_cpi(
         sol_deposit_amount,
-        ctx.accounts.beneficiary.to_account_info(),
-        ctx.accounts.beneficiary_sol_ata.to_account_info(),
+        ctx.accounts.provider.to_account_info(),
+        ctx.accounts.provider_sol_ata.to_account_info(),
+        ctx.accounts.sol_vault.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    if initial_liquidity_addition {
+        utils::mint_to_cpi(
+            math::MINIMUM_LIQUIDITY,
+            ctx.accounts.market_state.to_account_info(),
+            ctx.accounts.lp_mint.to_account_info(),
+            ctx.accounts.lp_lock.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            outer.as_ref(),
+        )?;
+    }
+
+    utils::mint_to_cpi(
+        lp_amount,
+        ctx.accounts.market_state.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.provider_lp_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    let token_vault_balance = curr_token_in_vault
+        .checked_add(token_deposit_amount)
+        .ok_or(error::ErrorCode::MathOverflow)?;
+    let sol_vault_balance = curr_sol_in_vault
+        .checked_add(sol_deposit_amount)
+        .ok_or(error::ErrorCode::MathOverflow)?;
+
+    emit!(events::LiquidityAdded {
+        market_state: ctx.accounts.market_state.key(),
+        provider: ctx.accounts.provider.key(),
+        token_amount: token_deposit_amount,
+        sol_amount: sol_deposit_amount,
+        lp_amount,
+        token_vault_balance,
+        sol_vault_balance,
+    });
+
+    Ok(())
+}
+
+pub fn remove_liquidity(
+    ctx: Context<LiquidityOperation>,
+    lp_amount: u64,
+    min_token_amount: u64,
+    min_sol_amount: u64,
+) -> Result<()> {
+    require!(
+        lp_amount <= ctx.accounts.provider_lp_ata.amount,
+        error::ErrorCode::NotEnoughBalance
+    );
+
+    let curr_token_in_vault = ctx.accounts.token_vault.amount;
+    let curr_sol_in_vault = ctx.accounts.sol_vault.amount;
+
+    let (token_withdraw_amount, sol_withdraw_amount) = math::withdrawal_amounts(
+        lp_amount,
+        curr_token_in_vault,
+        curr_sol_in_vault,
+        ctx.accounts.lp_mint.supply,
+    )?;
+
+    require!(
+        token_withdraw_amount >= min_token_amount,
+        error::ErrorCode::SlippageExceeded
+    );
+    require!(
+        sol_withdraw_amount >= min_sol_amount,
+        error::ErrorCode::SlippageExceeded
+    );
+
+    utils::burn_cpi(
+        lp_amount,
+        ctx.accounts.provider.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.provider_lp_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    let state_bump_bytes = ctx.accounts.market_state.state_bump.to_le_bytes();
+    let inner = vec![
+        b"market-state".as_ref(),
+        ctx.accounts.market_state.owner.as_ref(),
+        state_bump_bytes.as_ref()
+    ];
+    let outer = vec![inner.as_slice()];
+
+    utils::secure_transfer_cpi(
+        token_withdraw_amount,
+        ctx.accounts.market_state.to_account_info(),
+        ctx.accounts.token_vault.to_account_info(),
+        ctx.accounts.provider_token_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    utils::secure_transfer_cpi(
+        sol_withdraw_amount,
+        ctx.accounts.market_state.to_account_info(),
         ctx.accounts.sol_vault.to_account_info(),
+        ctx.accounts.provider_sol_ata.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         outer.as_ref(),
     )?;
 
+    emit!(events::LiquidityRemoved {
+        market_state: ctx.accounts.market_state.key(),
+        provider: ctx.accounts.provider.key(),
+        lp_amount,
+        token_amount: token_withdraw_amount,
+        sol_amount: sol_withdraw_amount,
+        token_vault_balance: curr_token_in_vault - token_withdraw_amount,
+        sol_vault_balance: curr_sol_in_vault - sol_withdraw_amount,
+    });
+
     Ok(())
 }
 
@@ -69,17 +202,24 @@ pub struct LiquidityOperation<'info> {
     #[account(mut, 
         seeds = [
             b"market-state".as_ref(), 
-            beneficiary.key().as_ref()
+            market_state.owner.as_ref()
         ],
         bump = market_state.state_bump
     )]
     pub market_state: Account<'info, state::MarketState>,
 
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, state::ProgramConfig>,
+
     #[account(mut, 
         seeds = [
             b"token-vault".as_ref(), 
             market_state.key().as_ref(),
-            beneficiary.key().as_ref()
+            market_state.owner.as_ref()
         ], 
         bump = market_state.token_vault_bump,
         )]
@@ -89,19 +229,43 @@ pub struct LiquidityOperation<'info> {
         seeds = [
             b"sol-vault".as_ref(), 
             market_state.key().as_ref(),
-            beneficiary.key().as_ref()
+            market_state.owner.as_ref()
         ],
         bump = market_state.sol_vault_bump,
         )]
     pub sol_vault: Account<'info, TokenAccount>,
 
-    #[account(mut, token::authority = beneficiary)]
-    pub beneficiary_token_ata: Account<'info, TokenAccount>,
+    #[account(mut, 
+        seeds = [
+            b"lp-mint".as_ref(), 
+            market_state.key().as_ref(),
+            market_state.owner.as_ref()
+        ],
+        bump = market_state.lp_mint_bump,
+        )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, 
+        seeds = [
+            b"lp-lock".as_ref(), 
+            market_state.key().as_ref(),
+            market_state.owner.as_ref()
+        ],
+        bump = market_state.lp_lock_bump,
+        )]
+    pub lp_lock: Account<'info, TokenAccount>,
+
+    #[account(mut, token::authority = provider)]
+    pub provider_token_ata: Account<'info, TokenAccount>,
+
+    #[account(mut, token::authority = provider)]
+    pub provider_sol_ata: Account<'info, TokenAccount>,
 
-    #[account(mut, token::authority = beneficiary)]
-    pub beneficiary_sol_ata: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = lp_mint, token::authority = provider)]
+    pub provider_lp_ata: Account<'info, TokenAccount>,
 
-    pub beneficiary: Signer<'info>,
+    // anyone holding the tokens or the LP shares, not only the market owner
+    pub provider: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
 }
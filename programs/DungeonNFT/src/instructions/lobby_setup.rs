@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+pub fn lobby_setup(
+    ctx: Context<LobbySetup>,
+    lobby_id: u64,
+    entry_stake: u64,
+    max_players: u8,
+    payout_bps: Vec<u16>,
+) -> Result<()> {
+    require!(
+        max_players >= 2 && max_players as usize <= state::LobbyState::MAX_PLAYERS,
+        error::ErrorCode::InvalidLobbySize
+    );
+    require!(
+        !payout_bps.is_empty()
+            && payout_bps.len() <= state::LobbyState::MAX_WINNERS
+            && payout_bps.len() <= max_players as usize
+            && payout_bps.iter().map(|bps| *bps as u64).sum::<u64>() == utils::BASIS_POINTS,
+        error::ErrorCode::InvalidPayoutTable
+    );
+
+    let lobby = &mut ctx.accounts.lobby_state;
+
+    lobby.creator = ctx.accounts.creator.key().clone();
+    lobby.mint_of_token = ctx.accounts.mint_of_token.key().clone();
+    lobby.lobby_vault = ctx.accounts.lobby_vault.key().clone();
+
+    lobby.lobby_id = lobby_id;
+    lobby.entry_stake = entry_stake;
+    lobby.max_players = max_players;
+    lobby.players = Vec::new();
+    lobby.payout_bps = payout_bps;
+    lobby.fee_bps = ctx.accounts.program_config.fee_bps;
+    // the creator has the settlement timeout to fill and lock the lobby
+    lobby.deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.program_config.settlement_timeout)
+        .unwrap();
+
+    lobby.state_bump = *ctx.bumps.get("lobby_state").unwrap();
+    lobby.vault_bump = *ctx.bumps.get("lobby_vault").unwrap();
+
+    msg!(
+        "Opened lobby {} for up to {} players at {} tokens each",
+        lobby_id,
+        max_players,
+        entry_stake
+    );
+
+    lobby.stage = utils::LobbyStage::Open.to_code();
+
+    emit!(events::LobbyCreated {
+        lobby_state: lobby.key(),
+        creator: lobby.creator,
+        mint_of_token: lobby.mint_of_token,
+        lobby_vault: lobby.lobby_vault,
+        lobby_id,
+        entry_stake,
+        max_players,
+        payout_bps: lobby.payout_bps.clone(),
+        fee_bps: lobby.fee_bps,
+        deadline: lobby.deadline,
+        stage: lobby.stage,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(lobby_id: u64)]
+pub struct LobbySetup<'info> {
+    #[account(
+        init,
+        space = 8 + state::LobbyState::LEN,
+        payer = creator,
+        seeds = [
+            b"lobby-state".as_ref(),
+            creator.key().as_ref(),
+            mint_of_token.key().as_ref(),
+            lobby_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    lobby_state: Account<'info, state::LobbyState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            b"lobby-vault".as_ref(),
+            lobby_state.key().as_ref(),
+        ],
+        bump,
+        token::mint = mint_of_token,
+        token::authority = lobby_state
+    )]
+    lobby_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+
+    rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+pub fn lock_lobby(ctx: Context<LockLobby>) -> Result<()> {
+    utils::LobbyStage::transition(
+        &mut ctx.accounts.lobby_state.stage,
+        utils::LobbyStage::Locked,
+    )?;
+
+    let lobby = &mut ctx.accounts.lobby_state;
+
+    // every place in the payout table needs somebody to fill it
+    require!(
+        lobby.players.len() >= 2 && lobby.players.len() >= lobby.payout_bps.len(),
+        error::ErrorCode::InvalidLobbySize
+    );
+
+    // and the referee as long again to settle it
+    lobby.deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.program_config.settlement_timeout)
+        .unwrap();
+
+    emit!(events::LobbyLocked {
+        lobby_state: lobby.key(),
+        players: lobby.players.len() as u8,
+        deadline: lobby.deadline,
+        stage: lobby.stage,
+    });
+
+    msg!("Locked the lobby with {} players", lobby.players.len());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LockLobby<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-state".as_ref(),
+            lobby_state.creator.as_ref(),
+            lobby_state.mint_of_token.as_ref(),
+            lobby_state.lobby_id.to_le_bytes().as_ref(),
+        ],
+        bump = lobby_state.state_bump,
+        constraint = lobby_state.creator == creator.key() @ error::ErrorCode::UnauthorizedCreator
+    )]
+    lobby_state: Account<'info, state::LobbyState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    creator: Signer<'info>,
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state};
+
+// gives a market migrated from before LP shares its LP mint and lock. with no shares out the
+// next add_liquidity is a first deposit, so the house gets shares for the legacy reserves too
+pub fn lp_setup(ctx: Context<LpSetup>) -> Result<()> {
+    let market_state = &mut ctx.accounts.market_state;
+
+    market_state.lp_mint_bump = *ctx.bumps.get("lp_mint").unwrap();
+    market_state.lp_lock_bump = *ctx.bumps.get("lp_lock").unwrap();
+    market_state.has_lp_mint = true;
+    market_state.owner = ctx.accounts.beneficiary.key();
+
+    emit!(events::MarketLpSetup {
+        market_state: market_state.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        lp_mint: ctx.accounts.lp_mint.key(),
+        lp_lock: ctx.accounts.lp_lock.key(),
+    });
+
+    msg!("Set up the LP mint {} for a legacy market", ctx.accounts.lp_mint.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LpSetup<'info> {
+    // markets opened by amm_setup got their LP mint with it
+    #[account(
+        mut,
+        seeds = [
+            b"market-state".as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump = market_state.state_bump,
+        constraint = !market_state.has_lp_mint @ error::ErrorCode::LpMintExists
+    )]
+    pub market_state: Account<'info, state::MarketState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        seeds = [
+            b"token-vault".as_ref(),
+            market_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump = market_state.token_vault_bump,
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    #[account(address = token_vault.mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = beneficiary,
+        seeds = [
+            b"lp-mint".as_ref(),
+            market_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump,
+        mint::decimals = token_mint.decimals,
+        mint::authority = market_state
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = beneficiary,
+        seeds = [
+            b"lp-lock".as_ref(),
+            market_state.key().as_ref(),
+            beneficiary.key().as_ref()
+        ],
+        bump,
+        token::mint = lp_mint,
+        token::authority = market_state
+    )]
+    pub lp_lock: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
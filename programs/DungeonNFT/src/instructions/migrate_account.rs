@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{error, events, state, utils};
+
+// grows a TransactionState or MarketState written before the version byte to the current layout,
+// the admin covers the extra rent
+pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+    require!(
+        account.owner == ctx.program_id,
+        error::ErrorCode::UnknownAccountLayout
+    );
+
+    let (migrated, version) = {
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= 8, error::ErrorCode::UnknownAccountLayout);
+
+        let (discriminator, body) = data.split_at(8);
+        if discriminator == state::TransactionState::discriminator() {
+            (
+                upgrade::<state::TransactionStateV0, state::TransactionState>(
+                    body,
+                    state::TransactionStateV0::LEN,
+                    state::TransactionState::LEN,
+                )?,
+                state::TransactionState::VERSION,
+            )
+        } else if discriminator == state::MarketState::discriminator() {
+            (
+                upgrade::<state::MarketStateV0, state::MarketState>(
+                    body,
+                    state::MarketStateV0::LEN,
+                    state::MarketState::LEN,
+                )?,
+                state::MarketState::VERSION,
+            )
+        } else {
+            return err!(error::ErrorCode::UnknownAccountLayout);
+        }
+    };
+
+    let rent_difference = Rent::get()?
+        .minimum_balance(migrated.len())
+        .saturating_sub(account.lamports());
+
+    if rent_difference > 0 {
+        utils::system_transfer_cpi(
+            rent_difference,
+            ctx.accounts.admin.to_account_info(),
+            account.clone(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    account.realloc(migrated.len(), false)?;
+    account.try_borrow_mut_data()?.copy_from_slice(&migrated);
+
+    emit!(events::AccountMigrated {
+        account: account.key(),
+        version,
+        space: migrated.len() as u64,
+    });
+
+    msg!(
+        "Migrated {} to version {}, paid {} lamports of rent",
+        account.key(),
+        version,
+        rent_difference
+    );
+
+    Ok(())
+}
+
+// only an account of exactly the old allocation is on the legacy layout, it may carry
+// padding past the fields. the migrated account gets the full space of the new layout
+fn upgrade<Legacy, Current>(body: &[u8], legacy_len: usize, current_len: usize) -> Result<Vec<u8>>
+where
+    Legacy: AnchorDeserialize,
+    Current: From<Legacy> + AccountSerialize,
+{
+    require!(
+        body.len() != current_len,
+        error::ErrorCode::AccountAlreadyMigrated
+    );
+    require!(
+        body.len() == legacy_len,
+        error::ErrorCode::UnknownAccountLayout
+    );
+
+    let legacy = Legacy::deserialize(&mut &body[..])
+        .map_err(|_| error!(error::ErrorCode::UnknownAccountLayout))?;
+
+    let mut migrated = Vec::new();
+    Current::from(legacy).try_serialize(&mut migrated)?;
+    migrated.resize(8 + current_len, 0);
+
+    Ok(migrated)
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: The old layout doesn't deserialize, so the owner and discriminator are checked by hand
+    #[account(mut)]
+    account: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn mint_registry_setup(ctx: Context<MintRegistrySetup>) -> Result<()> {
+    let registry = &mut ctx.accounts.mint_registry;
+
+    registry.entries = Vec::new();
+    registry.state_bump = *ctx.bumps.get("mint_registry").unwrap();
+
+    emit!(events::MintRegistryCreated {
+        mint_registry: registry.key(),
+    });
+
+    msg!("Initialized an empty mint registry");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MintRegistrySetup<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        init,
+        space = 8 + state::MintRegistry::LEN,
+        payer = admin,
+        seeds = [b"mint-registry".as_ref()],
+        bump
+    )]
+    mint_registry: Account<'info, state::MintRegistry>,
+
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
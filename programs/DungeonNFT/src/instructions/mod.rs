@@ -1,20 +1,146 @@
 pub mod transaction_setup;
 pub use transaction_setup::*;
 
-pub mod deposit_by_both_parties;
-pub use deposit_by_both_parties::*;
+pub mod deposit_by_player;
+pub use deposit_by_player::*;
+
+pub mod deposit_by_house;
+pub use deposit_by_house::*;
 
 pub mod transfer_to_winner;
 pub use transfer_to_winner::*;
 
+pub mod split_settlement;
+pub use split_settlement::*;
+
+pub mod raise_dispute;
+pub use raise_dispute::*;
+
+pub mod resolve_dispute;
+pub use resolve_dispute::*;
+
+pub mod finalize_settlement;
+pub use finalize_settlement::*;
+
 pub mod pullback;
 pub use pullback::*;
 
+pub mod cancel_transaction;
+pub use cancel_transaction::*;
+
+pub mod close_transaction;
+pub use close_transaction::*;
+
+pub mod sol_transaction_setup;
+pub use sol_transaction_setup::*;
+
+pub mod sol_deposit_by_player;
+pub use sol_deposit_by_player::*;
+
+pub mod sol_deposit_by_house;
+pub use sol_deposit_by_house::*;
+
+pub mod sol_transfer_to_winner;
+pub use sol_transfer_to_winner::*;
+
+pub mod sol_split_settlement;
+pub use sol_split_settlement::*;
+
+pub mod sol_finalize_settlement;
+pub use sol_finalize_settlement::*;
+
+pub mod sol_pullback;
+pub use sol_pullback::*;
+
+pub mod sol_cancel_transaction;
+pub use sol_cancel_transaction::*;
+
+pub mod leaderboard_setup;
+pub use leaderboard_setup::*;
+
+pub mod update_leaderboard;
+pub use update_leaderboard::*;
+
+pub mod lobby_setup;
+pub use lobby_setup::*;
+
+pub mod join_lobby;
+pub use join_lobby::*;
+
+pub mod lock_lobby;
+pub use lock_lobby::*;
+
+pub mod settle_lobby;
+pub use settle_lobby::*;
+
+pub mod cancel_lobby;
+pub use cancel_lobby::*;
+
+pub mod refund_lobby;
+pub use refund_lobby::*;
+
 pub mod amm_setup;
 pub use amm_setup::*;
 
+pub mod lp_setup;
+pub use lp_setup::*;
+
 pub mod liquidity;
 pub use liquidity::*;
 
 pub mod swap_tokens;
 pub use swap_tokens::*;
+
+pub mod config_setup;
+pub use config_setup::*;
+
+pub mod set_house;
+pub use set_house::*;
+
+pub mod set_paused;
+pub use set_paused::*;
+
+pub mod propose_admin;
+pub use propose_admin::*;
+
+pub mod accept_admin;
+pub use accept_admin::*;
+
+pub mod migrate_account;
+pub use migrate_account::*;
+
+pub mod legacy_pullback;
+pub use legacy_pullback::*;
+
+pub mod set_referee;
+pub use set_referee::*;
+
+pub mod set_settlement_timeout;
+pub use set_settlement_timeout::*;
+
+pub mod set_dispute_policy;
+pub use set_dispute_policy::*;
+
+pub mod set_fee;
+pub use set_fee::*;
+
+pub mod mint_registry_setup;
+pub use mint_registry_setup::*;
+
+pub mod register_mint;
+pub use register_mint::*;
+
+pub mod deregister_mint;
+pub use deregister_mint::*;
+
+pub mod treasury_setup;
+pub use treasury_setup::*;
+
+pub mod withdraw_treasury;
+pub use withdraw_treasury::*;
+
+pub mod sol_treasury_setup;
+pub use sol_treasury_setup::*;
+
+pub mod sol_withdraw_treasury;
+pub use sol_withdraw_treasury::*;
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    // the current admin stays in charge until the new one accepts
+    ctx.accounts.program_config.pending_admin = new_admin;
+
+    emit!(events::AdminProposed {
+        program_config: ctx.accounts.program_config.key(),
+        admin: ctx.accounts.program_config.admin,
+        pending_admin: new_admin,
+    });
+
+    msg!("Proposed {} as the new admin", new_admin);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
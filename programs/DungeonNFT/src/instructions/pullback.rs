@@ -1,34 +1,40 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::{error, state, utils};
+use crate::{error, events, state, utils};
 
 pub fn pull_back(ctx: Context<PullBack>) -> Result<()> {
-    if utils::Stage::from(ctx.accounts.transaction_state.stage)? != utils::Stage::FundsDeposited {
-        msg!(
-            "utils::Stage is invalid, state stage is {}",
-            ctx.accounts.transaction_state.stage
-        );
-        return Err(error::ErrorCode::StageInvalid.into());
-    }
-
-    assert!(
-        ctx.accounts.escrow_account.amount == 2 * ctx.accounts.transaction_state.amount_of_tokens
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::Expired,
+    )?;
+
+    // before the deadline only the referee may settle, afterwards anyone can refund
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.transaction_state.deadline,
+        error::ErrorCode::DeadlineNotReached
     );
 
+    let residue = utils::residue(
+        ctx.accounts.escrow_account.amount,
+        ctx.accounts.transaction_state.pot(),
+    )?;
+
     let mint_of_token_public_key = ctx.accounts.mint_of_token.key().clone();
+    let session_id_bytes = ctx.accounts.transaction_state.session_id.to_le_bytes();
     let state_bump_bytes = ctx.accounts.transaction_state.state_bump.to_le_bytes();
     let inner = vec![
         b"transaction-state".as_ref(),
         ctx.accounts.player.key.as_ref(),
         ctx.accounts.beneficiary.key.as_ref(),
         mint_of_token_public_key.as_ref(),
+        session_id_bytes.as_ref(),
         state_bump_bytes.as_ref(),
     ];
     let outer = vec![inner.as_slice()];
 
     utils::secure_transfer_cpi(
-        ctx.accounts.transaction_state.amount_of_tokens,
+        ctx.accounts.transaction_state.player_stake,
         ctx.accounts.transaction_state.to_account_info(),
         ctx.accounts.escrow_account.to_account_info(),
         ctx.accounts
@@ -38,8 +44,9 @@ pub fn pull_back(ctx: Context<PullBack>) -> Result<()> {
         outer.as_ref(),
     )?;
 
+    // whatever was sent on top of the stakes goes to the house along with its own
     utils::secure_transfer_cpi(
-        ctx.accounts.transaction_state.amount_of_tokens,
+        ctx.accounts.transaction_state.house_stake + residue,
         ctx.accounts.transaction_state.to_account_info(),
         ctx.accounts.escrow_account.to_account_info(),
         ctx.accounts
@@ -57,6 +64,17 @@ pub fn pull_back(ctx: Context<PullBack>) -> Result<()> {
         outer.as_ref(),
     )?;
 
+    emit!(events::EscrowPulledBack {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        player: ctx.accounts.player.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        player_refund: ctx.accounts.transaction_state.player_stake,
+        house_refund: ctx.accounts.transaction_state.house_stake,
+        residue,
+        escrow_balance: 0,
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
     Ok(())
 }
 
@@ -68,20 +86,28 @@ pub struct PullBack<'info> {
             b"transaction-state".as_ref(),
             player.key().as_ref(),
             beneficiary.key().as_ref(),
-            mint_of_token.key().as_ref()
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
         ],
-        bump = transaction_state.state_bump,
-        close = player
+        bump = transaction_state.state_bump
     )]
     transaction_state: Account<'info, state::TransactionState>,
 
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
     #[account(
         mut,
         seeds = [
             b"escrow-account".as_ref(),
             player.key().as_ref(),
             beneficiary.key().as_ref(),
-            mint_of_token.key().as_ref()
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
         ],
         bump = transaction_state.escrow_bump
     )]
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+// freezes the escrow until the arbiter rules on the submitted result
+pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::Disputed,
+    )?;
+
+    require!(
+        ctx.accounts
+            .transaction_state
+            .can_dispute(ctx.accounts.disputer.key),
+        error::ErrorCode::UnauthorizedDisputer
+    );
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.transaction_state.challenge_deadline,
+        error::ErrorCode::ChallengeWindowClosed
+    );
+
+    // the arbiter gets as long as the referee had, an unanswered dispute lets the verdict stand
+    let state = &mut ctx.accounts.transaction_state;
+    state.dispute_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.program_config.settlement_timeout)
+        .unwrap();
+
+    emit!(events::EscrowDisputed {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        disputer: ctx.accounts.disputer.key(),
+        dispute_deadline: ctx.accounts.transaction_state.dispute_deadline,
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
+    msg!(
+        "{} disputed the result of session {}",
+        ctx.accounts.disputer.key(),
+        ctx.accounts.transaction_state.session_id
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    // shared by token and lamport escrows, only this program can create a TransactionState
+    #[account(mut)]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    disputer: Signer<'info>,
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+use super::cancel_lobby::refund_entries;
+
+// remaining accounts are the token accounts of every participant, in the order they joined
+pub fn refund_lobby<'info>(ctx: Context<'_, '_, '_, 'info, RefundLobby<'info>>) -> Result<()> {
+    utils::LobbyStage::transition(
+        &mut ctx.accounts.lobby_state.stage,
+        utils::LobbyStage::Expired,
+    )?;
+
+    // a lobby the creator never locked or the referee never settled, anyone can refund it
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.lobby_state.deadline,
+        error::ErrorCode::DeadlineNotReached
+    );
+
+    let residue = refund_entries(
+        &ctx.accounts.lobby_state,
+        &ctx.accounts.lobby_vault,
+        ctx.accounts.treasury_vault.to_account_info(),
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_fees_collected = treasury_state
+        .total_fees_collected
+        .checked_add(residue)
+        .unwrap();
+
+    let lobby = &ctx.accounts.lobby_state;
+    emit!(events::LobbyRefunded {
+        lobby_state: lobby.key(),
+        players: lobby.players.len() as u8,
+        entry_stake: lobby.entry_stake,
+        residue,
+        treasury_fees_collected: treasury_state.total_fees_collected,
+        stage: lobby.stage,
+    });
+
+    msg!(
+        "Refunded {} players of the expired lobby",
+        ctx.accounts.lobby_state.players.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefundLobby<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-state".as_ref(),
+            lobby_state.creator.as_ref(),
+            lobby_state.mint_of_token.as_ref(),
+            lobby_state.lobby_id.to_le_bytes().as_ref(),
+        ],
+        bump = lobby_state.state_bump
+    )]
+    lobby_state: Account<'info, state::LobbyState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-vault".as_ref(),
+            lobby_state.key().as_ref(),
+        ],
+        bump = lobby_state.vault_bump
+    )]
+    lobby_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-state".as_ref(),
+            lobby_state.mint_of_token.as_ref()
+        ],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-vault".as_ref(),
+            lobby_state.mint_of_token.as_ref()
+        ],
+        bump = treasury_state.vault_bump,
+    )]
+    treasury_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: Only receives the rent of the closed vault, the address is checked against the lobby
+    #[account(mut, address = lobby_state.creator)]
+    creator: AccountInfo<'info>,
+
+    token_program: Program<'info, Token>,
+}
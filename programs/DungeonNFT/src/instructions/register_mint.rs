@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{error, events, state};
+
+// adds the mint to the registry, or updates its limits when it's already listed
+pub fn register_mint(
+    ctx: Context<RegisterMint>,
+    decimals: u8,
+    min_stake: u64,
+    max_stake: u64,
+) -> Result<()> {
+    require!(
+        decimals == ctx.accounts.mint_of_token.decimals,
+        error::ErrorCode::MintDecimalsMismatch
+    );
+    require!(
+        min_stake > 0 && min_stake <= max_stake,
+        error::ErrorCode::InvalidStakeLimits
+    );
+
+    let entry = state::MintEntry {
+        mint: ctx.accounts.mint_of_token.key().clone(),
+        decimals,
+        min_stake,
+        max_stake,
+    };
+
+    let registry = &mut ctx.accounts.mint_registry;
+    match registry
+        .entries
+        .iter_mut()
+        .find(|existing| existing.mint == entry.mint)
+    {
+        Some(existing) => *existing = entry,
+        None => {
+            require!(
+                registry.entries.len() < state::MintRegistry::MAX_MINTS,
+                error::ErrorCode::MintRegistryFull
+            );
+            registry.entries.push(entry);
+        }
+    }
+
+    emit!(events::MintRegistered {
+        mint_registry: registry.key(),
+        mint: ctx.accounts.mint_of_token.key(),
+        decimals,
+        min_stake,
+        max_stake,
+    });
+
+    msg!(
+        "Registered mint {} with stakes between {} and {}",
+        ctx.accounts.mint_of_token.key(),
+        min_stake,
+        max_stake
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterMint<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-registry".as_ref()],
+        bump = mint_registry.state_bump,
+    )]
+    mint_registry: Account<'info, state::MintRegistry>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    admin: Signer<'info>,
+}
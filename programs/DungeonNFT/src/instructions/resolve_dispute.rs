@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+// uphold keeps the referee's verdict, otherwise the shares of both parties are swapped
+pub fn resolve_dispute(ctx: Context<ResolveDispute>, uphold: bool) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::ResultSubmitted,
+    )?;
+
+    // past the deadline the referee's verdict already stands and can be finalized
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.transaction_state.dispute_deadline,
+        error::ErrorCode::DeadlinePassed
+    );
+
+    let state = &mut ctx.accounts.transaction_state;
+    if !uphold {
+        let player_share = state.player_share;
+        state.player_share = state.beneficiary_share;
+        state.beneficiary_share = player_share;
+    }
+
+    // the ruling is final, so the escrow can be finalized straight away
+    state.challenge_deadline = Clock::get()?.unix_timestamp;
+
+    emit!(events::EscrowDisputeResolved {
+        transaction_state: state.key(),
+        arbiter: ctx.accounts.arbiter.key(),
+        upheld: uphold,
+        player_share: state.player_share,
+        beneficiary_share: state.beneficiary_share,
+        challenge_deadline: state.challenge_deadline,
+        stage: state.stage,
+    });
+
+    msg!(
+        "Arbiter {} the result of session {}",
+        if uphold { "upheld" } else { "overturned" },
+        state.session_id
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    // shared by token and lamport escrows, only this program can create a TransactionState
+    #[account(mut)]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.arbiter == arbiter.key() @ error::ErrorCode::UnauthorizedArbiter,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    arbiter: Signer<'info>,
+}
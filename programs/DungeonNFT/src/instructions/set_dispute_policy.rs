@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn set_dispute_policy(
+    ctx: Context<SetDisputePolicy>,
+    arbiter: Pubkey,
+    dispute_window: i64,
+) -> Result<()> {
+    require!(dispute_window >= 0, error::ErrorCode::InvalidTimeout);
+
+    let config = &mut ctx.accounts.program_config;
+    config.arbiter = arbiter;
+    config.dispute_window = dispute_window;
+
+    emit!(events::ConfigUpdated::new(
+        ctx.accounts.program_config.key(),
+        &ctx.accounts.program_config
+    ));
+
+    msg!(
+        "Arbiter changed to {} with a {} second dispute window",
+        arbiter,
+        dispute_window
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetDisputePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+    require!(
+        fee_bps <= utils::MAX_FEE_BPS,
+        error::ErrorCode::InvalidFee
+    );
+
+    ctx.accounts.program_config.fee_bps = fee_bps;
+
+    emit!(events::ConfigUpdated::new(
+        ctx.accounts.program_config.key(),
+        &ctx.accounts.program_config
+    ));
+
+    msg!("House fee changed to {} basis points", fee_bps);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn set_house(ctx: Context<SetHouse>, house: Pubkey) -> Result<()> {
+    ctx.accounts.program_config.house = house;
+
+    emit!(events::ConfigUpdated::new(
+        ctx.accounts.program_config.key(),
+        &ctx.accounts.program_config
+    ));
+
+    msg!("House changed to {}", house);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetHouse<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.program_config.paused = paused;
+
+    emit!(events::PauseChanged {
+        program_config: ctx.accounts.program_config.key(),
+        paused,
+    });
+
+    msg!("Program paused: {}", paused);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
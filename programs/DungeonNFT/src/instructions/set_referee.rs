@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn set_referee(ctx: Context<SetReferee>, referee: Pubkey) -> Result<()> {
+    ctx.accounts.program_config.referee = referee;
+
+    emit!(events::ConfigUpdated::new(
+        ctx.accounts.program_config.key(),
+        &ctx.accounts.program_config
+    ));
+
+    msg!("Referee changed to {}", referee);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetReferee<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+pub fn set_settlement_timeout(
+    ctx: Context<SetSettlementTimeout>,
+    settlement_timeout: i64,
+) -> Result<()> {
+    require!(settlement_timeout > 0, error::ErrorCode::InvalidTimeout);
+
+    ctx.accounts.program_config.settlement_timeout = settlement_timeout;
+
+    emit!(events::ConfigUpdated::new(
+        ctx.accounts.program_config.key(),
+        &ctx.accounts.program_config
+    ));
+
+    msg!("Settlement timeout changed to {} seconds", settlement_timeout);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetSettlementTimeout<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    admin: Signer<'info>,
+}
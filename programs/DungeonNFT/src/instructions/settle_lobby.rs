@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+use super::update_leaderboard::record_score;
+
+// remaining accounts are the token accounts of the winners, in the same order as winners,
+// followed by the profiles of every participant, in the order they joined
+pub fn settle_lobby<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleLobby<'info>>,
+    winners: Vec<Pubkey>,
+    expiry: i64,
+) -> Result<()> {
+    utils::LobbyStage::transition(
+        &mut ctx.accounts.lobby_state.stage,
+        utils::LobbyStage::Settled,
+    )?;
+
+    let lobby = &ctx.accounts.lobby_state;
+
+    require!(
+        winners.len() == lobby.payout_bps.len()
+            && ctx.remaining_accounts.len() == winners.len() + lobby.players.len(),
+        error::ErrorCode::InvalidLobbyWinners
+    );
+    for (place, winner) in winners.iter().enumerate() {
+        require!(
+            lobby.players.contains(winner) && !winners[..place].contains(winner),
+            error::ErrorCode::InvalidLobbyWinners
+        );
+    }
+
+    require!(
+        Clock::get()?.unix_timestamp <= expiry,
+        error::ErrorCode::AttestationExpired
+    );
+
+    // past the deadline the entries can only be refunded through refund_lobby
+    require!(
+        Clock::get()?.unix_timestamp < lobby.deadline,
+        error::ErrorCode::DeadlinePassed
+    );
+
+    let lobby_message = utils::lobby_message(&lobby.key(), &winners, expiry);
+    utils::verify_referee_attestation(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.program_config.referee,
+        &lobby_message,
+    )?;
+
+    let lobby_id_bytes = lobby.lobby_id.to_le_bytes();
+    let state_bump_bytes = lobby.state_bump.to_le_bytes();
+    let inner = vec![
+        b"lobby-state".as_ref(),
+        lobby.creator.as_ref(),
+        lobby.mint_of_token.as_ref(),
+        lobby_id_bytes.as_ref(),
+        state_bump_bytes.as_ref(),
+    ];
+    let outer = vec![inner.as_slice()];
+
+    let pot = lobby.pot();
+    let fee = utils::rake(pot, ctx.accounts.lobby_state.fee_bps);
+    let prize_pool = pot - fee;
+    let residue = utils::residue(ctx.accounts.lobby_vault.amount, pot)?;
+
+    // anything sent to the vault on top of the entries goes to the treasury with the rake
+    utils::secure_transfer_cpi(
+        fee + residue,
+        lobby.to_account_info(),
+        ctx.accounts.lobby_vault.to_account_info(),
+        ctx.accounts.treasury_vault.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    // every place is rounded down, the dust goes to the first place
+    let mut prizes: Vec<u64> = lobby
+        .payout_bps
+        .iter()
+        .map(|bps| ((prize_pool as u128) * (*bps as u128) / (utils::BASIS_POINTS as u128)) as u64)
+        .collect();
+    prizes[0] += prize_pool - prizes.iter().sum::<u64>();
+
+    for (place, winner) in winners.iter().enumerate() {
+        let winner_token_account_info = &ctx.remaining_accounts[place];
+        let winner_token_account = Account::<TokenAccount>::try_from(winner_token_account_info)?;
+        require!(
+            winner_token_account.owner == *winner
+                && winner_token_account.mint == lobby.mint_of_token,
+            error::ErrorCode::InvalidLobbyAccount
+        );
+
+        utils::secure_transfer_cpi(
+            prizes[place],
+            lobby.to_account_info(),
+            ctx.accounts.lobby_vault.to_account_info(),
+            winner_token_account_info.clone(),
+            ctx.accounts.token_program.to_account_info(),
+            outer.as_ref(),
+        )?;
+    }
+
+    utils::close_account_cpi(
+        ctx.accounts.lobby_vault.to_account_info(),
+        ctx.accounts.creator.to_account_info(),
+        lobby.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    // a placed player wins if their cut of the pot before the rake beats the entry
+    let season = ctx
+        .accounts
+        .program_config
+        .active_season(Clock::get()?.unix_timestamp);
+    let mut leaderboard = season
+        .map(|_| Account::<state::Leaderboard>::try_from(&ctx.accounts.leaderboard))
+        .transpose()?;
+    let profile_infos = &ctx.remaining_accounts[winners.len()..];
+    for (player, profile_info) in lobby.players.iter().zip(profile_infos) {
+        let mut profile = Account::<state::PlayerProfile>::try_from(profile_info)?;
+        require!(profile.player == *player, error::ErrorCode::InvalidLobbyAccount);
+
+        let (awarded, payout) = match winners.iter().position(|winner| winner == player) {
+            Some(place) => (
+                ((pot as u128) * (lobby.payout_bps[place] as u128)
+                    / (utils::BASIS_POINTS as u128)) as u64,
+                prizes[place],
+            ),
+            None => (0, 0),
+        };
+        profile.record_game(
+            &lobby.mint_of_token,
+            lobby.entry_stake,
+            payout,
+            state::GameOutcome::of(awarded, lobby.entry_stake),
+            season,
+        );
+        if let Some(leaderboard) = leaderboard.as_mut() {
+            record_score(leaderboard, &profile);
+        }
+        profile.exit(ctx.program_id)?;
+    }
+    if let Some(leaderboard) = leaderboard {
+        leaderboard.exit(ctx.program_id)?;
+    }
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_fees_collected =
+        treasury_state.total_fees_collected.checked_add(fee + residue).unwrap();
+
+    emit!(events::LobbySettled {
+        lobby_state: ctx.accounts.lobby_state.key(),
+        winners: winners.clone(),
+        prizes: prizes.clone(),
+        fee,
+        residue,
+        treasury_fees_collected: treasury_state.total_fees_collected,
+        stage: ctx.accounts.lobby_state.stage,
+    });
+
+    msg!(
+        "Settled the lobby, {} tokens to {} winners and {} tokens to the treasury",
+        prize_pool,
+        winners.len(),
+        fee
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleLobby<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-state".as_ref(),
+            lobby_state.creator.as_ref(),
+            lobby_state.mint_of_token.as_ref(),
+            lobby_state.lobby_id.to_le_bytes().as_ref(),
+        ],
+        bump = lobby_state.state_bump
+    )]
+    lobby_state: Account<'info, state::LobbyState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"lobby-vault".as_ref(),
+            lobby_state.key().as_ref(),
+        ],
+        bump = lobby_state.vault_bump
+    )]
+    lobby_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-state".as_ref(),
+            lobby_state.mint_of_token.as_ref()
+        ],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-vault".as_ref(),
+            lobby_state.mint_of_token.as_ref()
+        ],
+        bump = treasury_state.vault_bump,
+    )]
+    treasury_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: The running season's board, it doesn't exist before the first season so it's
+    /// only deserialized while the season is active
+    #[account(
+        mut,
+        seeds = [
+            b"leaderboard".as_ref(),
+            program_config.season.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    leaderboard: AccountInfo<'info>,
+
+    /// CHECK: This is the instructions sysvar, the address constraint makes sure of it
+    #[account(address = sysvar::instructions::ID)]
+    instructions_sysvar: AccountInfo<'info>,
+
+    /// CHECK: Only receives the rent of the closed vault, the address is checked against the lobby
+    #[account(mut, address = lobby_state.creator)]
+    creator: AccountInfo<'info>,
+
+    token_program: Program<'info, Token>,
+}
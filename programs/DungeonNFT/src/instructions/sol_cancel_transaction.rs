@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+// refunds whoever already deposited, close_transaction hands the rent back afterwards
+pub fn sol_cancel_transaction(ctx: Context<SolCancelTransaction>) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::Cancelled,
+    )?;
+
+    let escrow = ctx.accounts.transaction_state.to_account_info();
+
+    if ctx.accounts.transaction_state.player_deposited {
+        utils::debit_lamports(
+            ctx.accounts.transaction_state.player_stake,
+            &escrow,
+            &ctx.accounts.player.to_account_info(),
+        )?;
+    }
+
+    if ctx.accounts.transaction_state.house_deposited {
+        utils::debit_lamports(
+            ctx.accounts.transaction_state.house_stake,
+            &escrow,
+            &ctx.accounts.beneficiary,
+        )?;
+    }
+
+    let state = &ctx.accounts.transaction_state;
+    emit!(events::EscrowCancelled {
+        transaction_state: state.key(),
+        player: state.player,
+        beneficiary: state.beneficiary,
+        player_refund: if state.player_deposited {
+            state.player_stake
+        } else {
+            0
+        },
+        house_refund: if state.house_deposited {
+            state.house_stake
+        } else {
+            0
+        },
+        residue: 0,
+        escrow_balance: 0,
+        stage: state.stage,
+    });
+
+    msg!(
+        "Cancelled session {} before it was fully funded",
+        ctx.accounts.transaction_state.session_id
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolCancelTransaction<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    // the player or the house, so neither stake is stuck when the other side never pays in
+    #[account(
+        constraint = authority.key() == player.key()
+            || authority.key() == beneficiary.key() @ error::ErrorCode::UnauthorizedCanceller
+    )]
+    authority: Signer<'info>,
+
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    beneficiary: AccountInfo<'info>,
+}
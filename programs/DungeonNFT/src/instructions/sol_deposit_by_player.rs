@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+pub fn sol_deposit_by_player(ctx: Context<SolDepositByPlayer>, player_stake: u64) -> Result<()> {
+    let state = &ctx.accounts.transaction_state;
+
+    utils::Stage::expect(state.stage, utils::Stage::Initialized)?;
+    require!(!state.player_deposited, error::ErrorCode::AlreadyDeposited);
+    // the stakes were fixed when the escrow was set up
+    require!(
+        player_stake == state.player_stake,
+        error::ErrorCode::StakeMismatch
+    );
+
+    utils::system_transfer_cpi(
+        player_stake,
+        ctx.accounts.player.to_account_info(),
+        ctx.accounts.transaction_state.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let state = &mut ctx.accounts.transaction_state;
+    state.player_deposited = true;
+
+    if state.is_funded() {
+        utils::Stage::transition(&mut state.stage, utils::Stage::FundsDeposited)?;
+        state.fee_bps = ctx.accounts.program_config.fee_bps;
+        state.deadline = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.program_config.settlement_timeout)
+            .unwrap();
+    }
+
+    emit!(events::EscrowDeposited {
+        transaction_state: state.key(),
+        depositor: ctx.accounts.player.key(),
+        amount: player_stake,
+        player_stake: state.player_stake,
+        house_stake: state.house_stake,
+        escrow_balance: state.deposited(),
+        deadline: state.deadline,
+        stage: state.stage,
+    });
+
+    msg!("Player funded the escrow with {} lamports", player_stake);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolDepositByPlayer<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(mut)]
+    player: Signer<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+}
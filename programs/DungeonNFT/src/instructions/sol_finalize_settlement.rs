@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+use super::update_leaderboard::record_score;
+
+// pays out the recorded verdict in lamports, the rent stays on the state until close_transaction
+pub fn sol_finalize_settlement(ctx: Context<SolFinalizeSettlement>) -> Result<()> {
+    let disputed =
+        utils::Stage::from(ctx.accounts.transaction_state.stage)? == utils::Stage::Disputed;
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::EscrowComplete,
+    )?;
+
+    // a dispute the arbiter didn't rule on in time leaves the referee's verdict in place
+    if disputed {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.transaction_state.dispute_deadline,
+            error::ErrorCode::DisputeUnresolved
+        );
+    } else {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.transaction_state.challenge_deadline,
+            error::ErrorCode::ChallengeWindowOpen
+        );
+    }
+
+    let pot = ctx.accounts.transaction_state.pot();
+    let fee = utils::rake(pot, ctx.accounts.transaction_state.fee_bps);
+    let (player_amount, beneficiary_amount) = utils::split_pot(
+        pot - fee,
+        ctx.accounts.transaction_state.player_share,
+        ctx.accounts.transaction_state.beneficiary_share,
+    )?;
+
+    let escrow = ctx.accounts.transaction_state.to_account_info();
+
+    utils::debit_lamports(fee, &escrow, &ctx.accounts.treasury_state.to_account_info())?;
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_fees_collected = treasury_state
+        .total_fees_collected
+        .checked_add(fee)
+        .unwrap();
+
+    utils::debit_lamports(player_amount, &escrow, &ctx.accounts.player)?;
+    utils::debit_lamports(beneficiary_amount, &escrow, &ctx.accounts.beneficiary)?;
+
+    // the player's share of the pot before the rake decides how the game went
+    let transaction_state = &ctx.accounts.transaction_state;
+    let (awarded, _) = utils::split_pot(
+        pot,
+        transaction_state.player_share,
+        transaction_state.beneficiary_share,
+    )?;
+    let season = ctx
+        .accounts
+        .program_config
+        .active_season(Clock::get()?.unix_timestamp);
+    ctx.accounts.player_profile.record_game(
+        &transaction_state.mint_of_token,
+        transaction_state.player_stake,
+        player_amount,
+        state::GameOutcome::of(awarded, transaction_state.player_stake),
+        season,
+    );
+
+    if season.is_some() {
+        let mut leaderboard =
+            Account::<state::Leaderboard>::try_from(&ctx.accounts.leaderboard)?;
+        record_score(&mut leaderboard, &ctx.accounts.player_profile);
+        leaderboard.exit(ctx.program_id)?;
+    }
+
+    emit!(events::EscrowSettled {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        player: ctx.accounts.player.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        mint_of_token: ctx.accounts.transaction_state.mint_of_token,
+        player_amount,
+        beneficiary_amount,
+        fee,
+        treasury_fees_collected: ctx.accounts.treasury_state.total_fees_collected,
+        // lamports sent on top stay on the state account until it's closed
+        residue: 0,
+        escrow_balance: 0,
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
+    msg!(
+        "Paid out {} lamports to the player, {} lamports to the beneficiary and {} lamports to the treasury",
+        player_amount,
+        beneficiary_amount,
+        fee
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolFinalizeSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"sol-treasury-state".as_ref()],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"player-profile".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump = player_profile.state_bump
+    )]
+    player_profile: Account<'info, state::PlayerProfile>,
+
+    /// CHECK: The running season's board, it doesn't exist before the first season so it's
+    /// only deserialized while the season is active
+    #[account(
+        mut,
+        seeds = [
+            b"leaderboard".as_ref(),
+            program_config.season.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    leaderboard: AccountInfo<'info>,
+
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    beneficiary: AccountInfo<'info>,
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+pub fn sol_pull_back(ctx: Context<SolPullBack>) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::Expired,
+    )?;
+
+    // before the deadline only the referee may settle, afterwards anyone can refund
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.transaction_state.deadline,
+        error::ErrorCode::DeadlineNotReached
+    );
+
+    let escrow = ctx.accounts.transaction_state.to_account_info();
+
+    utils::debit_lamports(
+        ctx.accounts.transaction_state.player_stake,
+        &escrow,
+        &ctx.accounts.player,
+    )?;
+
+    utils::debit_lamports(
+        ctx.accounts.transaction_state.house_stake,
+        &escrow,
+        &ctx.accounts.beneficiary,
+    )?;
+
+    emit!(events::EscrowPulledBack {
+        transaction_state: ctx.accounts.transaction_state.key(),
+        player: ctx.accounts.player.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        player_refund: ctx.accounts.transaction_state.player_stake,
+        house_refund: ctx.accounts.transaction_state.house_stake,
+        // lamports sent on top stay on the state account until it's closed
+        residue: 0,
+        escrow_balance: 0,
+        stage: ctx.accounts.transaction_state.stage,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolPullBack<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    beneficiary: AccountInfo<'info>,
+}
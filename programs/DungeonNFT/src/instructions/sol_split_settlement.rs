@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+
+use crate::{error, events, state, utils};
+
+// player_share and beneficiary_share are the two sides of the ratio, e.g. 70 and 30,
+// like sol_transfer_to_winner this only records the verdict for sol_finalize_settlement
+pub fn sol_split_settlement(
+    ctx: Context<SolSplitSettlement>,
+    player_share: u64,
+    beneficiary_share: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::ResultSubmitted,
+    )?;
+
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.transaction_state.deadline,
+        error::ErrorCode::DeadlinePassed
+    );
+
+    require!(
+        nonce == ctx.accounts.transaction_state.result_nonce,
+        error::ErrorCode::AttestationNonceMismatch
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= expiry,
+        error::ErrorCode::AttestationExpired
+    );
+
+    let split_message = utils::split_message(
+        &ctx.accounts.transaction_state.key(),
+        player_share,
+        beneficiary_share,
+        nonce,
+        expiry,
+    );
+    utils::verify_referee_attestation(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.program_config.referee,
+        &split_message,
+    )?;
+
+    // rejects a 0 : 0 ratio before anything is recorded
+    utils::split_pot(
+        ctx.accounts.transaction_state.pot(),
+        player_share,
+        beneficiary_share,
+    )?;
+
+    let state = &mut ctx.accounts.transaction_state;
+    state.player_share = player_share;
+    state.beneficiary_share = beneficiary_share;
+    state.challenge_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.program_config.dispute_window)
+        .unwrap();
+
+    emit!(events::EscrowResultSubmitted {
+        transaction_state: state.key(),
+        player_share: state.player_share,
+        beneficiary_share: state.beneficiary_share,
+        challenge_deadline: state.challenge_deadline,
+        stage: state.stage,
+    });
+
+    msg!(
+        "Referee split the pot {} : {}, disputes are open until {}",
+        player_share,
+        beneficiary_share,
+        state.challenge_deadline
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolSplitSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    /// CHECK: This is the instructions sysvar, the address constraint makes sure of it
+    #[account(address = sysvar::instructions::ID)]
+    instructions_sysvar: AccountInfo<'info>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+}
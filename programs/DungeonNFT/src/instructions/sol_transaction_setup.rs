@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+// same as transaction_setup, but the stakes are lamports kept on the state account itself
+pub fn sol_transaction_setup(
+    ctx: Context<SolTransactionSetup>,
+    player_stake: u64,
+    house_stake: u64,
+) -> Result<()> {
+    let counter = &mut ctx.accounts.session_counter;
+    let session_id = counter.next_session_id;
+
+    counter.player = ctx.accounts.player.key().clone();
+    counter.next_session_id = session_id.checked_add(1).unwrap();
+    counter.state_bump = *ctx.bumps.get("session_counter").unwrap();
+
+    let profile = &mut ctx.accounts.player_profile;
+    profile.player = ctx.accounts.player.key().clone();
+    profile.state_bump = *ctx.bumps.get("player_profile").unwrap();
+
+    let state_key = ctx.accounts.transaction_state.key().clone();
+    let state = &mut ctx.accounts.transaction_state;
+
+    state.player = ctx.accounts.player.key().clone();
+    state.beneficiary = ctx.accounts.beneficiary.key().clone();
+
+    state.mint_of_token = Pubkey::default();
+    state.session_id = session_id;
+    state.escrow_account = state_key;
+
+    state.player_stake = player_stake;
+    state.house_stake = house_stake;
+    state.player_deposited = false;
+    state.house_deposited = false;
+    state.result_nonce = Clock::get()?.slot;
+
+    state.state_bump = *ctx.bumps.get("transaction_state").unwrap();
+    state.escrow_bump = state.state_bump;
+    state.version = state::TransactionState::VERSION;
+
+    msg!("Initialized new lamport escrow for session {}", session_id);
+
+    state.stage = utils::Stage::Initialized.to_code();
+
+    emit!(events::EscrowCreated {
+        transaction_state: state.key(),
+        player: state.player,
+        beneficiary: state.beneficiary,
+        mint_of_token: state.mint_of_token,
+        escrow_account: state.escrow_account,
+        session_id,
+        player_stake,
+        house_stake,
+        result_nonce: state.result_nonce,
+        stage: state.stage,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolTransactionSetup<'info> {
+    #[account(
+        init_if_needed,
+        space = 8 + state::SessionCounter::LEN,
+        payer = player,
+        seeds = [
+            b"session-counter".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump
+    )]
+    session_counter: Account<'info, state::SessionCounter>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + state::PlayerProfile::LEN,
+        payer = player,
+        seeds = [
+            b"player-profile".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump
+    )]
+    player_profile: Account<'info, state::PlayerProfile>,
+
+    #[account(
+        init,
+        space = 8 + state::TransactionState::LEN,
+        payer = player,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            session_counter.next_session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused,
+        constraint = program_config.house == beneficiary.key() @ error::ErrorCode::UnauthorizedHouse
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(mut)]
+    player: Signer<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+
+use crate::{error, events, state, utils};
+
+// records the referee's verdict, the lamports are paid out by sol_finalize_settlement
+// once the dispute window has passed
+pub fn sol_transfer_to_winner(
+    ctx: Context<SolTransferToWinner>,
+    winner: Pubkey,
+    nonce: u64,
+    expiry: i64,
+) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::ResultSubmitted,
+    )?;
+
+    require!(
+        winner == ctx.accounts.transaction_state.player
+            || winner == ctx.accounts.transaction_state.beneficiary,
+        error::ErrorCode::InvalidWinner
+    );
+
+    // past the deadline the stakes can only be refunded through pullback
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.transaction_state.deadline,
+        error::ErrorCode::DeadlinePassed
+    );
+
+    // the result is only valid for the nonce handed out when this wager was set up,
+    // so a signature from an earlier wager between the same pair can't be replayed
+    require!(
+        nonce == ctx.accounts.transaction_state.result_nonce,
+        error::ErrorCode::AttestationNonceMismatch
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= expiry,
+        error::ErrorCode::AttestationExpired
+    );
+
+    let result_message = utils::result_message(
+        &ctx.accounts.transaction_state.key(),
+        &winner,
+        nonce,
+        expiry,
+    );
+    utils::verify_referee_attestation(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.program_config.referee,
+        &result_message,
+    )?;
+
+    let state = &mut ctx.accounts.transaction_state;
+    if winner == state.player {
+        state.player_share = 1;
+        state.beneficiary_share = 0;
+    } else {
+        state.player_share = 0;
+        state.beneficiary_share = 1;
+    }
+    state.challenge_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.program_config.dispute_window)
+        .unwrap();
+
+    emit!(events::EscrowResultSubmitted {
+        transaction_state: state.key(),
+        player_share: state.player_share,
+        beneficiary_share: state.beneficiary_share,
+        challenge_deadline: state.challenge_deadline,
+        stage: state.stage,
+    });
+
+    msg!(
+        "Referee picked {} as the winner, disputes are open until {}",
+        winner,
+        state.challenge_deadline
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolTransferToWinner<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"sol-transaction-state".as_ref(),
+            player.key().as_ref(),
+            beneficiary.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
+        ],
+        bump = transaction_state.state_bump
+    )]
+    transaction_state: Account<'info, state::TransactionState>,
+
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    /// CHECK: This is the instructions sysvar, the address constraint makes sure of it
+    #[account(address = sysvar::instructions::ID)]
+    instructions_sysvar: AccountInfo<'info>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    player: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    beneficiary: AccountInfo<'info>,
+}
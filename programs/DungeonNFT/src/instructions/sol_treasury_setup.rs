@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+// the lamport treasury keeps the fees on its own state account, so there is no vault
+pub fn sol_treasury_setup(ctx: Context<SolTreasurySetup>) -> Result<()> {
+    let treasury_key = ctx.accounts.treasury_state.key().clone();
+    let treasury_state = &mut ctx.accounts.treasury_state;
+
+    treasury_state.mint_of_token = Pubkey::default();
+    treasury_state.treasury_vault = treasury_key;
+    treasury_state.total_fees_collected = 0;
+    treasury_state.total_withdrawn = 0;
+
+    treasury_state.state_bump = *ctx.bumps.get("treasury_state").unwrap();
+    treasury_state.vault_bump = treasury_state.state_bump;
+
+    emit!(events::TreasuryCreated {
+        treasury_state: treasury_state.key(),
+        mint_of_token: treasury_state.mint_of_token,
+        treasury_vault: treasury_state.treasury_vault,
+    });
+
+    msg!("Initialized the lamport treasury");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolTreasurySetup<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        init,
+        space = 8 + state::TreasuryState::LEN,
+        payer = admin,
+        seeds = [b"sol-treasury-state".as_ref()],
+        bump
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    system_program: Program<'info, System>,
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state, utils};
+
+pub fn sol_withdraw_treasury(ctx: Context<SolWithdrawTreasury>, amount: u64) -> Result<()> {
+    let treasury = ctx.accounts.treasury_state.to_account_info();
+
+    // the rent of the treasury itself is not a fee
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury.data_len());
+    require!(
+        amount <= treasury.lamports().saturating_sub(rent_exempt_minimum),
+        error::ErrorCode::NotEnoughBalance
+    );
+
+    utils::debit_lamports(amount, &treasury, &ctx.accounts.destination)?;
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_withdrawn = treasury_state.total_withdrawn.checked_add(amount).unwrap();
+
+    // the lamport treasury holds its own rent as well
+    emit!(events::TreasuryWithdrawn {
+        treasury_state: treasury_state.key(),
+        mint_of_token: treasury_state.mint_of_token,
+        destination: ctx.accounts.destination.key(),
+        amount,
+        total_fees_collected: treasury_state.total_fees_collected,
+        total_withdrawn: treasury_state.total_withdrawn,
+        treasury_balance: treasury.lamports(),
+    });
+
+    msg!("Withdrew {} lamports from the treasury", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SolWithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"sol-treasury-state".as_ref()],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    /// CHECK: This is not dangerous because we only credit lamports to this account
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+
+    admin: Signer<'info>,
+}
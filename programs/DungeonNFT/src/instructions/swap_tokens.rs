@@ -1,10 +1,23 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{TokenAccount, Token};
 
-use crate::{error, state, utils};
+use crate::{error, events, math, state, utils};
 
 // sol_to_token = true ==> player is giving sol to get tokens
-pub fn swap_tokens(ctx: Context<SwapTokens>, amount_in: u64, sol_to_token: bool) -> Result<()> {
+// the swap fails rather than pay out less than min_amount_out or land after deadline
+pub fn swap_tokens(
+    ctx: Context<SwapTokens>,
+    amount_in: u64,
+    sol_to_token: bool,
+    min_amount_out: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require!(
+            Clock::get()?.unix_timestamp <= deadline,
+            error::ErrorCode::Expired
+        );
+    }
 
     // player_source_ata sends the token to be swapped to the destination_vault
     // the required token is sent from source_vault to player_dest_ata
@@ -35,26 +48,24 @@ pub fn swap_tokens(ctx: Context<SwapTokens>, amount_in: u64, sol_to_token: bool)
         error::ErrorCode::NotEnoughBalance
     );
 
-    let source_vault_balance = source_vault.amount as u128;
-    let destination_vault_balance = destination_vault.amount as u128;
-
     let market_state = &ctx.accounts.market_state;
-    let fee_amount = (amount_in as u128)
-        .checked_mul(market_state.fee_numerator as u128)
-        .unwrap()
-        .checked_div(market_state.fee_denominator as u128)
-        .unwrap();
-    let amount_in_minus_fee = amount_in as u128 - fee_amount;
-
-    let constant_product = source_vault_balance
-        .checked_mul(destination_vault_balance)
-        .unwrap();
-
-    let post_transaction_dest_vault_balance = destination_vault_balance + amount_in_minus_fee;
-    let post_transaction_source_vault_balance = constant_product.checked_div(post_transaction_dest_vault_balance).unwrap();
-    let amount_out = source_vault_balance.checked_sub(post_transaction_source_vault_balance).unwrap();
+    let fee_amount = math::fee(
+        amount_in,
+        market_state.fee_numerator,
+        market_state.fee_denominator,
+    )?;
+    let amount_out = math::quote_out(
+        amount_in,
+        destination_vault.amount,
+        source_vault.amount,
+        market_state.fee_numerator,
+        market_state.fee_denominator,
+    )?;
+    require!(
+        amount_out >= min_amount_out,
+        error::ErrorCode::SlippageExceeded
+    );
 
-    
     let state_bump_bytes = market_state.state_bump.to_le_bytes();
     let inner = vec![
         b"market-state".as_ref(),
@@ -66,7 +77,30 @@ pub fn swap_tokens(ctx: Context<SwapTokens>, amount_in: u64, sol_to_token: bool)
 
     utils::secure_transfer_cpi(amount_in, ctx.accounts.player.to_account_info(), player_source_ata.to_account_info(), destination_vault.to_account_info(), ctx.accounts.token_program.to_account_info(), outer.as_ref())?;
 
-    utils::secure_transfer_cpi(amount_out as u64, market_state.to_account_info(), source_vault.to_account_info(), player_dest_ata.to_account_info(), ctx.accounts.token_program.to_account_info(), outer.as_ref())?;
+    utils::secure_transfer_cpi(amount_out, market_state.to_account_info(), source_vault.to_account_info(), player_dest_ata.to_account_info(), ctx.accounts.token_program.to_account_info(), outer.as_ref())?;
+
+    // the fee stays in the pool, so the vault that was paid into grows by the full amount_in
+    let source_vault_post_balance = source_vault.amount - amount_out;
+    let destination_vault_post_balance = destination_vault
+        .amount
+        .checked_add(amount_in)
+        .ok_or(error::ErrorCode::MathOverflow)?;
+    let (token_vault_balance, sol_vault_balance) = if sol_to_token {
+        (source_vault_post_balance, destination_vault_post_balance)
+    } else {
+        (destination_vault_post_balance, source_vault_post_balance)
+    };
+
+    emit!(events::SwapExecuted {
+        market_state: market_state.key(),
+        player: ctx.accounts.player.key(),
+        sol_to_token,
+        amount_in,
+        amount_out,
+        fee_amount,
+        token_vault_balance,
+        sol_vault_balance,
+    });
 
     Ok(())
 }
@@ -82,6 +116,13 @@ pub struct SwapTokens<'info> {
     )]
     pub market_state: Account<'info, state::MarketState>,
 
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, state::ProgramConfig>,
+
     #[account(mut, 
         seeds = [
             b"token-vault".as_ref(), 
@@ -1,31 +1,104 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::{state, utils};
+use crate::{error, events, state, utils};
+
+// both stakes are agreed on up front, each side's deposit has to match its own
+pub fn transaction_setup(
+    ctx: Context<TransactionSetup>,
+    player_stake: u64,
+    house_stake: u64,
+) -> Result<()> {
+    let entry = ctx
+        .accounts
+        .mint_registry
+        .entry(&ctx.accounts.mint_of_token.key())
+        .ok_or(error::ErrorCode::MintNotRegistered)?;
+    require!(
+        entry.decimals == ctx.accounts.mint_of_token.decimals,
+        error::ErrorCode::MintDecimalsMismatch
+    );
+    require!(
+        entry.allows_stake(player_stake) && entry.allows_stake(house_stake),
+        error::ErrorCode::StakeOutOfRange
+    );
+
+    let counter = &mut ctx.accounts.session_counter;
+    let session_id = counter.next_session_id;
+
+    counter.player = ctx.accounts.player.key().clone();
+    counter.next_session_id = session_id.checked_add(1).unwrap();
+    counter.state_bump = *ctx.bumps.get("session_counter").unwrap();
+
+    let profile = &mut ctx.accounts.player_profile;
+    profile.player = ctx.accounts.player.key().clone();
+    profile.state_bump = *ctx.bumps.get("player_profile").unwrap();
 
-pub fn transaction_setup(ctx: Context<TransactionSetup>) -> Result<()> {
     let state = &mut ctx.accounts.transaction_state;
 
     state.player = ctx.accounts.player.key().clone();
     state.beneficiary = ctx.accounts.beneficiary.key().clone();
 
     state.mint_of_token = ctx.accounts.mint_of_token.key().clone();
+    state.session_id = session_id;
     state.escrow_account = ctx.accounts.escrow_account.key().clone();
 
-    state.amount_of_tokens = 0;
+    state.player_stake = player_stake;
+    state.house_stake = house_stake;
+    state.player_deposited = false;
+    state.house_deposited = false;
+    state.result_nonce = Clock::get()?.slot;
 
     state.state_bump = *ctx.bumps.get("transaction_state").unwrap();
     state.escrow_bump = *ctx.bumps.get("escrow_account").unwrap();
+    state.version = state::TransactionState::VERSION;
 
-    msg!("Initialized new Safe Transfer instance");
+    msg!("Initialized new Safe Transfer instance for session {}", session_id);
 
     state.stage = utils::Stage::Initialized.to_code();
 
+    emit!(events::EscrowCreated {
+        transaction_state: state.key(),
+        player: state.player,
+        beneficiary: state.beneficiary,
+        mint_of_token: state.mint_of_token,
+        escrow_account: state.escrow_account,
+        session_id,
+        player_stake,
+        house_stake,
+        result_nonce: state.result_nonce,
+        stage: state.stage,
+    });
+
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct TransactionSetup<'info> {
+    #[account(
+        init_if_needed,
+        space = 8 + state::SessionCounter::LEN,
+        payer = player,
+        seeds = [
+            b"session-counter".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump
+    )]
+    session_counter: Account<'info, state::SessionCounter>,
+
+    #[account(
+        init_if_needed,
+        space = 8 + state::PlayerProfile::LEN,
+        payer = player,
+        seeds = [
+            b"player-profile".as_ref(),
+            player.key().as_ref(),
+        ],
+        bump
+    )]
+    player_profile: Account<'info, state::PlayerProfile>,
+
     #[account(
         init,
         space = 8 + state::TransactionState::LEN,
@@ -35,11 +108,20 @@ pub struct TransactionSetup<'info> {
             player.key().as_ref(),
             beneficiary.key().as_ref(),
             mint_of_token.key().as_ref(),
+            session_counter.next_session_id.to_le_bytes().as_ref(),
         ],
         bump
     )]
     transaction_state: Account<'info, state::TransactionState>,
 
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused,
+        constraint = program_config.house == beneficiary.key() @ error::ErrorCode::UnauthorizedHouse
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
     #[account(
         init,
         payer = player,
@@ -48,6 +130,7 @@ pub struct TransactionSetup<'info> {
             player.key().as_ref(),
             beneficiary.key().as_ref(),
             mint_of_token.key().as_ref(),
+            session_counter.next_session_id.to_le_bytes().as_ref(),
         ],
         bump,
         token::mint = mint_of_token,
@@ -60,6 +143,12 @@ pub struct TransactionSetup<'info> {
     /// CHECK: This is not dangerous because we don't read or write from this account
     beneficiary: AccountInfo<'info>,
 
+    #[account(
+        seeds = [b"mint-registry".as_ref()],
+        bump = mint_registry.state_bump,
+    )]
+    mint_registry: Account<'info, state::MintRegistry>,
+
     mint_of_token: Account<'info, Mint>,
 
     system_program: Program<'info, System>,
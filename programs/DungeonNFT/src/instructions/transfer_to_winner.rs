@@ -1,53 +1,88 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Mint, TokenAccount};
 
-use crate::{error, state, utils};
+use crate::{error, events, state, utils};
 
-pub fn transfer_to_winner(ctx: Context<TransferToWinner>, _winner: Pubkey) -> Result<()> {
-    if utils::Stage::from(ctx.accounts.transaction_state.stage)? != utils::Stage::FundsDeposited {
-        msg!(
-            "Stage is invalid, state stage is {}",
-            ctx.accounts.transaction_state.stage
-        );
-        return Err(error::ErrorCode::StageInvalid.into());
-    }
-
-    let mint_of_token_public_key = ctx.accounts.mint_of_token.key().clone();
-    let state_bump_bytes = ctx.accounts.transaction_state.state_bump.to_le_bytes();
-    let inner = vec![
-        b"transaction-state".as_ref(),
-        ctx.accounts.player.key.as_ref(),
-        ctx.accounts.beneficiary.key.as_ref(),
-        mint_of_token_public_key.as_ref(),
-        state_bump_bytes.as_ref(),
-    ];
-    let outer = vec![inner.as_slice()];
-
-    utils::secure_transfer_cpi(
-        2 * ctx.accounts.transaction_state.amount_of_tokens,
-        ctx.accounts.transaction_state.to_account_info(),
-        ctx.accounts.escrow_account.to_account_info(),
-        ctx.accounts
-            .winner_associated_token_account
-            .to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        outer.as_ref(),
+// records the referee's verdict, the pot itself is paid out by finalize_settlement
+// once the dispute window has passed
+pub fn transfer_to_winner(
+    ctx: Context<TransferToWinner>,
+    winner: Pubkey,
+    nonce: u64,
+    expiry: i64,
+) -> Result<()> {
+    utils::Stage::transition(
+        &mut ctx.accounts.transaction_state.stage,
+        utils::Stage::ResultSubmitted,
     )?;
 
-    utils::close_account_cpi(
-        ctx.accounts.escrow_account.to_account_info(),
-        ctx.accounts.player.to_account_info(),
-        ctx.accounts.transaction_state.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        outer.as_ref(),
+    require!(
+        winner == ctx.accounts.transaction_state.player
+            || winner == ctx.accounts.transaction_state.beneficiary,
+        error::ErrorCode::InvalidWinner
+    );
+
+    // past the deadline the stakes can only be refunded through pullback
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.transaction_state.deadline,
+        error::ErrorCode::DeadlinePassed
+    );
+
+    // the result is only valid for the nonce handed out when this wager was set up,
+    // so a signature from an earlier wager between the same pair can't be replayed
+    require!(
+        nonce == ctx.accounts.transaction_state.result_nonce,
+        error::ErrorCode::AttestationNonceMismatch
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= expiry,
+        error::ErrorCode::AttestationExpired
+    );
+
+    let result_message = utils::result_message(
+        &ctx.accounts.escrow_account.key(),
+        &winner,
+        nonce,
+        expiry,
+    );
+    utils::verify_referee_attestation(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.program_config.referee,
+        &result_message,
     )?;
 
-    ctx.accounts.transaction_state.stage = utils::Stage::EscrowComplete.to_code();
+    let state = &mut ctx.accounts.transaction_state;
+    if winner == state.player {
+        state.player_share = 1;
+        state.beneficiary_share = 0;
+    } else {
+        state.player_share = 0;
+        state.beneficiary_share = 1;
+    }
+    state.challenge_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.program_config.dispute_window)
+        .unwrap();
+
+    emit!(events::EscrowResultSubmitted {
+        transaction_state: state.key(),
+        player_share: state.player_share,
+        beneficiary_share: state.beneficiary_share,
+        challenge_deadline: state.challenge_deadline,
+        stage: state.stage,
+    });
+
+    msg!(
+        "Referee picked {} as the winner, disputes are open until {}",
+        winner,
+        state.challenge_deadline
+    );
+
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(winner: Pubkey)]
 pub struct TransferToWinner<'info> {
     #[account(
         mut,
@@ -55,39 +90,40 @@ pub struct TransferToWinner<'info> {
             b"transaction-state".as_ref(),
             player.key().as_ref(),
             beneficiary.key().as_ref(),
-            mint_of_token.key().as_ref()
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
         ],
-        bump = transaction_state.state_bump, 
-        close = player
+        bump = transaction_state.state_bump
     )]
     transaction_state: Account<'info, state::TransactionState>,
 
     #[account(
-        mut,
         seeds = [
             b"escrow-account".as_ref(),
             player.key().as_ref(),
             beneficiary.key().as_ref(),
-            mint_of_token.key().as_ref()
+            mint_of_token.key().as_ref(),
+            transaction_state.session_id.to_le_bytes().as_ref()
         ],
-        bump = transaction_state.escrow_bump, 
+        bump = transaction_state.escrow_bump,
     )]
     escrow_account: Account<'info, TokenAccount>,
 
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = !program_config.paused @ error::ErrorCode::ProgramPaused
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    /// CHECK: This is the instructions sysvar, the address constraint makes sure of it
+    #[account(address = sysvar::instructions::ID)]
+    instructions_sysvar: AccountInfo<'info>,
+
     /// CHECK: This is not dangerous because we don't read or write from this account
-    #[account(mut)]
     player: AccountInfo<'info>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     beneficiary: AccountInfo<'info>,
 
     mint_of_token: Account<'info, Mint>,
-
-    #[account(
-        mut,
-        constraint = winner_associated_token_account.owner == winner,
-        constraint = winner_associated_token_account.mint == mint_of_token.key()
-    )]
-    winner_associated_token_account: Account<'info, TokenAccount>,
-
-    token_program: Program<'info, Token>,
 }
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state};
+
+pub fn treasury_setup(ctx: Context<TreasurySetup>) -> Result<()> {
+    let treasury_state = &mut ctx.accounts.treasury_state;
+
+    treasury_state.mint_of_token = ctx.accounts.mint_of_token.key().clone();
+    treasury_state.treasury_vault = ctx.accounts.treasury_vault.key().clone();
+    treasury_state.total_fees_collected = 0;
+    treasury_state.total_withdrawn = 0;
+
+    treasury_state.state_bump = *ctx.bumps.get("treasury_state").unwrap();
+    treasury_state.vault_bump = *ctx.bumps.get("treasury_vault").unwrap();
+
+    emit!(events::TreasuryCreated {
+        treasury_state: treasury_state.key(),
+        mint_of_token: treasury_state.mint_of_token,
+        treasury_vault: treasury_state.treasury_vault,
+    });
+
+    msg!(
+        "Initialized treasury for mint {}",
+        treasury_state.mint_of_token
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TreasurySetup<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        init,
+        space = 8 + state::TreasuryState::LEN,
+        payer = admin,
+        seeds = [
+            b"treasury-state".as_ref(),
+            mint_of_token.key().as_ref()
+        ],
+        bump
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [
+            b"treasury-vault".as_ref(),
+            mint_of_token.key().as_ref()
+        ],
+        bump,
+        token::mint = mint_of_token,
+        token::authority = treasury_state
+    )]
+    treasury_vault: Account<'info, TokenAccount>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+
+    rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{error, events, state};
+
+// pushes a profile's season score onto the running season's board, the settlements already do
+// this, anyone can crank it for a profile that was left out
+pub fn update_leaderboard(ctx: Context<UpdateLeaderboard>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let leaderboard = &mut ctx.accounts.leaderboard;
+
+    require!(
+        now >= leaderboard.starts_at && now < leaderboard.ends_at,
+        error::ErrorCode::SeasonNotActive
+    );
+
+    record_score(leaderboard, &ctx.accounts.player_profile);
+
+    Ok(())
+}
+
+// the settlements call this for the running season too, a no-op unless the score made the cut
+pub fn record_score(leaderboard: &mut Account<state::Leaderboard>, profile: &state::PlayerProfile) {
+    let score = profile.score(leaderboard.season);
+    if score > 0 && leaderboard.record(&profile.player, score) {
+        emit!(events::LeaderboardUpdated {
+            leaderboard: leaderboard.key(),
+            season: leaderboard.season,
+            player: profile.player,
+            score,
+            entries: leaderboard.entries.clone(),
+        });
+
+        msg!(
+            "{} is on the season {} leaderboard with a score of {}",
+            profile.player,
+            leaderboard.season,
+            score
+        );
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateLeaderboard<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"leaderboard".as_ref(),
+            leaderboard.season.to_le_bytes().as_ref(),
+        ],
+        bump = leaderboard.state_bump
+    )]
+    leaderboard: Account<'info, state::Leaderboard>,
+
+    #[account(
+        seeds = [
+            b"player-profile".as_ref(),
+            player_profile.player.as_ref(),
+        ],
+        bump = player_profile.state_bump
+    )]
+    player_profile: Account<'info, state::PlayerProfile>,
+}
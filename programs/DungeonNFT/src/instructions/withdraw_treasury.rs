@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{error, events, state, utils};
+
+pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    require!(
+        amount <= ctx.accounts.treasury_vault.amount,
+        error::ErrorCode::NotEnoughBalance
+    );
+
+    let mint_of_token_public_key = ctx.accounts.mint_of_token.key().clone();
+    let state_bump_bytes = ctx.accounts.treasury_state.state_bump.to_le_bytes();
+    let inner = vec![
+        b"treasury-state".as_ref(),
+        mint_of_token_public_key.as_ref(),
+        state_bump_bytes.as_ref(),
+    ];
+    let outer = vec![inner.as_slice()];
+
+    utils::secure_transfer_cpi(
+        amount,
+        ctx.accounts.treasury_state.to_account_info(),
+        ctx.accounts.treasury_vault.to_account_info(),
+        ctx.accounts.destination_token_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        outer.as_ref(),
+    )?;
+
+    let treasury_state = &mut ctx.accounts.treasury_state;
+    treasury_state.total_withdrawn = treasury_state.total_withdrawn.checked_add(amount).unwrap();
+
+    ctx.accounts.treasury_vault.reload()?;
+    emit!(events::TreasuryWithdrawn {
+        treasury_state: treasury_state.key(),
+        mint_of_token: treasury_state.mint_of_token,
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+        total_fees_collected: treasury_state.total_fees_collected,
+        total_withdrawn: treasury_state.total_withdrawn,
+        treasury_balance: ctx.accounts.treasury_vault.amount,
+    });
+
+    msg!("Withdrew {} tokens from the treasury", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"program-config".as_ref()],
+        bump = program_config.state_bump,
+        constraint = program_config.admin == admin.key() @ error::ErrorCode::UnauthorizedAdmin
+    )]
+    program_config: Account<'info, state::ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-state".as_ref(),
+            mint_of_token.key().as_ref()
+        ],
+        bump = treasury_state.state_bump,
+    )]
+    treasury_state: Account<'info, state::TreasuryState>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"treasury-vault".as_ref(),
+            mint_of_token.key().as_ref()
+        ],
+        bump = treasury_state.vault_bump,
+    )]
+    treasury_vault: Account<'info, TokenAccount>,
+
+    mint_of_token: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == mint_of_token.key()
+    )]
+    destination_token_account: Account<'info, TokenAccount>,
+
+    admin: Signer<'info>,
+
+    token_program: Program<'info, Token>,
+}
@@ -4,6 +4,8 @@ pub mod instructions;
 pub use instructions::*;
 
 pub mod error;
+pub mod events;
+pub mod math;
 pub mod state;
 pub mod utils;
 
@@ -16,28 +18,284 @@ pub mod dungeon_nft {
     // !!!!!!!!!!!!!!
     // NEED TO CHANGE THE TRANSACTION SETUP
     // ONLY THE PLAYER NEEDS TO BE A SIGNER SEED
-    pub fn transaction_setup_instruction(ctx: Context<TransactionSetup>) -> Result<()> {
-        transaction_setup::transaction_setup(ctx)
+    pub fn transaction_setup_instruction(
+        ctx: Context<TransactionSetup>,
+        player_stake: u64,
+        house_stake: u64,
+    ) -> Result<()> {
+        transaction_setup::transaction_setup(ctx, player_stake, house_stake)
     }
 
-    pub fn deposit_by_both_parties_instruction(
-        ctx: Context<DepositByBothParties>,
-        amount: u64,
+    pub fn deposit_by_player_instruction(
+        ctx: Context<DepositByPlayer>,
+        player_stake: u64,
     ) -> Result<()> {
-        deposit_by_both_parties::deposit_by_both_parties(ctx, amount)
+        deposit_by_player::deposit_by_player(ctx, player_stake)
+    }
+
+    pub fn deposit_by_house_instruction(ctx: Context<DepositByHouse>, house_stake: u64) -> Result<()> {
+        deposit_by_house::deposit_by_house(ctx, house_stake)
     }
 
     pub fn transfer_to_winner_instruction(
         ctx: Context<TransferToWinner>,
         winner: Pubkey,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        transfer_to_winner::transfer_to_winner(ctx, winner, nonce, expiry)
+    }
+
+    pub fn split_settlement_instruction(
+        ctx: Context<SplitSettlement>,
+        player_share: u64,
+        beneficiary_share: u64,
+        nonce: u64,
+        expiry: i64,
     ) -> Result<()> {
-        transfer_to_winner::transfer_to_winner(ctx, winner)
+        split_settlement::split_settlement(ctx, player_share, beneficiary_share, nonce, expiry)
+    }
+
+    pub fn raise_dispute_instruction(ctx: Context<RaiseDispute>) -> Result<()> {
+        raise_dispute::raise_dispute(ctx)
+    }
+
+    pub fn resolve_dispute_instruction(ctx: Context<ResolveDispute>, uphold: bool) -> Result<()> {
+        resolve_dispute::resolve_dispute(ctx, uphold)
+    }
+
+    pub fn finalize_settlement_instruction(ctx: Context<FinalizeSettlement>) -> Result<()> {
+        finalize_settlement::finalize_settlement(ctx)
     }
 
     pub fn pullback_instruction(ctx: Context<PullBack>) -> Result<()> {
         pullback::pull_back(ctx)
     }
 
+    pub fn cancel_transaction_instruction(ctx: Context<CancelTransaction>) -> Result<()> {
+        cancel_transaction::cancel_transaction(ctx)
+    }
+
+    pub fn close_transaction_instruction(ctx: Context<CloseTransaction>) -> Result<()> {
+        close_transaction::close_transaction(ctx)
+    }
+
+    pub fn sol_transaction_setup_instruction(
+        ctx: Context<SolTransactionSetup>,
+        player_stake: u64,
+        house_stake: u64,
+    ) -> Result<()> {
+        sol_transaction_setup::sol_transaction_setup(ctx, player_stake, house_stake)
+    }
+
+    pub fn sol_deposit_by_player_instruction(
+        ctx: Context<SolDepositByPlayer>,
+        player_stake: u64,
+    ) -> Result<()> {
+        sol_deposit_by_player::sol_deposit_by_player(ctx, player_stake)
+    }
+
+    pub fn sol_deposit_by_house_instruction(
+        ctx: Context<SolDepositByHouse>,
+        house_stake: u64,
+    ) -> Result<()> {
+        sol_deposit_by_house::sol_deposit_by_house(ctx, house_stake)
+    }
+
+    pub fn sol_transfer_to_winner_instruction(
+        ctx: Context<SolTransferToWinner>,
+        winner: Pubkey,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        sol_transfer_to_winner::sol_transfer_to_winner(ctx, winner, nonce, expiry)
+    }
+
+    pub fn sol_split_settlement_instruction(
+        ctx: Context<SolSplitSettlement>,
+        player_share: u64,
+        beneficiary_share: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        sol_split_settlement::sol_split_settlement(
+            ctx,
+            player_share,
+            beneficiary_share,
+            nonce,
+            expiry,
+        )
+    }
+
+    pub fn sol_finalize_settlement_instruction(ctx: Context<SolFinalizeSettlement>) -> Result<()> {
+        sol_finalize_settlement::sol_finalize_settlement(ctx)
+    }
+
+    pub fn sol_pullback_instruction(ctx: Context<SolPullBack>) -> Result<()> {
+        sol_pullback::sol_pull_back(ctx)
+    }
+
+    pub fn sol_cancel_transaction_instruction(ctx: Context<SolCancelTransaction>) -> Result<()> {
+        sol_cancel_transaction::sol_cancel_transaction(ctx)
+    }
+
+    pub fn leaderboard_setup_instruction(
+        ctx: Context<LeaderboardSetup>,
+        season: u32,
+        starts_at: i64,
+        ends_at: i64,
+    ) -> Result<()> {
+        leaderboard_setup::leaderboard_setup(ctx, season, starts_at, ends_at)
+    }
+
+    pub fn update_leaderboard_instruction(ctx: Context<UpdateLeaderboard>) -> Result<()> {
+        update_leaderboard::update_leaderboard(ctx)
+    }
+
+    pub fn lobby_setup_instruction(
+        ctx: Context<LobbySetup>,
+        lobby_id: u64,
+        entry_stake: u64,
+        max_players: u8,
+        payout_bps: Vec<u16>,
+    ) -> Result<()> {
+        lobby_setup::lobby_setup(ctx, lobby_id, entry_stake, max_players, payout_bps)
+    }
+
+    pub fn join_lobby_instruction(ctx: Context<JoinLobby>) -> Result<()> {
+        join_lobby::join_lobby(ctx)
+    }
+
+    pub fn lock_lobby_instruction(ctx: Context<LockLobby>) -> Result<()> {
+        lock_lobby::lock_lobby(ctx)
+    }
+
+    pub fn settle_lobby_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleLobby<'info>>,
+        winners: Vec<Pubkey>,
+        expiry: i64,
+    ) -> Result<()> {
+        settle_lobby::settle_lobby(ctx, winners, expiry)
+    }
+
+    pub fn cancel_lobby_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelLobby<'info>>,
+    ) -> Result<()> {
+        cancel_lobby::cancel_lobby(ctx)
+    }
+
+    pub fn refund_lobby_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundLobby<'info>>,
+    ) -> Result<()> {
+        refund_lobby::refund_lobby(ctx)
+    }
+
+    pub fn config_setup_instruction(
+        ctx: Context<ConfigSetup>,
+        house: Pubkey,
+        referee: Pubkey,
+        arbiter: Pubkey,
+        settlement_timeout: i64,
+        dispute_window: i64,
+        fee_bps: u16,
+    ) -> Result<()> {
+        config_setup::config_setup(
+            ctx,
+            house,
+            referee,
+            arbiter,
+            settlement_timeout,
+            dispute_window,
+            fee_bps,
+        )
+    }
+
+    pub fn set_house_instruction(ctx: Context<SetHouse>, house: Pubkey) -> Result<()> {
+        set_house::set_house(ctx, house)
+    }
+
+    pub fn set_paused_instruction(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        set_paused::set_paused(ctx, paused)
+    }
+
+    pub fn propose_admin_instruction(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        propose_admin::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin_instruction(ctx: Context<AcceptAdmin>) -> Result<()> {
+        accept_admin::accept_admin(ctx)
+    }
+
+    pub fn migrate_account_instruction(ctx: Context<MigrateAccount>) -> Result<()> {
+        migrate_account::migrate_account(ctx)
+    }
+
+    pub fn legacy_pullback_instruction(ctx: Context<LegacyPullBack>) -> Result<()> {
+        legacy_pullback::legacy_pull_back(ctx)
+    }
+
+    pub fn set_referee_instruction(ctx: Context<SetReferee>, referee: Pubkey) -> Result<()> {
+        set_referee::set_referee(ctx, referee)
+    }
+
+    pub fn set_settlement_timeout_instruction(
+        ctx: Context<SetSettlementTimeout>,
+        settlement_timeout: i64,
+    ) -> Result<()> {
+        set_settlement_timeout::set_settlement_timeout(ctx, settlement_timeout)
+    }
+
+    pub fn set_dispute_policy_instruction(
+        ctx: Context<SetDisputePolicy>,
+        arbiter: Pubkey,
+        dispute_window: i64,
+    ) -> Result<()> {
+        set_dispute_policy::set_dispute_policy(ctx, arbiter, dispute_window)
+    }
+
+    pub fn set_fee_instruction(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+        set_fee::set_fee(ctx, fee_bps)
+    }
+
+    pub fn mint_registry_setup_instruction(ctx: Context<MintRegistrySetup>) -> Result<()> {
+        mint_registry_setup::mint_registry_setup(ctx)
+    }
+
+    pub fn register_mint_instruction(
+        ctx: Context<RegisterMint>,
+        decimals: u8,
+        min_stake: u64,
+        max_stake: u64,
+    ) -> Result<()> {
+        register_mint::register_mint(ctx, decimals, min_stake, max_stake)
+    }
+
+    pub fn deregister_mint_instruction(ctx: Context<DeregisterMint>, mint: Pubkey) -> Result<()> {
+        deregister_mint::deregister_mint(ctx, mint)
+    }
+
+    pub fn treasury_setup_instruction(ctx: Context<TreasurySetup>) -> Result<()> {
+        treasury_setup::treasury_setup(ctx)
+    }
+
+    pub fn withdraw_treasury_instruction(
+        ctx: Context<WithdrawTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw_treasury::withdraw_treasury(ctx, amount)
+    }
+
+    pub fn sol_treasury_setup_instruction(ctx: Context<SolTreasurySetup>) -> Result<()> {
+        sol_treasury_setup::sol_treasury_setup(ctx)
+    }
+
+    pub fn sol_withdraw_treasury_instruction(
+        ctx: Context<SolWithdrawTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        sol_withdraw_treasury::sol_withdraw_treasury(ctx, amount)
+    }
+
     pub fn amm_setup_instruction(
         ctx: Context<AMMSetup>,
         fee_numerator: u64,
@@ -46,19 +304,34 @@ pub mod dungeon_nft {
         amm_setup::amm_setup(ctx, fee_numerator, fee_denominator)
     }
 
+    pub fn lp_setup_instruction(ctx: Context<LpSetup>) -> Result<()> {
+        lp_setup::lp_setup(ctx)
+    }
+
     pub fn add_liquidity_instruction(
         ctx: Context<LiquidityOperation>, 
-        token_amount: u64, 
-        sol_amount: u64
+        max_token_amount: u64, 
+        max_sol_amount: u64
+    ) -> Result<()> {
+        liquidity::add_liquidity(ctx, max_token_amount, max_sol_amount)
+    }
+
+    pub fn remove_liquidity_instruction(
+        ctx: Context<LiquidityOperation>,
+        lp_amount: u64,
+        min_token_amount: u64,
+        min_sol_amount: u64,
     ) -> Result<()> {
-        liquidity::add_liquidity(ctx, token_amount, sol_amount)
+        liquidity::remove_liquidity(ctx, lp_amount, min_token_amount, min_sol_amount)
     }
 
     pub fn swap_tokens_instruction(
         ctx: Context<SwapTokens>, 
         amount_in: u64, 
-        sol_to_token: bool
+        sol_to_token: bool,
+        min_amount_out: u64,
+        deadline: Option<i64>
     ) -> Result<()> {
-        swap_tokens::swap_tokens(ctx, amount_in, sol_to_token)
+        swap_tokens::swap_tokens(ctx, amount_in, sol_to_token, min_amount_out, deadline)
     }
 }
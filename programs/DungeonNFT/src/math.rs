@@ -0,0 +1,207 @@
+// constant product math for the markets, shared with the sdk for off-chain quotes.
+// it only touches core and plain integers, every intermediate is a u128 so two u64
+// amounts can always be multiplied, and nothing in here panics
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    // a result doesn't fit in a u64
+    Overflow,
+    ZeroAmount,
+    // one side of the pool holds nothing, so there's no price
+    EmptyPool,
+    // the fee has to be a fraction below one with a non-zero denominator
+    InvalidFee,
+    // asked for everything the pool holds, or more
+    InsufficientLiquidity,
+}
+
+pub type MathResult<T> = core::result::Result<T, MathError>;
+
+// lp shares locked away on the first deposit, so the supply never drops back to a few
+// units a donation to the vaults could inflate
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+fn to_u64(value: u128) -> MathResult<u64> {
+    u64::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+fn div_ceil(numerator: u128, denominator: u128) -> u128 {
+    numerator / denominator + u128::from(numerator % denominator != 0)
+}
+
+// floor of the square root, by newton's method from an overestimate
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut root = value;
+    let mut next = value / 2 + 1;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
+
+fn check_pool(reserve_in: u64, reserve_out: u64) -> MathResult<()> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::EmptyPool);
+    }
+    Ok(())
+}
+
+pub fn check_fee(fee_numerator: u64, fee_denominator: u64) -> MathResult<()> {
+    if fee_denominator == 0 || fee_numerator >= fee_denominator {
+        return Err(MathError::InvalidFee);
+    }
+    Ok(())
+}
+
+// the part of amount_in that stays in the pool, rounded down in favour of the trader
+pub fn fee(amount_in: u64, fee_numerator: u64, fee_denominator: u64) -> MathResult<u64> {
+    check_fee(fee_numerator, fee_denominator)?;
+    to_u64(u128::from(amount_in) * u128::from(fee_numerator) / u128::from(fee_denominator))
+}
+
+// what a swap of amount_in pays out of reserve_out, rounded down so k never shrinks
+pub fn quote_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> MathResult<u64> {
+    if amount_in == 0 {
+        return Err(MathError::ZeroAmount);
+    }
+    check_pool(reserve_in, reserve_out)?;
+
+    let amount_in_after_fee =
+        u128::from(amount_in - fee(amount_in, fee_numerator, fee_denominator)?);
+
+    to_u64(
+        amount_in_after_fee * u128::from(reserve_out)
+            / (u128::from(reserve_in) + amount_in_after_fee),
+    )
+}
+
+// the smallest amount_in, give or take the fee rounding, for which quote_out pays at least
+// amount_out
+pub fn quote_in(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> MathResult<u64> {
+    if amount_out == 0 {
+        return Err(MathError::ZeroAmount);
+    }
+    check_pool(reserve_in, reserve_out)?;
+    check_fee(fee_numerator, fee_denominator)?;
+    if amount_out >= reserve_out {
+        return Err(MathError::InsufficientLiquidity);
+    }
+
+    let amount_in_after_fee = div_ceil(
+        u128::from(reserve_in) * u128::from(amount_out),
+        u128::from(reserve_out - amount_out),
+    );
+    let amount_in = amount_in_after_fee
+        .checked_mul(u128::from(fee_denominator))
+        .ok_or(MathError::Overflow)?;
+
+    to_u64(div_ceil(
+        amount_in,
+        u128::from(fee_denominator - fee_numerator),
+    ))
+}
+
+// how much of the other side goes with `amount` at the pool's current ratio, rounded up
+// so a deposit can't dilute what's already in the pool
+pub fn liquidity_ratio(amount: u64, reserve: u64, other_reserve: u64) -> MathResult<u64> {
+    check_pool(reserve, other_reserve)?;
+
+    to_u64(div_ceil(
+        u128::from(amount) * u128::from(other_reserve),
+        u128::from(reserve),
+    ))
+}
+
+// the largest pair within both maxima that keeps the pool's ratio, the side that isn't
+// the limit is rounded up in favour of the pool
+pub fn balanced_deposit(
+    max_token_amount: u64,
+    max_sol_amount: u64,
+    token_reserve: u64,
+    sol_reserve: u64,
+) -> MathResult<(u64, u64)> {
+    check_pool(token_reserve, sol_reserve)?;
+
+    let sol_amount = liquidity_ratio(max_token_amount, token_reserve, sol_reserve)?;
+    if sol_amount <= max_sol_amount {
+        return Ok((max_token_amount, sol_amount));
+    }
+
+    // not enough sol for all the tokens, so the sol side sets the size
+    let token_amount =
+        to_u64(u128::from(max_sol_amount) * u128::from(token_reserve) / u128::from(sol_reserve))?;
+    if token_amount == 0 {
+        return Err(MathError::ZeroAmount);
+    }
+    Ok((
+        token_amount,
+        liquidity_ratio(token_amount, token_reserve, sol_reserve)?,
+    ))
+}
+
+// lp shares for the deposit that seeds the pool, the geometric mean of both sides less
+// the locked MINIMUM_LIQUIDITY
+pub fn initial_shares(token_amount: u64, sol_amount: u64) -> MathResult<u64> {
+    let shares = to_u64(isqrt(u128::from(token_amount) * u128::from(sol_amount)))?;
+    if shares <= MINIMUM_LIQUIDITY {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    Ok(shares - MINIMUM_LIQUIDITY)
+}
+
+// lp shares for a deposit into a seeded pool, pro rata to the smaller side and rounded
+// down so the shares already out are never diluted
+pub fn proportional_shares(
+    token_amount: u64,
+    sol_amount: u64,
+    token_reserve: u64,
+    sol_reserve: u64,
+    supply: u64,
+) -> MathResult<u64> {
+    check_pool(token_reserve, sol_reserve)?;
+
+    let by_token = u128::from(token_amount) * u128::from(supply) / u128::from(token_reserve);
+    let by_sol = u128::from(sol_amount) * u128::from(supply) / u128::from(sol_reserve);
+    let shares = to_u64(by_token.min(by_sol))?;
+    if shares == 0 {
+        return Err(MathError::ZeroAmount);
+    }
+    Ok(shares)
+}
+
+// what burning lp_amount of the supply pays out of each side, rounded down so the shares
+// left are never worth less
+pub fn withdrawal_amounts(
+    lp_amount: u64,
+    token_reserve: u64,
+    sol_reserve: u64,
+    supply: u64,
+) -> MathResult<(u64, u64)> {
+    if lp_amount == 0 {
+        return Err(MathError::ZeroAmount);
+    }
+    // the locked minimum is part of the supply, so it can never all be burnt
+    if lp_amount >= supply {
+        return Err(MathError::InsufficientLiquidity);
+    }
+
+    let token_amount = u128::from(token_reserve) * u128::from(lp_amount) / u128::from(supply);
+    let sol_amount = u128::from(sol_reserve) * u128::from(lp_amount) / u128::from(supply);
+    Ok((to_u64(token_amount)?, to_u64(sol_amount)?))
+}
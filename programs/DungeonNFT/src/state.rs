@@ -1,19 +1,79 @@
 use anchor_lang::prelude::*;
 
+use crate::utils;
+
 #[account]
 pub struct TransactionState {
     pub player: Pubkey,
     pub beneficiary: Pubkey,
+    // the default pubkey for lamport escrows, which hold the stakes on this account itself
     pub mint_of_token: Pubkey,
     pub escrow_account: Pubkey,
-    pub amount_of_tokens: u64,
+    pub session_id: u64,
+    pub player_stake: u64,
+    pub house_stake: u64,
+    pub result_nonce: u64,
+    // unix timestamp after which the stakes can only be refunded
+    pub deadline: i64,
+    // the referee's verdict as a ratio of the pot, e.g. 1 : 0 when the player wins
+    pub player_share: u64,
+    pub beneficiary_share: u64,
+    // unix timestamp until which the losing party can dispute the verdict
+    pub challenge_deadline: i64,
+    // each side pays in on its own, the escrow is funded once both flags are set
+    pub player_deposited: bool,
+    pub house_deposited: bool,
     pub stage: u8,
     pub state_bump: u8,
     pub escrow_bump: u8,
+    // layout version, older accounts are brought up to date by migrate_account
+    pub version: u8,
+    // the config's fee when the escrow got funded, the rake taken on settlement
+    pub fee_bps: u16,
+    // unix timestamp until which the arbiter can rule on a dispute, the verdict stands afterwards
+    pub dispute_deadline: i64,
+    // room for new fields so they don't need another realloc
+    pub reserved: [u8; 54],
 }
 
 impl TransactionState {
-    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 1 + 1 + 1;
+    pub const LEN: usize =
+        32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 1 + 1 + 2 + 8 + 54;
+    pub const VERSION: u8 = 1;
+
+    // everything the winner walks away with
+    pub fn pot(&self) -> u64 {
+        self.player_stake.checked_add(self.house_stake).unwrap()
+    }
+
+    // what the escrow holds while it's being funded
+    pub fn deposited(&self) -> u64 {
+        let player_side = if self.player_deposited { self.player_stake } else { 0 };
+        let house_side = if self.house_deposited { self.house_stake } else { 0 };
+        player_side.checked_add(house_side).unwrap()
+    }
+
+    pub fn is_funded(&self) -> bool {
+        self.player_deposited && self.house_deposited
+    }
+
+    // only a party that isn't getting the whole pot has something to dispute
+    pub fn can_dispute(&self, party: &Pubkey) -> bool {
+        (*party == self.player && self.beneficiary_share > 0)
+            || (*party == self.beneficiary && self.player_share > 0)
+    }
+}
+
+// hands out the session id of every new escrow a player opens
+#[account]
+pub struct SessionCounter {
+    pub player: Pubkey,
+    pub next_session_id: u64,
+    pub state_bump: u8,
+}
+
+impl SessionCounter {
+    pub const LEN: usize = 32 + 8 + 1;
 }
 
 #[account]
@@ -23,8 +83,418 @@ pub struct MarketState {
     pub state_bump: u8,
     pub token_vault_bump: u8,
     pub sol_vault_bump: u8,
+    pub version: u8,
+    pub lp_mint_bump: u8,
+    // holds the MINIMUM_LIQUIDITY shares minted on the first deposit, nothing moves them out
+    pub lp_lock_bump: u8,
+    // false on markets migrated from the v0 layout until lp_setup, those were opened
+    // without an lp mint. a bump can be zero, so it doesn't tell
+    pub has_lp_mint: bool,
+    // the house that opened the market, its key seeds the market PDA and the vaults.
+    // liquidity providers sign for themselves
+    pub owner: Pubkey,
+    pub reserved: [u8; 29],
 }
 
 impl MarketState {
+    pub const LEN: usize = 8 + 8 + 8 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 32 + 29;
+    pub const VERSION: u8 = 1;
+}
+
+// the layouts from before the version byte, only read by migrate_account
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TransactionStateV0 {
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub escrow_account: Pubkey,
+    // what each side paid in, both deposited in the same instruction
+    pub amount_of_tokens: u64,
+    pub stage: u8,
+    pub state_bump: u8,
+    pub escrow_bump: u8,
+}
+
+impl TransactionStateV0 {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 1 + 1 + 1;
+}
+
+// v0 escrows have no session id, they stay at the session-less seeds and are only refunded
+// through legacy_pullback
+impl From<TransactionStateV0> for TransactionState {
+    fn from(legacy: TransactionStateV0) -> Self {
+        let funded = legacy.stage != utils::Stage::Initialized.to_code();
+        TransactionState {
+            player: legacy.player,
+            beneficiary: legacy.beneficiary,
+            mint_of_token: legacy.mint_of_token,
+            escrow_account: legacy.escrow_account,
+            session_id: 0,
+            player_stake: legacy.amount_of_tokens,
+            house_stake: legacy.amount_of_tokens,
+            result_nonce: 0,
+            // v0 escrows never had a deadline, they can be refunded right away
+            deadline: 0,
+            player_share: 0,
+            beneficiary_share: 0,
+            challenge_deadline: 0,
+            player_deposited: funded,
+            house_deposited: funded,
+            stage: legacy.stage,
+            state_bump: legacy.state_bump,
+            escrow_bump: legacy.escrow_bump,
+            version: TransactionState::VERSION,
+            // v0 escrows predate the fee, they settle without a rake
+            fee_bps: 0,
+            dispute_deadline: 0,
+            reserved: [0; 54],
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MarketStateV0 {
+    pub fee_numerator: u64,
+    pub fee_denominator: u64,
+    pub state_bump: u8,
+    pub token_vault_bump: u8,
+    pub sol_vault_bump: u8,
+}
+
+impl MarketStateV0 {
+    // what the old markets were allocated, an 8 too many, the layout serializes to 19
     pub const LEN: usize = 8 + 8 + 8 + 1 + 1 + 1;
 }
+
+impl From<MarketStateV0> for MarketState {
+    fn from(legacy: MarketStateV0) -> Self {
+        MarketState {
+            fee_numerator: legacy.fee_numerator,
+            fee_denominator: legacy.fee_denominator,
+            state_bump: legacy.state_bump,
+            token_vault_bump: legacy.token_vault_bump,
+            sol_vault_bump: legacy.sol_vault_bump,
+            version: MarketState::VERSION,
+            lp_mint_bump: 0,
+            lp_lock_bump: 0,
+            has_lp_mint: false,
+            // filled in by lp_setup, the old layout didn't keep it
+            owner: Pubkey::default(),
+            reserved: [0; 29],
+        }
+    }
+}
+
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    // ed25519 key of the game server, it signs the results instead of submitting them
+    pub referee: Pubkey,
+    // seconds the referee has to settle a funded escrow
+    pub settlement_timeout: i64,
+    // cut of every settled pot that goes to the treasury, in basis points
+    pub fee_bps: u16,
+    // rules on disputed results
+    pub arbiter: Pubkey,
+    // seconds the losing party has to dispute a submitted result
+    pub dispute_window: i64,
+    // the only beneficiary escrows and markets can be opened against
+    pub house: Pubkey,
+    // stops every escrow, lobby and market instruction, the admin ones keep working
+    pub paused: bool,
+    // set by propose_admin, becomes the admin once it accepts
+    pub pending_admin: Pubkey,
+    pub state_bump: u8,
+    // the latest leaderboard season, settlements in its window count towards it
+    pub season: u32,
+    pub season_starts_at: i64,
+    pub season_ends_at: i64,
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 32 + 32 + 8 + 2 + 32 + 8 + 32 + 1 + 32 + 1 + 4 + 8 + 8;
+
+    pub fn active_season(&self, now: i64) -> Option<u32> {
+        if now >= self.season_starts_at && now < self.season_ends_at {
+            Some(self.season)
+        } else {
+            None
+        }
+    }
+}
+
+#[account]
+pub struct TreasuryState {
+    // the default pubkey for the lamport treasury, which keeps the fees on this account itself
+    pub mint_of_token: Pubkey,
+    pub treasury_vault: Pubkey,
+    pub total_fees_collected: u64,
+    pub total_withdrawn: u64,
+    pub state_bump: u8,
+    pub vault_bump: u8,
+}
+
+impl TreasuryState {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MintEntry {
+    pub mint: Pubkey,
+    // what the admin expected the mint to have when it was registered
+    pub decimals: u8,
+    // bounds for each side's stake, in base units of the mint
+    pub min_stake: u64,
+    pub max_stake: u64,
+}
+
+impl MintEntry {
+    pub const LEN: usize = 32 + 1 + 8 + 8;
+
+    pub fn allows_stake(&self, stake: u64) -> bool {
+        stake >= self.min_stake && stake <= self.max_stake
+    }
+}
+
+// the mints an escrow can be set up in, managed by the admin
+#[account]
+pub struct MintRegistry {
+    pub entries: Vec<MintEntry>,
+    pub state_bump: u8,
+}
+
+impl MintRegistry {
+    pub const MAX_MINTS: usize = 32;
+
+    pub const LEN: usize = (4 + MintEntry::LEN * MintRegistry::MAX_MINTS) + 1;
+
+    pub fn entry(&self, mint: &Pubkey) -> Option<&MintEntry> {
+        self.entries.iter().find(|entry| entry.mint == *mint)
+    }
+}
+
+#[account]
+pub struct LobbyState {
+    pub creator: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub lobby_vault: Pubkey,
+    pub lobby_id: u64,
+    pub entry_stake: u64,
+    pub max_players: u8,
+    pub players: Vec<Pubkey>,
+    // share of the pot for 1st, 2nd, ... place, in basis points
+    pub payout_bps: Vec<u16>,
+    pub stage: u8,
+    pub state_bump: u8,
+    pub vault_bump: u8,
+    // the config's fee when the lobby opened, every entry is paid in against it
+    pub fee_bps: u16,
+    // unix timestamp after which anyone can refund the entries, moved on when the lobby locks
+    pub deadline: i64,
+}
+
+impl LobbyState {
+    pub const MAX_PLAYERS: usize = 16;
+    pub const MAX_WINNERS: usize = 8;
+
+    pub const LEN: usize = 32
+        + 32
+        + 32
+        + 8
+        + 8
+        + 1
+        + (4 + 32 * LobbyState::MAX_PLAYERS)
+        + (4 + 2 * LobbyState::MAX_WINNERS)
+        + 1
+        + 1
+        + 1
+        + 2
+        + 8;
+
+    pub fn pot(&self) -> u64 {
+        self.entry_stake
+            .checked_mul(self.players.len() as u64)
+            .unwrap()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MintStats {
+    pub mint: Pubkey,
+    pub total_wagered: u64,
+    // what the player got back minus what they staked, over every settled game
+    pub net_profit: i64,
+}
+
+impl MintStats {
+    pub const LEN: usize = 32 + 8 + 8;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameOutcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl GameOutcome {
+    // judged on what the player was awarded before the rake, so a raked draw stays a draw
+    pub fn of(awarded: u64, stake: u64) -> Self {
+        match awarded.cmp(&stake) {
+            std::cmp::Ordering::Greater => GameOutcome::Win,
+            std::cmp::Ordering::Less => GameOutcome::Loss,
+            std::cmp::Ordering::Equal => GameOutcome::Draw,
+        }
+    }
+}
+
+// stats of a wallet, updated by every settled escrow it played as the player and every
+// settled lobby it sat in
+#[account]
+pub struct PlayerProfile {
+    pub player: Pubkey,
+    pub games_played: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    // keyed by mint, the default pubkey for lamport escrows
+    pub mint_stats: Vec<MintStats>,
+    pub state_bump: u8,
+    // counters of the last season the wallet played in, reset when a new one starts
+    pub season: u32,
+    pub season_games: u64,
+    pub season_wins: u64,
+}
+
+impl PlayerProfile {
+    // every registered mint plus native SOL
+    pub const MAX_MINTS: usize = MintRegistry::MAX_MINTS + 1;
+
+    pub const LEN: usize =
+        32 + 8 + 8 + 8 + 4 + 4 + (4 + MintStats::LEN * PlayerProfile::MAX_MINTS) + 1 + 4 + 8 + 8;
+
+    // a draw counts as a game but neither extends nor breaks the streak
+    pub fn record_game(
+        &mut self,
+        mint: &Pubkey,
+        stake: u64,
+        payout: u64,
+        outcome: GameOutcome,
+        season: Option<u32>,
+    ) {
+        self.games_played = self.games_played.checked_add(1).unwrap();
+
+        match outcome {
+            GameOutcome::Win => {
+                self.wins = self.wins.checked_add(1).unwrap();
+                self.current_streak = self.current_streak.saturating_add(1);
+                self.longest_streak = self.longest_streak.max(self.current_streak);
+            }
+            GameOutcome::Loss => {
+                self.losses = self.losses.checked_add(1).unwrap();
+                self.current_streak = 0;
+            }
+            GameOutcome::Draw => {}
+        }
+
+        if let Some(season) = season {
+            if self.season != season {
+                self.season = season;
+                self.season_games = 0;
+                self.season_wins = 0;
+            }
+            self.season_games = self.season_games.checked_add(1).unwrap();
+            if outcome == GameOutcome::Win {
+                self.season_wins = self.season_wins.checked_add(1).unwrap();
+            }
+        }
+
+        let index = match self.mint_stats.iter().position(|stats| stats.mint == *mint) {
+            Some(index) => index,
+            None if self.mint_stats.len() < PlayerProfile::MAX_MINTS => {
+                self.mint_stats.push(MintStats {
+                    mint: *mint,
+                    total_wagered: 0,
+                    net_profit: 0,
+                });
+                self.mint_stats.len() - 1
+            }
+            // only reachable after mints were swapped out of the registry, the counters above still move
+            None => return,
+        };
+
+        let stats = &mut self.mint_stats[index];
+        stats.total_wagered = stats.total_wagered.checked_add(stake).unwrap();
+        stats.net_profit = stats
+            .net_profit
+            .checked_add(payout as i64 - stake as i64)
+            .unwrap();
+    }
+
+    // what the leaderboard of `season` ranks by, nothing for a season the wallet didn't play
+    pub fn score(&self, season: u32) -> u64 {
+        if self.season == season {
+            self.season_wins
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeaderboardEntry {
+    pub player: Pubkey,
+    pub score: u64,
+}
+
+impl LeaderboardEntry {
+    pub const LEN: usize = 32 + 8;
+}
+
+// the top players of a season, best score first
+#[account]
+pub struct Leaderboard {
+    pub season: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub entries: Vec<LeaderboardEntry>,
+    pub state_bump: u8,
+}
+
+impl Leaderboard {
+    pub const MAX_ENTRIES: usize = 10;
+
+    pub const LEN: usize = 4 + 8 + 8 + (4 + LeaderboardEntry::LEN * Leaderboard::MAX_ENTRIES) + 1;
+
+    // returns whether the board changed
+    pub fn record(&mut self, player: &Pubkey, score: u64) -> bool {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.player == *player)
+        {
+            if score <= entry.score {
+                return false;
+            }
+            entry.score = score;
+        } else if self.entries.len() < Leaderboard::MAX_ENTRIES {
+            self.entries.push(LeaderboardEntry {
+                player: *player,
+                score,
+            });
+        } else {
+            let last = self.entries.last_mut().unwrap();
+            if score <= last.score {
+                return false;
+            }
+            *last = LeaderboardEntry {
+                player: *player,
+                score,
+            };
+        }
+
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score));
+        true
+    }
+}
@@ -1,12 +1,20 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 
 use super::error::ErrorCode;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Stage {
     Initialized,
     FundsDeposited,
     EscrowComplete,
+    Cancelled,
+    Expired,
+    Disputed,
+    ResultSubmitted,
 }
 
 impl Stage {
@@ -15,6 +23,10 @@ impl Stage {
             Stage::Initialized => 1,
             Stage::FundsDeposited => 2,
             Stage::EscrowComplete => 3,
+            Stage::Cancelled => 4,
+            Stage::Expired => 5,
+            Stage::Disputed => 6,
+            Stage::ResultSubmitted => 7,
         }
     }
 
@@ -23,12 +35,152 @@ impl Stage {
             1 => Ok(Stage::Initialized),
             2 => Ok(Stage::FundsDeposited),
             3 => Ok(Stage::EscrowComplete),
+            4 => Ok(Stage::Cancelled),
+            5 => Ok(Stage::Expired),
+            6 => Ok(Stage::Disputed),
+            7 => Ok(Stage::ResultSubmitted),
             unknown_value => {
                 msg!("Unknown stage: {}", unknown_value);
                 Err(error!(ErrorCode::StageInvalid).into())
             }
         }
     }
+
+    pub fn can_transition_to(&self, next: Stage) -> bool {
+        matches!(
+            (self, next),
+            (Stage::Initialized, Stage::FundsDeposited)
+                | (Stage::Initialized, Stage::Cancelled)
+                | (Stage::FundsDeposited, Stage::ResultSubmitted)
+                | (Stage::FundsDeposited, Stage::Expired)
+                | (Stage::ResultSubmitted, Stage::Disputed)
+                | (Stage::ResultSubmitted, Stage::EscrowComplete)
+                | (Stage::Disputed, Stage::ResultSubmitted)
+                | (Stage::Disputed, Stage::EscrowComplete)
+        )
+    }
+
+    // no way out of these, the state account only waits to be closed
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Stage::EscrowComplete | Stage::Cancelled | Stage::Expired
+        )
+    }
+
+    // every stage change of an escrow goes through here
+    pub fn transition(stage: &mut u8, next: Stage) -> Result<()> {
+        if !Stage::from(*stage)?.can_transition_to(next) {
+            msg!(
+                "Stage is invalid, can't move from stage {} to {}",
+                stage,
+                next.to_code()
+            );
+            return Err(ErrorCode::StageInvalid.into());
+        }
+
+        *stage = next.to_code();
+        Ok(())
+    }
+
+    // for the instructions that need the escrow in a stage without moving it on
+    pub fn expect(stage: u8, expected: Stage) -> Result<()> {
+        if Stage::from(stage)? != expected {
+            msg!("Stage is invalid, state stage is {}", stage);
+            return Err(ErrorCode::StageInvalid.into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LobbyStage {
+    Open,
+    Locked,
+    Settled,
+    Cancelled,
+    Expired,
+}
+
+impl LobbyStage {
+    pub fn to_code(&self) -> u8 {
+        match self {
+            LobbyStage::Open => 1,
+            LobbyStage::Locked => 2,
+            LobbyStage::Settled => 3,
+            LobbyStage::Cancelled => 4,
+            LobbyStage::Expired => 5,
+        }
+    }
+
+    pub fn from(val: u8) -> std::result::Result<LobbyStage, ProgramError> {
+        match val {
+            1 => Ok(LobbyStage::Open),
+            2 => Ok(LobbyStage::Locked),
+            3 => Ok(LobbyStage::Settled),
+            4 => Ok(LobbyStage::Cancelled),
+            5 => Ok(LobbyStage::Expired),
+            unknown_value => {
+                msg!("Unknown lobby stage: {}", unknown_value);
+                Err(error!(ErrorCode::StageInvalid).into())
+            }
+        }
+    }
+
+    pub fn can_transition_to(&self, next: LobbyStage) -> bool {
+        matches!(
+            (self, next),
+            (LobbyStage::Open, LobbyStage::Locked)
+                | (LobbyStage::Open, LobbyStage::Cancelled)
+                | (LobbyStage::Locked, LobbyStage::Settled)
+                | (LobbyStage::Open, LobbyStage::Expired)
+                | (LobbyStage::Locked, LobbyStage::Expired)
+        )
+    }
+
+    // every stage change of a lobby goes through here
+    pub fn transition(stage: &mut u8, next: LobbyStage) -> Result<()> {
+        if !LobbyStage::from(*stage)?.can_transition_to(next) {
+            msg!(
+                "Stage is invalid, can't move lobby from stage {} to {}",
+                stage,
+                next.to_code()
+            );
+            return Err(ErrorCode::StageInvalid.into());
+        }
+
+        *stage = next.to_code();
+        Ok(())
+    }
+}
+
+pub const BASIS_POINTS: u64 = 10_000;
+
+// the most the admin can set the house's cut to, a tenth of every pot
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+// the house's cut of a pot, rounded down in favour of the winner
+pub fn rake(amount: u64, fee_bps: u16) -> u64 {
+    ((amount as u128) * (fee_bps as u128) / (BASIS_POINTS as u128)) as u64
+}
+
+// splits amount by player_share : beneficiary_share, the player's side is rounded down
+// so any dust always lands with the beneficiary
+pub fn split_pot(amount: u64, player_share: u64, beneficiary_share: u64) -> Result<(u64, u64)> {
+    let total_shares = (player_share as u128) + (beneficiary_share as u128);
+    require!(total_shares > 0, ErrorCode::InvalidSplit);
+
+    let player_amount = ((amount as u128) * (player_share as u128) / total_shares) as u64;
+
+    Ok((player_amount, amount - player_amount))
+}
+
+// what a pot's token account holds beyond the pot, anyone can send tokens to it, so the
+// payouts sweep this instead of expecting an exact balance the close would trip over
+pub fn residue(balance: u64, pot: u64) -> Result<u64> {
+    balance
+        .checked_sub(pot)
+        .ok_or_else(|| error!(ErrorCode::NotEnoughBalance))
 }
 
 pub fn secure_transfer_cpi<'info>(
@@ -51,6 +203,44 @@ pub fn secure_transfer_cpi<'info>(
     anchor_spl::token::transfer(secure_transfer_cpi_ctx, amount)
 }
 
+pub fn mint_to_cpi<'info>(
+    amount: u64,
+    mint_authority: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    receiver_token_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mint_to_instruction = anchor_spl::token::MintTo {
+        mint,
+        to: receiver_token_account,
+        authority: mint_authority,
+    };
+
+    let mint_to_cpi_ctx =
+        CpiContext::new_with_signer(token_program, mint_to_instruction, signer_seeds);
+
+    anchor_spl::token::mint_to(mint_to_cpi_ctx, amount)
+}
+
+pub fn burn_cpi<'info>(
+    amount: u64,
+    owner: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let burn_instruction = anchor_spl::token::Burn {
+        mint,
+        from: token_account,
+        authority: owner,
+    };
+
+    let burn_cpi_ctx = CpiContext::new(token_program, burn_instruction);
+
+    anchor_spl::token::burn(burn_cpi_ctx, amount)
+}
+
 pub fn close_account_cpi<'info>(
     escrow_account: AccountInfo<'info>,
     player: AccountInfo<'info>,
@@ -72,3 +262,132 @@ pub fn close_account_cpi<'info>(
 
     anchor_spl::token::close_account(close_escrow_account_cpi_ctx)
 }
+
+pub fn system_transfer_cpi<'info>(
+    amount: u64,
+    sender: AccountInfo<'info>,
+    receiver: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let system_transfer_instruction = anchor_lang::system_program::Transfer {
+        from: sender,
+        to: receiver,
+    };
+
+    let system_transfer_cpi_ctx = CpiContext::new(system_program, system_transfer_instruction);
+
+    anchor_lang::system_program::transfer(system_transfer_cpi_ctx, amount)
+}
+
+// moves lamports out of an account owned by this program, the system program can't
+// transfer from accounts that carry data
+pub fn debit_lamports<'info>(
+    amount: u64,
+    sender: &AccountInfo<'info>,
+    receiver: &AccountInfo<'info>,
+) -> Result<()> {
+    let sender_balance = sender
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::NotEnoughBalance)?;
+    let receiver_balance = receiver.lamports().checked_add(amount).unwrap();
+
+    **sender.try_borrow_mut_lamports()? = sender_balance;
+    **receiver.try_borrow_mut_lamports()? = receiver_balance;
+
+    Ok(())
+}
+
+// layout of the message the game server signs for a settled run
+pub fn result_message(escrow: &Pubkey, winner: &Pubkey, nonce: u64, expiry: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 + 32 + 8 + 8);
+    message.extend_from_slice(escrow.as_ref());
+    message.extend_from_slice(winner.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+// layout of the message the game server signs for a drawn or partially cleared run
+pub fn split_message(
+    escrow: &Pubkey,
+    player_share: u64,
+    beneficiary_share: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 + 8 + 8 + 8 + 8);
+    message.extend_from_slice(escrow.as_ref());
+    message.extend_from_slice(&player_share.to_le_bytes());
+    message.extend_from_slice(&beneficiary_share.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+// layout of the message the game server signs for a settled lobby, winners in finishing order
+pub fn lobby_message(lobby: &Pubkey, winners: &[Pubkey], expiry: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 + 32 * winners.len() + 8);
+    message.extend_from_slice(lobby.as_ref());
+    for winner in winners {
+        message.extend_from_slice(winner.as_ref());
+    }
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+// the ed25519 program has already checked the signature by the time we run,
+// so we only need to make sure it signed the right message with the right key
+pub fn verify_referee_attestation(
+    instructions_sysvar: &AccountInfo,
+    referee: &Pubkey,
+    expected_message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, ErrorCode::InvalidAttestation);
+
+    let ed25519_instruction =
+        load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require!(
+        ed25519_instruction.program_id == ed25519_program::ID,
+        ErrorCode::InvalidAttestation
+    );
+
+    let data = &ed25519_instruction.data;
+    // one signature, one padding byte, then the 14 byte offsets struct
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidAttestation);
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_instruction_index = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_instruction_index = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_instruction_index = read_u16(14);
+
+    // everything has to live inside the ed25519 instruction itself
+    require!(
+        signature_instruction_index == u16::MAX
+            && public_key_instruction_index == u16::MAX
+            && message_instruction_index == u16::MAX,
+        ErrorCode::InvalidAttestation
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(ErrorCode::InvalidAttestation)?;
+    require!(
+        public_key == referee.as_ref(),
+        ErrorCode::UnauthorizedReferee
+    );
+
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidAttestation)?;
+    require!(
+        message == expected_message,
+        ErrorCode::InvalidAttestation
+    );
+
+    Ok(())
+}
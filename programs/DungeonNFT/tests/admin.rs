@@ -0,0 +1,419 @@
+mod common;
+
+use anchor_lang::AnchorSerialize;
+use common::*;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::state::{
+    MarketState, MarketStateV0, MintRegistry, ProgramConfig, TransactionState,
+    TransactionStateV0, TreasuryState,
+};
+use dungeon_nft_sdk::{instructions, pda, EscrowKeys};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+
+async fn config(harness: &Harness) -> ProgramConfig {
+    harness.account(&pda::find_program_config().0).await
+}
+
+#[tokio::test]
+async fn admin_tunes_the_config() {
+    let harness = Harness::new().await;
+    let admin = harness.admin();
+    let referee = Keypair::new().pubkey();
+    let arbiter = Keypair::new().pubkey();
+
+    harness
+        .send(
+            &[
+                instructions::set_referee(&admin, referee),
+                instructions::set_settlement_timeout(&admin, 60),
+                instructions::set_dispute_policy(&admin, arbiter, 120),
+                instructions::set_fee(&admin, 100),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let config = config(&harness).await;
+    assert_eq!(config.referee, referee);
+    assert_eq!(config.settlement_timeout, 60);
+    assert_eq!((config.arbiter, config.dispute_window), (arbiter, 120));
+    assert_eq!(config.fee_bps, 100);
+
+    assert_error(
+        harness
+            .send(&[instructions::set_fee(&admin, 1_001)], &[])
+            .await,
+        ErrorCode::InvalidFee,
+    );
+    assert_error(
+        harness
+            .send(&[instructions::set_settlement_timeout(&admin, 0)], &[])
+            .await,
+        ErrorCode::InvalidTimeout,
+    );
+
+    let stranger = harness.create_user().await;
+    assert_error(
+        harness
+            .send(
+                &[instructions::set_referee(&stranger.pubkey(), stranger.pubkey())],
+                &[&stranger],
+            )
+            .await,
+        ErrorCode::UnauthorizedAdmin,
+    );
+}
+
+#[tokio::test]
+async fn only_the_upgrade_authority_claims_the_config() {
+    let mut harness = Harness {
+        context: program_test().start_with_context().await,
+        house: Keypair::new(),
+        referee: Keypair::new(),
+        arbiter: Keypair::new(),
+    };
+    let payer = harness.admin();
+    let setup = instructions::config_setup(
+        &payer,
+        payer,
+        payer,
+        payer,
+        SETTLEMENT_TIMEOUT,
+        0,
+        FEE_BPS,
+    );
+
+    // the first caller after a deploy isn't the admin just for being first
+    harness.set_upgrade_authority(&Keypair::new().pubkey());
+    assert_error(
+        harness.send(&[setup.clone()], &[]).await,
+        ErrorCode::UnauthorizedUpgradeAuthority,
+    );
+
+    harness.set_upgrade_authority(&payer);
+    harness.advance(1).await;
+    harness.send(&[setup], &[]).await.unwrap();
+    assert_eq!(config(&harness).await.admin, payer);
+}
+
+#[tokio::test]
+async fn admin_is_handed_over_in_two_steps() {
+    let harness = Harness::new().await;
+    let old_admin = harness.admin();
+    let new_admin = harness.create_user().await;
+    let stranger = harness.create_user().await;
+
+    harness
+        .send(
+            &[instructions::propose_admin(&old_admin, new_admin.pubkey())],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(&[instructions::accept_admin(&stranger.pubkey())], &[&stranger])
+            .await,
+        ErrorCode::UnauthorizedPendingAdmin,
+    );
+
+    harness
+        .send(&[instructions::accept_admin(&new_admin.pubkey())], &[&new_admin])
+        .await
+        .unwrap();
+    assert_eq!(config(&harness).await.admin, new_admin.pubkey());
+
+    assert_error(
+        harness
+            .send(&[instructions::set_fee(&old_admin, 0)], &[])
+            .await,
+        ErrorCode::UnauthorizedAdmin,
+    );
+}
+
+#[tokio::test]
+async fn pause_stops_new_escrows() {
+    let mut harness = Harness::new().await;
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+
+    harness
+        .send(&[instructions::set_paused(&harness.admin(), true)], &[])
+        .await
+        .unwrap();
+
+    let keys = EscrowKeys {
+        player: player.pubkey(),
+        beneficiary: harness.house.pubkey(),
+        mint_of_token: mint,
+        session_id: 0,
+    };
+    assert_error(
+        harness
+            .send(&[instructions::transaction_setup(&keys, TOKEN, TOKEN)], &[&player])
+            .await,
+        ErrorCode::ProgramPaused,
+    );
+
+    harness
+        .send(&[instructions::set_paused(&harness.admin(), false)], &[])
+        .await
+        .unwrap();
+    harness.advance(1).await;
+    harness
+        .send(&[instructions::transaction_setup(&keys, TOKEN, TOKEN)], &[&player])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn registry_controls_the_wager_mints() {
+    let harness = Harness::new().await;
+    let admin = harness.admin();
+    let mint = harness.create_mint().await;
+
+    assert_error(
+        harness
+            .send(
+                &[instructions::register_mint(&admin, &mint, DECIMALS, 10, 5)],
+                &[],
+            )
+            .await,
+        ErrorCode::InvalidStakeLimits,
+    );
+    assert_error(
+        harness
+            .send(
+                &[instructions::register_mint(&admin, &mint, DECIMALS + 1, 1, 5)],
+                &[],
+            )
+            .await,
+        ErrorCode::MintDecimalsMismatch,
+    );
+
+    harness
+        .send(
+            &[instructions::register_mint(&admin, &mint, DECIMALS, 1, 5)],
+            &[],
+        )
+        .await
+        .unwrap();
+    let registry: MintRegistry = harness.account(&pda::find_mint_registry().0).await;
+    assert_eq!(registry.entries.len(), 1);
+    assert_eq!(registry.entries[0].max_stake, 5);
+
+    harness
+        .send(&[instructions::deregister_mint(&admin, mint)], &[])
+        .await
+        .unwrap();
+
+    let player = harness.create_user().await;
+    let keys = EscrowKeys {
+        player: player.pubkey(),
+        beneficiary: harness.house.pubkey(),
+        mint_of_token: mint,
+        session_id: 0,
+    };
+    assert_error(
+        harness
+            .send(&[instructions::transaction_setup(&keys, TOKEN, TOKEN)], &[&player])
+            .await,
+        ErrorCode::MintNotRegistered,
+    );
+}
+
+#[tokio::test]
+async fn treasuries_pay_out_what_they_collected() {
+    let harness = Harness::new().await;
+    let admin = harness.admin();
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+    harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+    harness.fund(&harness.house.pubkey(), &mint, 50 * TOKEN).await;
+
+    let keys = harness.open_escrow(&player, &mint, 20 * TOKEN, 20 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+    harness.settle(&keys, &keys.player).await.unwrap();
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+
+    let fee = TOKEN;
+    let destination = harness.fund(&admin, &mint, 0).await;
+    assert_error(
+        harness
+            .send(
+                &[instructions::withdraw_treasury(&admin, &mint, &destination, fee + 1)],
+                &[],
+            )
+            .await,
+        ErrorCode::NotEnoughBalance,
+    );
+    harness
+        .send(
+            &[instructions::withdraw_treasury(&admin, &mint, &destination, fee)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&destination).await, fee);
+    let treasury: TreasuryState = harness.account(&pda::find_treasury_state(&mint).0).await;
+    assert_eq!(treasury.total_withdrawn, fee);
+
+    // the lamport treasury only gives out what's above its rent
+    let sol_keys = harness
+        .open_sol_escrow(&player, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL)
+        .await;
+    harness.fund_sol_escrow(&sol_keys, &player).await;
+    harness.settle_sol(&sol_keys, &sol_keys.beneficiary).await.unwrap();
+    harness
+        .send(&[instructions::sol_finalize_settlement(&sol_keys, 0)], &[])
+        .await
+        .unwrap();
+
+    let sol_fee = LAMPORTS_PER_SOL / 20;
+    let destination = Keypair::new().pubkey();
+    assert_error(
+        harness
+            .send(
+                &[instructions::sol_withdraw_treasury(&admin, &destination, sol_fee + 1)],
+                &[],
+            )
+            .await,
+        ErrorCode::NotEnoughBalance,
+    );
+    harness
+        .send(
+            &[instructions::sol_withdraw_treasury(&admin, &destination, sol_fee)],
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(harness.lamports(&destination).await, sol_fee);
+}
+
+#[tokio::test]
+async fn legacy_accounts_are_migrated_in_place() {
+    let escrow_address = Pubkey::new_unique();
+    let market_address = Pubkey::new_unique();
+    let player = Pubkey::new_unique();
+
+    // the layout every escrow had before the series, one amount for both sides
+    let legacy_escrow = TransactionStateV0 {
+        player,
+        beneficiary: Pubkey::new_unique(),
+        mint_of_token: Pubkey::new_unique(),
+        escrow_account: Pubkey::new_unique(),
+        amount_of_tokens: 20,
+        stage: 2,
+        state_bump: 254,
+        escrow_bump: 253,
+    };
+    let legacy_market = MarketStateV0 {
+        fee_numerator: 3,
+        fee_denominator: 1_000,
+        state_bump: 255,
+        token_vault_bump: 254,
+        sol_vault_bump: 253,
+    };
+
+    // old markets were allocated more than their fields take up
+    let mut legacy_market_data = legacy_market.try_to_vec().unwrap();
+    legacy_market_data.resize(MarketStateV0::LEN, 0);
+
+    let legacy_escrow_data = legacy_escrow.try_to_vec().unwrap();
+    assert_eq!(legacy_escrow_data.len(), TransactionStateV0::LEN);
+
+    // the right discriminator but neither the old nor the current size
+    let mut unknown_data = legacy_escrow_data.clone();
+    unknown_data.push(0);
+    let unknown_address = Pubkey::new_unique();
+
+    let mut program_test = program_test();
+    program_test.add_account(
+        escrow_address,
+        program_account::<TransactionState>(&legacy_escrow_data),
+    );
+    program_test.add_account(
+        unknown_address,
+        program_account::<TransactionState>(&unknown_data),
+    );
+    program_test.add_account(
+        market_address,
+        program_account::<MarketState>(&legacy_market_data),
+    );
+
+    let mut harness = Harness::start(program_test, 0).await;
+    let admin = harness.admin();
+
+    harness
+        .send(
+            &[
+                instructions::migrate_account(&admin, &escrow_address),
+                instructions::migrate_account(&admin, &market_address),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let escrow: TransactionState = harness.account(&escrow_address).await;
+    assert_eq!(escrow.version, TransactionState::VERSION);
+    assert_eq!(escrow.player, player);
+    assert_eq!((escrow.player_stake, escrow.house_stake), (20, 20));
+    assert!(escrow.is_funded());
+    assert_eq!(escrow.deposited(), 40);
+    assert_eq!((escrow.session_id, escrow.deadline), (0, 0));
+    assert_eq!((escrow.stage, escrow.escrow_bump), (2, 253));
+
+    let market: MarketState = harness.account(&market_address).await;
+    assert_eq!(market.version, MarketState::VERSION);
+    assert_eq!((market.fee_numerator, market.fee_denominator), (3, 1_000));
+
+    // the admin topped the rent up for the bigger layouts
+    let space = 8 + TransactionState::LEN;
+    assert_eq!(
+        harness.lamports(&escrow_address).await,
+        Rent::default().minimum_balance(space)
+    );
+    let space = 8 + MarketState::LEN;
+    assert_eq!(
+        harness.lamports(&market_address).await,
+        Rent::default().minimum_balance(space)
+    );
+
+    harness.advance(1).await;
+    for address in [escrow_address, market_address] {
+        assert_error(
+            harness
+                .send(&[instructions::migrate_account(&admin, &address)], &[])
+                .await,
+            ErrorCode::AccountAlreadyMigrated,
+        );
+    }
+    assert_error(
+        harness
+            .send(&[instructions::migrate_account(&admin, &unknown_address)], &[])
+            .await,
+        ErrorCode::UnknownAccountLayout,
+    );
+
+    // only the admin migrates
+    let stranger = harness.create_user().await;
+    assert_error(
+        harness
+            .send(
+                &[instructions::migrate_account(&stranger.pubkey(), &market_address)],
+                &[&stranger],
+            )
+            .await,
+        ErrorCode::UnauthorizedAdmin,
+    );
+}
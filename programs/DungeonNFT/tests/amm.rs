@@ -0,0 +1,678 @@
+mod common;
+
+use anchor_lang::AnchorSerialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::math::MINIMUM_LIQUIDITY;
+use dungeon_nft::state::{MarketState, MarketStateV0};
+use dungeon_nft_sdk::instructions::{self, SwapParams};
+use dungeon_nft_sdk::{pda, LiquidityProvider, MarketKeys};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transport::TransportError;
+
+// an empty market with a 0.3% fee, opened by the house
+async fn open_market(harness: &Harness) -> (MarketKeys, Pubkey, Pubkey) {
+    let token_mint = harness.create_mint().await;
+    let sol_mint = harness.create_mint().await;
+    let keys = MarketKeys {
+        beneficiary: harness.house.pubkey(),
+    };
+
+    harness
+        .send(
+            &[instructions::amm_setup(
+                &keys,
+                &token_mint,
+                &sol_mint,
+                3,
+                1_000,
+            )],
+            &[&harness.house],
+        )
+        .await
+        .unwrap();
+
+    (keys, token_mint, sol_mint)
+}
+
+// the market with 1000 of each side in the pool
+async fn setup() -> (Harness, MarketKeys, Pubkey, Pubkey) {
+    let harness = Harness::new().await;
+    let (keys, token_mint, sol_mint) = open_market(&harness).await;
+    deposit(
+        &harness,
+        &keys,
+        &token_mint,
+        &sol_mint,
+        1_000 * TOKEN,
+        1_000 * TOKEN,
+    )
+    .await
+    .unwrap();
+
+    (harness, keys, token_mint, sol_mint)
+}
+
+// the associated token accounts `owner` pays from and is paid into
+fn provider(
+    owner: &Pubkey,
+    keys: &MarketKeys,
+    token_mint: &Pubkey,
+    sol_mint: &Pubkey,
+) -> LiquidityProvider {
+    LiquidityProvider {
+        provider: *owner,
+        token_ata: get_associated_token_address(owner, token_mint),
+        sol_ata: get_associated_token_address(owner, sol_mint),
+        lp_ata: get_associated_token_address(owner, &keys.lp_mint()),
+    }
+}
+
+// the house seeding its own market
+async fn deposit(
+    harness: &Harness,
+    keys: &MarketKeys,
+    token_mint: &Pubkey,
+    sol_mint: &Pubkey,
+    token_amount: u64,
+    sol_amount: u64,
+) -> Result<(), TransportError> {
+    deposit_as(
+        harness,
+        keys,
+        &harness.house,
+        token_mint,
+        sol_mint,
+        token_amount,
+        sol_amount,
+    )
+    .await
+}
+
+// mints the depositor what it deposits, the lp shares land in its lp ata
+async fn deposit_as(
+    harness: &Harness,
+    keys: &MarketKeys,
+    depositor: &Keypair,
+    token_mint: &Pubkey,
+    sol_mint: &Pubkey,
+    token_amount: u64,
+    sol_amount: u64,
+) -> Result<(), TransportError> {
+    let owner = depositor.pubkey();
+    harness.fund(&owner, token_mint, token_amount).await;
+    harness.fund(&owner, sol_mint, sol_amount).await;
+    harness.fund(&owner, &keys.lp_mint(), 0).await;
+
+    harness
+        .send(
+            &[instructions::add_liquidity(
+                keys,
+                &provider(&owner, keys, token_mint, sol_mint),
+                token_amount,
+                sol_amount,
+            )],
+            &[depositor],
+        )
+        .await
+}
+
+#[tokio::test]
+async fn liquidity_lands_in_the_vaults() {
+    let (harness, keys, _, _) = setup().await;
+
+    let market: MarketState = harness.account(&keys.market_state()).await;
+    assert_eq!(market.version, MarketState::VERSION);
+    assert_eq!((market.fee_numerator, market.fee_denominator), (3, 1_000));
+
+    assert_eq!(
+        harness.token_balance(&keys.token_vault()).await,
+        1_000 * TOKEN
+    );
+    assert_eq!(
+        harness.token_balance(&keys.sol_vault()).await,
+        1_000 * TOKEN
+    );
+}
+
+#[tokio::test]
+async fn lp_shares_follow_the_deposits() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let house_lp_ata = get_associated_token_address(&harness.house.pubkey(), &keys.lp_mint());
+
+    // the geometric mean of the first deposit, less the locked minimum
+    let initial_shares = 1_000 * TOKEN - MINIMUM_LIQUIDITY;
+    assert_eq!(harness.token_balance(&house_lp_ata).await, initial_shares);
+    assert_eq!(
+        harness.token_balance(&keys.lp_lock()).await,
+        MINIMUM_LIQUIDITY
+    );
+
+    // a tenth of the pool again buys a tenth of the supply
+    deposit(
+        &harness,
+        &keys,
+        &token_mint,
+        &sol_mint,
+        100 * TOKEN,
+        100 * TOKEN,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        harness.token_balance(&house_lp_ata).await,
+        initial_shares + 100 * TOKEN
+    );
+    assert_eq!(
+        harness.token_balance(&keys.lp_lock()).await,
+        MINIMUM_LIQUIDITY
+    );
+}
+
+#[tokio::test]
+async fn liquidity_comes_back_out_pro_rata() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let house = harness.house.pubkey();
+    let house_token_ata = get_associated_token_address(&house, &token_mint);
+    let house_sol_ata = get_associated_token_address(&house, &sol_mint);
+    let house_lp_ata = get_associated_token_address(&house, &keys.lp_mint());
+
+    // half the supply, which is half of both vaults
+    let lp_amount = 500 * TOKEN;
+    let remove = |min_token_amount, min_sol_amount| {
+        instructions::remove_liquidity(
+            &keys,
+            &provider(&house, &keys, &token_mint, &sol_mint),
+            lp_amount,
+            min_token_amount,
+            min_sol_amount,
+        )
+    };
+
+    assert_error(
+        harness
+            .send(&[remove(500 * TOKEN + 1, 0)], &[&harness.house])
+            .await,
+        ErrorCode::SlippageExceeded,
+    );
+    assert_error(
+        harness
+            .send(&[remove(0, 500 * TOKEN + 1)], &[&harness.house])
+            .await,
+        ErrorCode::SlippageExceeded,
+    );
+
+    harness
+        .send(&[remove(500 * TOKEN, 500 * TOKEN)], &[&harness.house])
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&house_token_ata).await, 500 * TOKEN);
+    assert_eq!(harness.token_balance(&house_sol_ata).await, 500 * TOKEN);
+    assert_eq!(
+        harness.token_balance(&keys.token_vault()).await,
+        500 * TOKEN
+    );
+    assert_eq!(harness.token_balance(&keys.sol_vault()).await, 500 * TOKEN);
+    assert_eq!(
+        harness.token_balance(&house_lp_ata).await,
+        500 * TOKEN - MINIMUM_LIQUIDITY
+    );
+
+    // the burnt shares are gone, so another half is more than the house holds
+    assert_error(
+        harness.send(&[remove(0, 0)], &[&harness.house]).await,
+        ErrorCode::NotEnoughBalance,
+    );
+}
+
+#[tokio::test]
+async fn anyone_provides_liquidity_to_the_house_market() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let player = harness.create_user().await;
+    let player_provider = provider(&player.pubkey(), &keys, &token_mint, &sol_mint);
+
+    // a tenth of the pool, so a tenth of the 1000 shares out
+    deposit_as(
+        &harness,
+        &keys,
+        &player,
+        &token_mint,
+        &sol_mint,
+        100 * TOKEN,
+        100 * TOKEN,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        harness.token_balance(&player_provider.lp_ata).await,
+        100 * TOKEN
+    );
+
+    // someone else's shares don't move for the house
+    let house_provider = provider(&harness.house.pubkey(), &keys, &token_mint, &sol_mint);
+    let stolen = LiquidityProvider {
+        lp_ata: player_provider.lp_ata,
+        ..house_provider
+    };
+    assert!(harness
+        .send(
+            &[instructions::remove_liquidity(&keys, &stolen, TOKEN, 0, 0)],
+            &[&harness.house],
+        )
+        .await
+        .is_err());
+
+    harness
+        .send(
+            &[instructions::remove_liquidity(
+                &keys,
+                &player_provider,
+                100 * TOKEN,
+                100 * TOKEN,
+                100 * TOKEN,
+            )],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        harness.token_balance(&player_provider.token_ata).await,
+        100 * TOKEN
+    );
+    assert_eq!(
+        harness.token_balance(&player_provider.sol_ata).await,
+        100 * TOKEN
+    );
+    assert_eq!(harness.token_balance(&player_provider.lp_ata).await, 0);
+    assert_eq!(
+        harness.token_balance(&keys.token_vault()).await,
+        1_000 * TOKEN
+    );
+}
+
+#[tokio::test]
+async fn first_deposit_has_to_cover_the_locked_minimum() {
+    let harness = Harness::new().await;
+    let (keys, token_mint, sol_mint) = open_market(&harness).await;
+
+    assert_error(
+        deposit(&harness, &keys, &token_mint, &sol_mint, 1_000, 1_000).await,
+        ErrorCode::InsufficientLiquidity,
+    );
+}
+
+#[tokio::test]
+async fn deposits_take_the_largest_balanced_pair() {
+    let harness = Harness::new().await;
+    let (keys, token_mint, sol_mint) = open_market(&harness).await;
+    let house = harness.house.pubkey();
+    let house_token_ata = get_associated_token_address(&house, &token_mint);
+    let house_sol_ata = get_associated_token_address(&house, &sol_mint);
+
+    // a hundred tokens to the sol, fewer sol than tokens in the pool
+    deposit(
+        &harness,
+        &keys,
+        &token_mint,
+        &sol_mint,
+        1_000 * TOKEN,
+        10 * TOKEN,
+    )
+    .await
+    .unwrap();
+
+    // the tokens are the limit, only the sol that goes with them is taken
+    deposit(
+        &harness,
+        &keys,
+        &token_mint,
+        &sol_mint,
+        100 * TOKEN,
+        10 * TOKEN,
+    )
+    .await
+    .unwrap();
+    assert_eq!(harness.token_balance(&house_token_ata).await, 0);
+    assert_eq!(harness.token_balance(&house_sol_ata).await, 9 * TOKEN);
+
+    // now the sol is the limit, 0.5 of it brings 50 of the 100 tokens along
+    harness.fund(&house, &token_mint, 100 * TOKEN).await;
+    harness
+        .send(
+            &[instructions::add_liquidity(
+                &keys,
+                &provider(&house, &keys, &token_mint, &sol_mint),
+                100 * TOKEN,
+                TOKEN / 2,
+            )],
+            &[&harness.house],
+        )
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(&house_token_ata).await, 50 * TOKEN);
+    assert_eq!(
+        harness.token_balance(&house_sol_ata).await,
+        8 * TOKEN + TOKEN / 2
+    );
+
+    assert_eq!(
+        harness.token_balance(&keys.token_vault()).await,
+        1_150 * TOKEN
+    );
+    assert_eq!(
+        harness.token_balance(&keys.sol_vault()).await,
+        11 * TOKEN + TOKEN / 2
+    );
+}
+
+#[tokio::test]
+async fn swap_keeps_the_constant_product() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let player = harness.create_user().await;
+    let player_token_ata = harness.fund(&player.pubkey(), &token_mint, 0).await;
+    let player_sol_ata = harness.fund(&player.pubkey(), &sol_mint, 200 * TOKEN).await;
+
+    harness
+        .send(
+            &[instructions::swap_tokens(
+                &keys,
+                &player.pubkey(),
+                &player_token_ata,
+                &player_sol_ata,
+                SwapParams {
+                    amount_in: 100 * TOKEN,
+                    sol_to_token: true,
+                    min_amount_out: 0,
+                    deadline: None,
+                },
+            )],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    // 99.7 of the 100 in count against the curve, the fee stays in the pool
+    let amount_out = 90_661_089;
+    assert_eq!(harness.token_balance(&player_token_ata).await, amount_out);
+    assert_eq!(harness.token_balance(&player_sol_ata).await, 100 * TOKEN);
+
+    let token_vault = harness.token_balance(&keys.token_vault()).await;
+    let sol_vault = harness.token_balance(&keys.sol_vault()).await;
+    assert_eq!(token_vault, 1_000 * TOKEN - amount_out);
+    assert_eq!(sol_vault, 1_100 * TOKEN);
+    assert!(token_vault as u128 * sol_vault as u128 >= (1_000 * TOKEN as u128).pow(2));
+
+    // and back the other way
+    harness
+        .send(
+            &[instructions::swap_tokens(
+                &keys,
+                &player.pubkey(),
+                &player_token_ata,
+                &player_sol_ata,
+                SwapParams {
+                    amount_in: amount_out,
+                    sol_to_token: false,
+                    min_amount_out: 0,
+                    deadline: None,
+                },
+            )],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&player_token_ata).await, 0);
+    // the fee is paid twice, so less than the 100 put in comes back
+    assert!(harness.token_balance(&player_sol_ata).await < 200 * TOKEN);
+}
+
+#[tokio::test]
+async fn swap_honours_the_minimum_out_and_the_deadline() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let player = harness.create_user().await;
+    let player_token_ata = harness.fund(&player.pubkey(), &token_mint, 0).await;
+    let player_sol_ata = harness.fund(&player.pubkey(), &sol_mint, 100 * TOKEN).await;
+
+    let amount_out = 90_661_089;
+    let now = harness.now().await;
+    let swap = |min_amount_out, deadline| {
+        instructions::swap_tokens(
+            &keys,
+            &player.pubkey(),
+            &player_token_ata,
+            &player_sol_ata,
+            SwapParams {
+                amount_in: 100 * TOKEN,
+                sol_to_token: true,
+                min_amount_out,
+                deadline,
+            },
+        )
+    };
+
+    assert_error(
+        harness
+            .send(&[swap(amount_out + 1, None)], &[&player])
+            .await,
+        ErrorCode::SlippageExceeded,
+    );
+    assert_error(
+        harness
+            .send(&[swap(amount_out, Some(now - 1))], &[&player])
+            .await,
+        ErrorCode::Expired,
+    );
+
+    harness
+        .send(&[swap(amount_out, Some(now + 60))], &[&player])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(&player_token_ata).await, amount_out);
+}
+
+#[tokio::test]
+async fn swap_rejects_more_than_the_balance() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let player = harness.create_user().await;
+    let player_token_ata = harness.fund(&player.pubkey(), &token_mint, 0).await;
+    let player_sol_ata = harness.fund(&player.pubkey(), &sol_mint, 10 * TOKEN).await;
+
+    assert_error(
+        harness
+            .send(
+                &[instructions::swap_tokens(
+                    &keys,
+                    &player.pubkey(),
+                    &player_token_ata,
+                    &player_sol_ata,
+                    SwapParams {
+                        amount_in: 11 * TOKEN,
+                        sol_to_token: true,
+                        min_amount_out: 0,
+                        deadline: None,
+                    },
+                )],
+                &[&player],
+            )
+            .await,
+        ErrorCode::NotEnoughBalance,
+    );
+}
+
+#[tokio::test]
+async fn only_the_house_opens_a_market() {
+    let harness = Harness::new().await;
+    let token_mint = harness.create_mint().await;
+    let sol_mint = harness.create_mint().await;
+    let impostor = harness.create_user().await;
+
+    let keys = MarketKeys {
+        beneficiary: impostor.pubkey(),
+    };
+    assert_error(
+        harness
+            .send(
+                &[instructions::amm_setup(
+                    &keys,
+                    &token_mint,
+                    &sol_mint,
+                    3,
+                    1_000,
+                )],
+                &[&impostor],
+            )
+            .await,
+        ErrorCode::UnauthorizedHouse,
+    );
+}
+
+#[tokio::test]
+async fn pause_stops_swaps() {
+    let (harness, keys, token_mint, sol_mint) = setup().await;
+    let player = harness.create_user().await;
+    let player_token_ata = harness.fund(&player.pubkey(), &token_mint, 0).await;
+    let player_sol_ata = harness.fund(&player.pubkey(), &sol_mint, 10 * TOKEN).await;
+
+    harness
+        .send(&[instructions::set_paused(&harness.admin(), true)], &[])
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(
+                &[instructions::swap_tokens(
+                    &keys,
+                    &player.pubkey(),
+                    &player_token_ata,
+                    &player_sol_ata,
+                    SwapParams {
+                        amount_in: TOKEN,
+                        sol_to_token: true,
+                        min_amount_out: 0,
+                        deadline: None,
+                    },
+                )],
+                &[&player],
+            )
+            .await,
+        ErrorCode::ProgramPaused,
+    );
+}
+
+#[tokio::test]
+async fn a_new_market_takes_no_lp_setup() {
+    let harness = Harness::new().await;
+    let (keys, token_mint, _) = open_market(&harness).await;
+
+    let market: MarketState = harness.account(&keys.market_state()).await;
+    assert!(market.has_lp_mint);
+    assert_error(
+        harness
+            .send(
+                &[instructions::lp_setup(&keys, &token_mint)],
+                &[&harness.house],
+            )
+            .await,
+        ErrorCode::LpMintExists,
+    );
+}
+
+#[tokio::test]
+async fn a_migrated_market_hands_its_reserves_to_the_first_provider() {
+    let mut harness = Harness::new().await;
+    let token_mint = harness.create_mint().await;
+    let sol_mint = harness.create_mint().await;
+    let house = harness.house.pubkey();
+    let keys = MarketKeys { beneficiary: house };
+    let market_state = keys.market_state();
+
+    // a market from before LP shares, 1000 of each side in the vaults and nobody holding a claim
+    let legacy = MarketStateV0 {
+        fee_numerator: 3,
+        fee_denominator: 1_000,
+        state_bump: pda::find_market_state(&house).1,
+        token_vault_bump: pda::find_token_vault(&market_state, &house).1,
+        sol_vault_bump: pda::find_sol_vault(&market_state, &house).1,
+    };
+    let mut data = legacy.try_to_vec().unwrap();
+    data.resize(MarketStateV0::LEN, 0);
+    harness.context.set_account(
+        &market_state,
+        &program_account::<MarketState>(&data).into(),
+    );
+    for (vault, mint) in [(keys.token_vault(), token_mint), (keys.sol_vault(), sol_mint)] {
+        harness.context.set_account(
+            &vault,
+            &token_account(&mint, &market_state, 1_000 * TOKEN).into(),
+        );
+    }
+
+    let admin = harness.admin();
+    harness
+        .send(&[instructions::migrate_account(&admin, &market_state)], &[])
+        .await
+        .unwrap();
+    let market: MarketState = harness.account(&market_state).await;
+    assert!(!market.has_lp_mint);
+
+    harness
+        .send(&[instructions::lp_setup(&keys, &token_mint)], &[&harness.house])
+        .await
+        .unwrap();
+
+    let market: MarketState = harness.account(&market_state).await;
+    assert_eq!(market.lp_mint_bump, pda::find_lp_mint(&market_state, &house).1);
+    assert!(market.has_lp_mint);
+    assert_eq!(market.owner, house);
+
+    harness.advance(1).await;
+    assert_error(
+        harness
+            .send(&[instructions::lp_setup(&keys, &token_mint)], &[&harness.house])
+            .await,
+        ErrorCode::LpMintExists,
+    );
+
+    // no shares are out, so the deposit prices the whole vaults
+    deposit(
+        &harness,
+        &keys,
+        &token_mint,
+        &sol_mint,
+        1_000 * TOKEN,
+        1_000 * TOKEN,
+    )
+    .await
+    .unwrap();
+
+    let house_token_ata = get_associated_token_address(&house, &token_mint);
+    let house_sol_ata = get_associated_token_address(&house, &sol_mint);
+    let house_lp_ata = get_associated_token_address(&house, &keys.lp_mint());
+    let lp_amount = harness.token_balance(&house_lp_ata).await;
+    assert_eq!(lp_amount, 1_000 * TOKEN - MINIMUM_LIQUIDITY);
+
+    harness
+        .send(
+            &[instructions::remove_liquidity(
+                &keys,
+                &provider(&house, &keys, &token_mint, &sol_mint),
+                lp_amount,
+                0,
+                0,
+            )],
+            &[&harness.house],
+        )
+        .await
+        .unwrap();
+
+    // the deposit and the legacy reserves, less the locked minimum's share
+    let withdrawn = 2_000 * TOKEN - 2 * MINIMUM_LIQUIDITY;
+    assert_eq!(harness.token_balance(&house_token_ata).await, withdrawn);
+    assert_eq!(harness.token_balance(&house_sol_ata).await, withdrawn);
+}
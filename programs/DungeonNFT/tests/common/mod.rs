@@ -0,0 +1,488 @@
+//! Shared setup for the program-test suites. The program runs natively through its `entry`,
+//! the token programs are the ones bundled with solana-program-test.
+
+#![allow(dead_code)]
+
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::state::{SessionCounter, TransactionState};
+use dungeon_nft_sdk::{accounts, instructions, pda, result_message, EscrowKeys, SolEscrowKeys};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    ed25519_instruction::new_ed25519_instruction,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+
+pub const DECIMALS: u8 = 6;
+pub const TOKEN: u64 = 1_000_000;
+
+pub const MIN_STAKE: u64 = TOKEN;
+pub const MAX_STAKE: u64 = 100 * TOKEN;
+
+pub const SETTLEMENT_TIMEOUT: i64 = 3_600;
+pub const FEE_BPS: u16 = 250;
+
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("dungeon_nft", dungeon_nft::ID, processor!(dungeon_nft::entry))
+}
+
+// a rent exempt, program owned account holding `data` behind the discriminator of `T`,
+// for layouts the current instructions can no longer create
+pub fn program_account<T: Discriminator>(data: &[u8]) -> Account {
+    let mut bytes = T::discriminator().to_vec();
+    bytes.extend_from_slice(data);
+
+    Account {
+        lamports: Rent::default().minimum_balance(bytes.len()),
+        data: bytes,
+        owner: dungeon_nft::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// an initialized token account of `mint` owned by `owner`, already holding `amount`, for vaults
+// a legacy instruction would have created
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// the upgradeable loader's record of the program, naming `authority` as its upgrade authority
+pub fn program_data_account(authority: &Pubkey) -> Account {
+    let state = UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(*authority),
+    };
+    let space = UpgradeableLoaderState::programdata_data_offset().unwrap();
+
+    Account::new_data_with_space(
+        Rent::default().minimum_balance(space),
+        &state,
+        space,
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap()
+}
+
+// the ed25519 program instruction carrying `signer`'s signature over `message`
+pub fn ed25519_attestation(signer: &Keypair, message: &[u8]) -> Instruction {
+    let keypair = ed25519_dalek::Keypair::from_bytes(&signer.to_bytes()).unwrap();
+    new_ed25519_instruction(&keypair, message)
+}
+
+// what a failed transaction ended with
+pub fn instruction_error(result: Result<(), TransportError>) -> InstructionError {
+    match result.expect_err("transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, error) => error,
+        error => panic!("unexpected transaction error {:?}", error),
+    }
+}
+
+pub fn assert_error(result: Result<(), TransportError>, code: ErrorCode) {
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::Custom(ERROR_CODE_OFFSET + code as u32)
+    );
+}
+
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub house: Keypair,
+    pub referee: Keypair,
+    pub arbiter: Keypair,
+}
+
+impl Harness {
+    pub async fn new() -> Self {
+        Harness::start(program_test(), 0).await
+    }
+
+    // the config, the mint registry and the lamport treasury are set up with the payer as admin,
+    // which is made the program's upgrade authority first
+    pub async fn start(program_test: ProgramTest, dispute_window: i64) -> Self {
+        let mut harness = Harness {
+            context: program_test.start_with_context().await,
+            house: Keypair::new(),
+            referee: Keypair::new(),
+            arbiter: Keypair::new(),
+        };
+
+        let admin = harness.admin();
+        harness.set_upgrade_authority(&admin);
+        harness.airdrop(&harness.house.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+        harness
+            .send(
+                &[
+                    instructions::config_setup(
+                        &admin,
+                        harness.house.pubkey(),
+                        harness.referee.pubkey(),
+                        harness.arbiter.pubkey(),
+                        SETTLEMENT_TIMEOUT,
+                        dispute_window,
+                        FEE_BPS,
+                    ),
+                    instructions::mint_registry_setup(&admin),
+                    instructions::sol_treasury_setup(&admin),
+                ],
+                &[],
+            )
+            .await
+            .unwrap();
+
+        harness
+    }
+
+    pub fn set_upgrade_authority(&mut self, authority: &Pubkey) {
+        self.context.set_account(
+            &pda::find_program_data().0,
+            &program_data_account(authority).into(),
+        );
+    }
+
+    // the client is a cheap handle, cloning it keeps every helper on `&self`
+    pub fn banks(&self) -> BanksClient {
+        self.context.banks_client.clone()
+    }
+
+    pub fn admin(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    // the payer signs every transaction, `signers` only lists the others
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransportError> {
+        let mut banks = self.banks();
+        let blockhash = banks.get_latest_blockhash().await.unwrap();
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        banks
+            .process_transaction(transaction)
+            .await
+            .map_err(TransportError::from)
+    }
+
+    pub async fn now(&self) -> i64 {
+        self.banks().get_sysvar::<Clock>().await.unwrap().unix_timestamp
+    }
+
+    // warps to a later slot, which also brings a fresh blockhash so a transaction that was
+    // rejected before can be sent again
+    pub async fn set_time(&mut self, unix_timestamp: i64) {
+        let slot = self.banks().get_sysvar::<Clock>().await.unwrap().slot;
+        self.context.warp_to_slot(slot + 2).unwrap();
+
+        let mut clock = self.banks().get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn advance(&mut self, seconds: i64) {
+        let now = self.now().await;
+        self.set_time(now + seconds).await;
+    }
+
+    pub async fn airdrop(&self, to: &Pubkey, lamports: u64) {
+        self.send(
+            &[system_instruction::transfer(&self.admin(), to, lamports)],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn create_user(&self) -> Keypair {
+        let user = Keypair::new();
+        self.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+        user
+    }
+
+    pub async fn exists(&self, address: &Pubkey) -> bool {
+        self.banks().get_account(*address).await.unwrap().is_some()
+    }
+
+    pub async fn lamports(&self, address: &Pubkey) -> u64 {
+        self.banks().get_balance(*address).await.unwrap()
+    }
+
+    pub async fn account<T: AccountDeserialize + Discriminator>(&self, address: &Pubkey) -> T {
+        let account = self
+            .banks()
+            .get_account(*address)
+            .await
+            .unwrap()
+            .expect("account not found");
+        accounts::decode(&account.data).unwrap()
+    }
+
+    pub async fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        let account = self
+            .banks()
+            .get_account(*token_account)
+            .await
+            .unwrap()
+            .expect("token account not found");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    // a plain mint with the payer as its authority
+    pub async fn create_mint(&self) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.banks().get_rent().await.unwrap();
+
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &self.admin(),
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &self.admin(),
+                    None,
+                    DECIMALS,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+
+        mint.pubkey()
+    }
+
+    // a mint that is registered for wagers and has its treasury
+    pub async fn wager_mint(&self) -> Pubkey {
+        let mint = self.create_mint().await;
+        let admin = self.admin();
+
+        self.send(
+            &[
+                instructions::register_mint(&admin, &mint, DECIMALS, MIN_STAKE, MAX_STAKE),
+                instructions::treasury_setup(&admin, &mint),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+        mint
+    }
+
+    // creates the owner's associated token account if it's missing and mints `amount` into it
+    pub async fn fund(&self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let token_account = get_associated_token_address(owner, mint);
+
+        let mut instructions = Vec::new();
+        if !self.exists(&token_account).await {
+            instructions.push(
+                spl_associated_token_account::create_associated_token_account(
+                    &self.admin(),
+                    owner,
+                    mint,
+                ),
+            );
+        }
+        if amount > 0 {
+            instructions.push(
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    mint,
+                    &token_account,
+                    &self.admin(),
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            );
+        }
+
+        if !instructions.is_empty() {
+            self.send(&instructions, &[]).await.unwrap();
+        }
+
+        token_account
+    }
+
+    pub async fn next_session_id(&self, player: &Pubkey) -> u64 {
+        let counter = pda::find_session_counter(player).0;
+        if self.exists(&counter).await {
+            self.account::<SessionCounter>(&counter)
+                .await
+                .next_session_id
+        } else {
+            0
+        }
+    }
+
+    pub async fn open_escrow(
+        &self,
+        player: &Keypair,
+        mint: &Pubkey,
+        player_stake: u64,
+        house_stake: u64,
+    ) -> EscrowKeys {
+        let keys = EscrowKeys {
+            player: player.pubkey(),
+            beneficiary: self.house.pubkey(),
+            mint_of_token: *mint,
+            session_id: self.next_session_id(&player.pubkey()).await,
+        };
+
+        self.send(
+            &[instructions::transaction_setup(&keys, player_stake, house_stake)],
+            &[player],
+        )
+        .await
+        .unwrap();
+
+        keys
+    }
+
+    // both sides pay in the stakes the escrow was opened with
+    pub async fn fund_escrow(&self, keys: &EscrowKeys, player: &Keypair) {
+        let state: TransactionState = self.account(&keys.transaction_state()).await;
+        self.send(
+            &[
+                instructions::deposit_by_player(keys, state.player_stake),
+                instructions::deposit_by_house(keys, state.house_stake),
+            ],
+            &[player, &self.house],
+        )
+        .await
+        .unwrap();
+    }
+
+    // the referee's verdict, signed by `signer` so unauthorized results can be tried as well
+    pub async fn settle_signed_by(
+        &self,
+        keys: &EscrowKeys,
+        winner: &Pubkey,
+        signer: &Keypair,
+    ) -> Result<(), TransportError> {
+        let nonce = self
+            .account::<TransactionState>(&keys.transaction_state())
+            .await
+            .result_nonce;
+        let expiry = self.now().await + 60;
+        let message = result_message(&keys.escrow_account(), winner, nonce, expiry);
+
+        self.send(
+            &[
+                ed25519_attestation(signer, &message),
+                instructions::transfer_to_winner(keys, *winner, nonce, expiry),
+            ],
+            &[],
+        )
+        .await
+    }
+
+    pub async fn settle(&self, keys: &EscrowKeys, winner: &Pubkey) -> Result<(), TransportError> {
+        self.settle_signed_by(keys, winner, &self.referee).await
+    }
+
+    pub async fn open_sol_escrow(
+        &self,
+        player: &Keypair,
+        player_stake: u64,
+        house_stake: u64,
+    ) -> SolEscrowKeys {
+        let keys = SolEscrowKeys {
+            player: player.pubkey(),
+            beneficiary: self.house.pubkey(),
+            session_id: self.next_session_id(&player.pubkey()).await,
+        };
+
+        self.send(
+            &[instructions::sol_transaction_setup(&keys, player_stake, house_stake)],
+            &[player],
+        )
+        .await
+        .unwrap();
+
+        keys
+    }
+
+    pub async fn fund_sol_escrow(&self, keys: &SolEscrowKeys, player: &Keypair) {
+        let state: TransactionState = self.account(&keys.transaction_state()).await;
+        self.send(
+            &[
+                instructions::sol_deposit_by_player(keys, state.player_stake),
+                instructions::sol_deposit_by_house(keys, state.house_stake),
+            ],
+            &[player, &self.house],
+        )
+        .await
+        .unwrap();
+    }
+
+    // lamport escrows are attested against the state account, they have no token account
+    pub async fn settle_sol(
+        &self,
+        keys: &SolEscrowKeys,
+        winner: &Pubkey,
+    ) -> Result<(), TransportError> {
+        let transaction_state = keys.transaction_state();
+        let nonce = self
+            .account::<TransactionState>(&transaction_state)
+            .await
+            .result_nonce;
+        let expiry = self.now().await + 60;
+        let message = result_message(&transaction_state, winner, nonce, expiry);
+
+        self.send(
+            &[
+                ed25519_attestation(&self.referee, &message),
+                instructions::sol_transfer_to_winner(keys, *winner, nonce, expiry),
+            ],
+            &[],
+        )
+        .await
+    }
+}
@@ -0,0 +1,761 @@
+mod common;
+
+use anchor_lang::AnchorSerialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::state::{PlayerProfile, TransactionState, TransactionStateV0, TreasuryState};
+use dungeon_nft_sdk::{instructions, pda, split_message, EscrowKeys, Stage};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::signature::{Keypair, Signer};
+
+// a fresh mint, a player holding 50 tokens and a house holding 500
+async fn setup() -> (Harness, Keypair, solana_sdk::pubkey::Pubkey) {
+    let harness = Harness::new().await;
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+
+    harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+    harness.fund(&harness.house.pubkey(), &mint, 500 * TOKEN).await;
+
+    (harness, player, mint)
+}
+
+async fn stage(harness: &Harness, transaction_state: &solana_sdk::pubkey::Pubkey) -> Stage {
+    let state: TransactionState = harness.account(transaction_state).await;
+    Stage::from(state.stage).unwrap()
+}
+
+#[tokio::test]
+async fn winner_takes_the_pot_minus_the_rake() {
+    let (harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Initialized);
+
+    harness.fund_escrow(&keys, &player).await;
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::FundsDeposited);
+    assert_eq!(harness.token_balance(&keys.escrow_account()).await, 40 * TOKEN);
+
+    harness.settle(&keys, &keys.player).await.unwrap();
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::ResultSubmitted);
+
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+
+    // 2.5% of the 40 token pot
+    let fee = TOKEN;
+    assert_eq!(harness.token_balance(&player_ata).await, 80 * TOKEN - fee);
+    assert_eq!(harness.token_balance(&house_ata).await, 470 * TOKEN);
+    assert_eq!(
+        harness
+            .token_balance(&pda::find_treasury_vault(&mint).0)
+            .await,
+        fee
+    );
+    assert!(!harness.exists(&keys.escrow_account()).await);
+
+    let treasury: TreasuryState = harness.account(&pda::find_treasury_state(&mint).0).await;
+    assert_eq!(treasury.total_fees_collected, fee);
+
+    let profile: PlayerProfile = harness
+        .account(&pda::find_player_profile(&player.pubkey()).0)
+        .await;
+    assert_eq!(profile.games_played, 1);
+    assert_eq!(profile.wins, 1);
+    assert_eq!(profile.current_streak, 1);
+
+    harness
+        .send(
+            &[instructions::close_transaction(
+                &keys.transaction_state(),
+                &keys.player,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    assert!(!harness.exists(&keys.transaction_state()).await);
+}
+
+#[tokio::test]
+async fn split_settlement_pays_both_sides() {
+    let (harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 20 * TOKEN, 20 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    let expiry = harness.now().await + 60;
+    let message = split_message(&keys.escrow_account(), 3, 1, state.result_nonce, expiry);
+    harness
+        .send(
+            &[
+                ed25519_attestation(&harness.referee, &message),
+                instructions::split_settlement(&keys, 3, 1, state.result_nonce, expiry),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+
+    // 39 tokens after the rake, split 3 : 1
+    assert_eq!(harness.token_balance(&player_ata).await, 30 * TOKEN + 29_250_000);
+    assert_eq!(harness.token_balance(&house_ata).await, 480 * TOKEN + 9_750_000);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::EscrowComplete);
+}
+
+#[tokio::test]
+async fn a_split_needs_a_share_on_one_side() {
+    let (harness, player, mint) = setup().await;
+
+    let keys = harness.open_escrow(&player, &mint, 20 * TOKEN, 20 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    let expiry = harness.now().await + 60;
+    let message = split_message(&keys.escrow_account(), 0, 0, state.result_nonce, expiry);
+    assert_error(
+        harness
+            .send(
+                &[
+                    ed25519_attestation(&harness.referee, &message),
+                    instructions::split_settlement(&keys, 0, 0, state.result_nonce, expiry),
+                ],
+                &[],
+            )
+            .await,
+        ErrorCode::InvalidSplit,
+    );
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::FundsDeposited);
+}
+
+#[tokio::test]
+async fn overturned_dispute_pays_the_other_side() {
+    let mut harness = Harness::start(program_test(), 600).await;
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+    let player_ata = harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+    harness.fund(&harness.house.pubkey(), &mint, 500 * TOKEN).await;
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 10 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+    harness.settle(&keys, &keys.beneficiary).await.unwrap();
+
+    assert_error(
+        harness
+            .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+            .await,
+        ErrorCode::ChallengeWindowOpen,
+    );
+
+    // the house won, so it has nothing to dispute
+    assert_error(
+        harness
+            .send(
+                &[instructions::raise_dispute(
+                    &keys.transaction_state(),
+                    &harness.house.pubkey(),
+                )],
+                &[&harness.house],
+            )
+            .await,
+        ErrorCode::UnauthorizedDisputer,
+    );
+
+    harness
+        .send(
+            &[instructions::raise_dispute(&keys.transaction_state(), &keys.player)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Disputed);
+
+    let stranger = harness.create_user().await;
+    assert_error(
+        harness
+            .send(
+                &[instructions::resolve_dispute(
+                    &keys.transaction_state(),
+                    &stranger.pubkey(),
+                    false,
+                )],
+                &[&stranger],
+            )
+            .await,
+        ErrorCode::UnauthorizedArbiter,
+    );
+
+    harness
+        .send(
+            &[instructions::resolve_dispute(
+                &keys.transaction_state(),
+                &harness.arbiter.pubkey(),
+                false,
+            )],
+            &[&harness.arbiter],
+        )
+        .await
+        .unwrap();
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    assert_eq!((state.player_share, state.beneficiary_share), (1, 0));
+
+    // a later slot keeps this finalize apart from the one rejected above
+    harness.advance(1).await;
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(&player_ata).await, 60 * TOKEN - 500_000);
+}
+
+#[tokio::test]
+async fn an_unanswered_dispute_lets_the_verdict_stand() {
+    let mut harness = Harness::start(program_test(), 600).await;
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+    let player_ata = harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+    let house_ata = harness.fund(&harness.house.pubkey(), &mint, 500 * TOKEN).await;
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 10 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+    harness.settle(&keys, &keys.beneficiary).await.unwrap();
+    harness
+        .send(
+            &[instructions::raise_dispute(&keys.transaction_state(), &keys.player)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+            .await,
+        ErrorCode::DisputeUnresolved,
+    );
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    harness.set_time(state.dispute_deadline).await;
+
+    // the arbiter is too late to rule
+    assert_error(
+        harness
+            .send(
+                &[instructions::resolve_dispute(
+                    &keys.transaction_state(),
+                    &harness.arbiter.pubkey(),
+                    false,
+                )],
+                &[&harness.arbiter],
+            )
+            .await,
+        ErrorCode::DeadlinePassed,
+    );
+
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(&player_ata).await, 40 * TOKEN);
+    assert_eq!(harness.token_balance(&house_ata).await, 510 * TOKEN - 500_000);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::EscrowComplete);
+}
+
+#[tokio::test]
+async fn finalize_sweeps_a_donation_to_the_house() {
+    let (harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    // anyone can send tokens straight to the escrow account
+    harness
+        .send(
+            &[spl_token::instruction::transfer(
+                &spl_token::id(),
+                &player_ata,
+                &keys.escrow_account(),
+                &player.pubkey(),
+                &[],
+                TOKEN,
+            )
+            .unwrap()],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    harness.settle(&keys, &keys.player).await.unwrap();
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+
+    // the winner's payout is unchanged, the donation goes to the house
+    assert_eq!(harness.token_balance(&player_ata).await, 78 * TOKEN);
+    assert_eq!(harness.token_balance(&house_ata).await, 471 * TOKEN);
+    assert!(!harness.exists(&keys.escrow_account()).await);
+}
+
+#[tokio::test]
+async fn cancel_refunds_a_partial_deposit() {
+    let (harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness
+        .send(&[instructions::deposit_by_player(&keys, 10 * TOKEN)], &[&player])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(&player_ata).await, 40 * TOKEN);
+
+    harness
+        .send(
+            &[instructions::cancel_transaction(&keys, &keys.player)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&player_ata).await, 50 * TOKEN);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Cancelled);
+    assert!(!harness.exists(&keys.escrow_account()).await);
+}
+
+#[tokio::test]
+async fn cancel_sweeps_a_donation_to_the_house() {
+    let (harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness
+        .send(&[instructions::deposit_by_player(&keys, 10 * TOKEN)], &[&player])
+        .await
+        .unwrap();
+
+    // a single token unit sent straight to the escrow account
+    harness
+        .send(
+            &[spl_token::instruction::transfer(
+                &spl_token::id(),
+                &player_ata,
+                &keys.escrow_account(),
+                &player.pubkey(),
+                &[],
+                1,
+            )
+            .unwrap()],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    harness
+        .send(
+            &[instructions::cancel_transaction(&keys, &keys.player)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    // the player's stake comes back and the donation is swept to the house
+    assert_eq!(harness.token_balance(&player_ata).await, 50 * TOKEN - 1);
+    assert_eq!(harness.token_balance(&house_ata).await, 500 * TOKEN + 1);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Cancelled);
+    assert!(!harness.exists(&keys.escrow_account()).await);
+}
+
+#[tokio::test]
+async fn the_house_cancels_when_the_player_never_pays_in() {
+    let (harness, player, mint) = setup().await;
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness
+        .send(
+            &[instructions::deposit_by_house(&keys, 30 * TOKEN)],
+            &[&harness.house],
+        )
+        .await
+        .unwrap();
+
+    let stranger = harness.create_user().await;
+    assert_error(
+        harness
+            .send(
+                &[instructions::cancel_transaction(&keys, &stranger.pubkey())],
+                &[&stranger],
+            )
+            .await,
+        ErrorCode::UnauthorizedCanceller,
+    );
+
+    harness
+        .send(
+            &[instructions::cancel_transaction(&keys, &keys.beneficiary)],
+            &[&harness.house],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&house_ata).await, 500 * TOKEN);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Cancelled);
+    assert!(!harness.exists(&keys.escrow_account()).await);
+}
+
+#[tokio::test]
+async fn the_rake_is_fixed_once_the_escrow_is_funded() {
+    let (harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    assert_eq!(state.fee_bps, 250);
+
+    // raising the fee afterwards doesn't touch the escrows already in play
+    harness
+        .send(&[instructions::set_fee(&harness.admin(), 1_000)], &[])
+        .await
+        .unwrap();
+
+    harness.settle(&keys, &keys.player).await.unwrap();
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&player_ata).await, 80 * TOKEN - TOKEN);
+}
+
+#[tokio::test]
+async fn pullback_refunds_both_stakes_after_the_deadline() {
+    let (mut harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    assert_error(
+        harness.send(&[instructions::pullback(&keys)], &[]).await,
+        ErrorCode::DeadlineNotReached,
+    );
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    harness.set_time(state.deadline).await;
+
+    // past the deadline the referee can't settle any more
+    assert_error(
+        harness.settle(&keys, &keys.player).await,
+        ErrorCode::DeadlinePassed,
+    );
+
+    harness
+        .send(&[instructions::pullback(&keys)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&player_ata).await, 50 * TOKEN);
+    assert_eq!(harness.token_balance(&house_ata).await, 500 * TOKEN);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Expired);
+}
+
+#[tokio::test]
+async fn deposits_are_rejected_in_the_wrong_stage() {
+    let (harness, player, mint) = setup().await;
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness
+        .send(&[instructions::deposit_by_player(&keys, 10 * TOKEN)], &[&player])
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(&[instructions::deposit_by_player(&keys, 5 * TOKEN)], &[&player])
+            .await,
+        ErrorCode::AlreadyDeposited,
+    );
+
+    // the house has to pay in exactly the stake agreed at setup
+    assert_error(
+        harness
+            .send(
+                &[instructions::deposit_by_house(&keys, 20 * TOKEN)],
+                &[&harness.house],
+            )
+            .await,
+        ErrorCode::StakeMismatch,
+    );
+
+    // nothing to pay out before a result was submitted
+    assert_error(
+        harness
+            .send(&[instructions::finalize_settlement(&keys, 0)], &[])
+            .await,
+        ErrorCode::StageInvalid,
+    );
+
+    harness
+        .send(
+            &[instructions::deposit_by_house(&keys, 30 * TOKEN)],
+            &[&harness.house],
+        )
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(
+                &[instructions::deposit_by_house(&keys, 15 * TOKEN)],
+                &[&harness.house],
+            )
+            .await,
+        ErrorCode::StageInvalid,
+    );
+    assert_error(
+        harness
+            .send(
+                &[instructions::cancel_transaction(&keys, &keys.player)],
+                &[&player],
+            )
+            .await,
+        ErrorCode::StageInvalid,
+    );
+    assert_error(
+        harness
+            .send(
+                &[instructions::close_transaction(
+                    &keys.transaction_state(),
+                    &keys.player,
+                )],
+                &[],
+            )
+            .await,
+        ErrorCode::StageInvalid,
+    );
+}
+
+#[tokio::test]
+async fn a_deregistered_mint_takes_no_new_deposits() {
+    let (harness, player, mint) = setup().await;
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness
+        .send(&[instructions::deregister_mint(&harness.admin(), mint)], &[])
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(&[instructions::deposit_by_player(&keys, 10 * TOKEN)], &[&player])
+            .await,
+        ErrorCode::MintNotRegistered,
+    );
+    assert_error(
+        harness
+            .send(
+                &[instructions::deposit_by_house(&keys, 30 * TOKEN)],
+                &[&harness.house],
+            )
+            .await,
+        ErrorCode::MintNotRegistered,
+    );
+
+    // the unfunded escrow can still be called off
+    harness
+        .send(
+            &[instructions::cancel_transaction(&keys, &keys.player)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Cancelled);
+}
+
+#[tokio::test]
+async fn settlement_needs_the_referee() {
+    let (harness, player, mint) = setup().await;
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    // a perfectly good signature from someone else
+    assert_error(
+        harness.settle_signed_by(&keys, &keys.player, &player).await,
+        ErrorCode::UnauthorizedReferee,
+    );
+
+    // no signature at all
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    let expiry = harness.now().await + 60;
+    assert_error(
+        harness
+            .send(
+                &[instructions::transfer_to_winner(
+                    &keys,
+                    keys.player,
+                    state.result_nonce,
+                    expiry,
+                )],
+                &[],
+            )
+            .await,
+        ErrorCode::InvalidAttestation,
+    );
+
+    // the referee can only pick one of the two parties
+    assert_error(
+        harness.settle(&keys, &harness.admin()).await,
+        ErrorCode::InvalidWinner,
+    );
+
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::FundsDeposited);
+}
+
+#[tokio::test]
+async fn stakes_have_to_be_covered_and_in_range() {
+    let (harness, player, mint) = setup().await;
+
+    let session_id = harness.next_session_id(&player.pubkey()).await;
+    let keys = EscrowKeys {
+        player: player.pubkey(),
+        beneficiary: harness.house.pubkey(),
+        mint_of_token: mint,
+        session_id,
+    };
+    assert_error(
+        harness
+            .send(
+                &[instructions::transaction_setup(&keys, MAX_STAKE + 1, 10 * TOKEN)],
+                &[&player],
+            )
+            .await,
+        ErrorCode::StakeOutOfRange,
+    );
+
+    let keys = harness.open_escrow(&player, &mint, 60 * TOKEN, 10 * TOKEN).await;
+
+    // within the registered limits but more than the player holds
+    assert_eq!(
+        instruction_error(
+            harness
+                .send(&[instructions::deposit_by_player(&keys, 60 * TOKEN)], &[&player])
+                .await
+        ),
+        InstructionError::Custom(spl_token::error::TokenError::InsufficientFunds as u32)
+    );
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    assert!(!state.player_deposited);
+}
+
+#[tokio::test]
+async fn pullback_sweeps_a_donation_to_the_house() {
+    let (mut harness, player, mint) = setup().await;
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&harness.house.pubkey(), &mint);
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 30 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+
+    // anyone can send tokens straight to the escrow account
+    harness
+        .send(
+            &[spl_token::instruction::transfer(
+                &spl_token::id(),
+                &player_ata,
+                &keys.escrow_account(),
+                &player.pubkey(),
+                &[],
+                TOKEN,
+            )
+            .unwrap()],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    harness.set_time(state.deadline).await;
+    harness
+        .send(&[instructions::pullback(&keys)], &[])
+        .await
+        .unwrap();
+
+    // the stakes go back and the donation is swept to the house
+    assert_eq!(harness.token_balance(&player_ata).await, 49 * TOKEN);
+    assert_eq!(harness.token_balance(&house_ata).await, 501 * TOKEN);
+    assert!(!harness.exists(&keys.escrow_account()).await);
+    assert_eq!(stage(&harness, &keys.transaction_state()).await, Stage::Expired);
+}
+
+#[tokio::test]
+async fn a_migrated_baseline_escrow_is_refunded() {
+    let (mut harness, player, mint) = setup().await;
+    let house = harness.house.pubkey();
+    let player_ata = get_associated_token_address(&player.pubkey(), &mint);
+    let house_ata = get_associated_token_address(&house, &mint);
+
+    // funded before session ids with 10 tokens a side, plus a token someone sent on top
+    let (transaction_state, state_bump) =
+        pda::find_legacy_transaction_state(&player.pubkey(), &house, &mint);
+    let (escrow_account, escrow_bump) =
+        pda::find_legacy_escrow_account(&player.pubkey(), &house, &mint);
+    let legacy = TransactionStateV0 {
+        player: player.pubkey(),
+        beneficiary: house,
+        mint_of_token: mint,
+        escrow_account,
+        amount_of_tokens: 10 * TOKEN,
+        stage: Stage::FundsDeposited.to_code(),
+        state_bump,
+        escrow_bump,
+    };
+    harness.context.set_account(
+        &transaction_state,
+        &program_account::<TransactionState>(&legacy.try_to_vec().unwrap()).into(),
+    );
+    harness.context.set_account(
+        &escrow_account,
+        &token_account(&mint, &transaction_state, 21 * TOKEN).into(),
+    );
+
+    harness
+        .send(
+            &[instructions::migrate_account(&harness.admin(), &transaction_state)],
+            &[],
+        )
+        .await
+        .unwrap();
+    harness
+        .send(
+            &[instructions::legacy_pullback(&player.pubkey(), &house, &mint)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&player_ata).await, 60 * TOKEN);
+    assert_eq!(harness.token_balance(&house_ata).await, 511 * TOKEN);
+    assert!(!harness.exists(&escrow_account).await);
+    assert_eq!(stage(&harness, &transaction_state).await, Stage::Expired);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use common::*;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::state::{Leaderboard, PlayerProfile, TransactionState};
+use dungeon_nft_sdk::{instructions, pda, split_message};
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn winners_climb_the_season_board() {
+    let mut harness = Harness::new().await;
+    let admin = harness.admin();
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+    harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+    harness.fund(&harness.house.pubkey(), &mint, 50 * TOKEN).await;
+
+    let now = harness.now().await;
+    assert_error(
+        harness
+            .send(&[instructions::leaderboard_setup(&admin, 1, now, now)], &[])
+            .await,
+        ErrorCode::InvalidSeason,
+    );
+    harness
+        .send(
+            &[instructions::leaderboard_setup(&admin, 1, now, now + 3_600)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 10 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+    harness.settle(&keys, &keys.player).await.unwrap();
+    // the settlement puts the win straight on the board
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 1)], &[])
+        .await
+        .unwrap();
+
+    let board: Leaderboard = harness.account(&pda::find_leaderboard(1).0).await;
+    assert_eq!(board.entries.len(), 1);
+    assert_eq!(board.entries[0].player, keys.player);
+    assert_eq!(board.entries[0].score, 1);
+
+    harness.set_time(now + 3_600).await;
+    assert_error(
+        harness
+            .send(&[instructions::update_leaderboard(1, &keys.player)], &[])
+            .await,
+        ErrorCode::SeasonNotActive,
+    );
+}
+
+#[tokio::test]
+async fn seasons_rank_their_own_wins_and_a_raked_draw_keeps_the_streak() {
+    let mut harness = Harness::new().await;
+    let admin = harness.admin();
+    let mint = harness.wager_mint().await;
+    let player = harness.create_user().await;
+    harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+    harness.fund(&harness.house.pubkey(), &mint, 50 * TOKEN).await;
+
+    let now = harness.now().await;
+    harness
+        .send(
+            &[instructions::leaderboard_setup(&admin, 1, now, now + 3_600)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let won = harness.open_escrow(&player, &mint, 10 * TOKEN, 10 * TOKEN).await;
+    harness.fund_escrow(&won, &player).await;
+    harness.settle(&won, &won.player).await.unwrap();
+    harness
+        .send(&[instructions::finalize_settlement(&won, 1)], &[])
+        .await
+        .unwrap();
+
+    // an even split pays back less than the stake once raked, it's still a draw
+    let drawn = harness.open_escrow(&player, &mint, 10 * TOKEN, 10 * TOKEN).await;
+    harness.fund_escrow(&drawn, &player).await;
+    let state: TransactionState = harness.account(&drawn.transaction_state()).await;
+    let expiry = harness.now().await + 60;
+    let message = split_message(&drawn.escrow_account(), 1, 1, state.result_nonce, expiry);
+    harness
+        .send(
+            &[
+                ed25519_attestation(&harness.referee, &message),
+                instructions::split_settlement(&drawn, 1, 1, state.result_nonce, expiry),
+                instructions::finalize_settlement(&drawn, 1),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let profile: PlayerProfile = harness
+        .account(&pda::find_player_profile(&player.pubkey()).0)
+        .await;
+    assert_eq!((profile.games_played, profile.wins, profile.losses), (2, 1, 0));
+    assert_eq!(profile.current_streak, 1);
+    assert_eq!((profile.season, profile.season_wins), (1, 1));
+    let board: Leaderboard = harness.account(&pda::find_leaderboard(1).0).await;
+    assert_eq!(board.entries[0].score, 1);
+
+    // the next season can't start before this one is over
+    assert_error(
+        harness
+            .send(
+                &[instructions::leaderboard_setup(&admin, 2, now + 60, now + 7_200)],
+                &[],
+            )
+            .await,
+        ErrorCode::InvalidSeason,
+    );
+
+    harness.set_time(now + 3_600).await;
+    harness
+        .send(
+            &[instructions::leaderboard_setup(&admin, 2, now + 3_600, now + 7_200)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // last season's win doesn't carry over
+    harness
+        .send(&[instructions::update_leaderboard(2, &player.pubkey())], &[])
+        .await
+        .unwrap();
+    let board: Leaderboard = harness.account(&pda::find_leaderboard(2).0).await;
+    assert!(board.entries.is_empty());
+
+    let keys = harness.open_escrow(&player, &mint, 10 * TOKEN, 10 * TOKEN).await;
+    harness.fund_escrow(&keys, &player).await;
+    harness.settle(&keys, &keys.player).await.unwrap();
+    harness
+        .send(&[instructions::finalize_settlement(&keys, 2)], &[])
+        .await
+        .unwrap();
+
+    let board: Leaderboard = harness.account(&pda::find_leaderboard(2).0).await;
+    assert_eq!(board.entries.len(), 1);
+    assert_eq!(board.entries[0].score, 1);
+
+    let profile: PlayerProfile = harness
+        .account(&pda::find_player_profile(&player.pubkey()).0)
+        .await;
+    assert_eq!(profile.wins, 2);
+    assert_eq!(profile.current_streak, 2);
+}
@@ -0,0 +1,302 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::state::{LobbyState, PlayerProfile};
+use dungeon_nft_sdk::{instructions, lobby_message, pda, LobbyKeys, LobbyStage};
+use solana_sdk::signature::{Keypair, Signer};
+
+// an open lobby for up to four players at 10 tokens a seat, paying 70% / 30%,
+// and three players holding 50 tokens each
+async fn setup() -> (Harness, Keypair, LobbyKeys, Vec<Keypair>) {
+    let harness = Harness::new().await;
+    let mint = harness.wager_mint().await;
+    let creator = harness.create_user().await;
+
+    let keys = LobbyKeys {
+        creator: creator.pubkey(),
+        mint_of_token: mint,
+        lobby_id: 7,
+    };
+    harness
+        .send(
+            &[instructions::lobby_setup(&keys, 10 * TOKEN, 4, vec![7_000, 3_000])],
+            &[&creator],
+        )
+        .await
+        .unwrap();
+
+    let mut players = Vec::new();
+    for _ in 0..3 {
+        let player = harness.create_user().await;
+        harness.fund(&player.pubkey(), &mint, 50 * TOKEN).await;
+        players.push(player);
+    }
+
+    (harness, creator, keys, players)
+}
+
+async fn join_all(harness: &Harness, keys: &LobbyKeys, players: &[Keypair]) {
+    for player in players {
+        harness
+            .send(&[instructions::join_lobby(keys, &player.pubkey())], &[player])
+            .await
+            .unwrap();
+    }
+}
+
+async fn balance(harness: &Harness, keys: &LobbyKeys, player: &Keypair) -> u64 {
+    let token_account = get_associated_token_address(&player.pubkey(), &keys.mint_of_token);
+    harness.token_balance(&token_account).await
+}
+
+#[tokio::test]
+async fn settled_lobby_pays_the_places() {
+    let (harness, creator, keys, players) = setup().await;
+    join_all(&harness, &keys, &players).await;
+    assert_eq!(harness.token_balance(&keys.lobby_vault()).await, 30 * TOKEN);
+
+    harness
+        .send(&[instructions::lock_lobby(&keys)], &[&creator])
+        .await
+        .unwrap();
+
+    let joined: Vec<_> = players.iter().map(|player| player.pubkey()).collect();
+    let winners = vec![players[2].pubkey(), players[0].pubkey()];
+    let expiry = harness.now().await + 60;
+    let message = lobby_message(&keys.lobby_state(), &winners, expiry);
+    harness
+        .send(
+            &[
+                ed25519_attestation(&harness.referee, &message),
+                instructions::settle_lobby(&keys, winners, &joined, expiry, 0),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // 29.25 tokens after the rake
+    assert_eq!(balance(&harness, &keys, &players[2]).await, 40 * TOKEN + 20_475_000);
+    assert_eq!(balance(&harness, &keys, &players[0]).await, 40 * TOKEN + 8_775_000);
+    assert_eq!(balance(&harness, &keys, &players[1]).await, 40 * TOKEN);
+    assert_eq!(
+        harness
+            .token_balance(&pda::find_treasury_vault(&keys.mint_of_token).0)
+            .await,
+        750_000
+    );
+    assert!(!harness.exists(&keys.lobby_vault()).await);
+
+    let lobby: LobbyState = harness.account(&keys.lobby_state()).await;
+    assert_eq!(LobbyStage::from(lobby.stage).unwrap(), LobbyStage::Settled);
+
+    // second place gets 9 of the 30 tokens back for a 10 token seat, that's a loss
+    let mut records = Vec::new();
+    for player in &players {
+        let profile: PlayerProfile = harness
+            .account(&pda::find_player_profile(&player.pubkey()).0)
+            .await;
+        assert_eq!(profile.games_played, 1);
+        records.push((profile.wins, profile.losses));
+    }
+    assert_eq!(records, vec![(0, 1), (0, 1), (1, 0)]);
+}
+
+#[tokio::test]
+async fn cancelled_lobby_refunds_every_seat() {
+    let (harness, creator, keys, players) = setup().await;
+    join_all(&harness, &keys, &players[..2]).await;
+
+    let joined: Vec<_> = players[..2].iter().map(|player| player.pubkey()).collect();
+    harness
+        .send(&[instructions::cancel_lobby(&keys, &joined)], &[&creator])
+        .await
+        .unwrap();
+
+    for player in &players[..2] {
+        assert_eq!(balance(&harness, &keys, player).await, 50 * TOKEN);
+    }
+
+    let lobby: LobbyState = harness.account(&keys.lobby_state()).await;
+    assert_eq!(LobbyStage::from(lobby.stage).unwrap(), LobbyStage::Cancelled);
+}
+
+#[tokio::test]
+async fn cancel_sweeps_a_donation_to_the_treasury() {
+    let (harness, creator, keys, players) = setup().await;
+    join_all(&harness, &keys, &players[..2]).await;
+
+    // tokens sent straight to the vault don't belong to any seat
+    let donor = get_associated_token_address(&players[2].pubkey(), &keys.mint_of_token);
+    harness
+        .send(
+            &[spl_token::instruction::transfer(
+                &spl_token::id(),
+                &donor,
+                &keys.lobby_vault(),
+                &players[2].pubkey(),
+                &[],
+                TOKEN,
+            )
+            .unwrap()],
+            &[&players[2]],
+        )
+        .await
+        .unwrap();
+
+    let joined: Vec<_> = players[..2].iter().map(|player| player.pubkey()).collect();
+    harness
+        .send(&[instructions::cancel_lobby(&keys, &joined)], &[&creator])
+        .await
+        .unwrap();
+
+    for player in &players[..2] {
+        assert_eq!(balance(&harness, &keys, player).await, 50 * TOKEN);
+    }
+    assert_eq!(
+        harness
+            .token_balance(&pda::find_treasury_vault(&keys.mint_of_token).0)
+            .await,
+        TOKEN
+    );
+    assert!(!harness.exists(&keys.lobby_vault()).await);
+}
+
+#[tokio::test]
+async fn anyone_refunds_a_lobby_left_past_its_deadline() {
+    let (mut harness, creator, keys, players) = setup().await;
+    join_all(&harness, &keys, &players).await;
+    harness
+        .send(&[instructions::lock_lobby(&keys)], &[&creator])
+        .await
+        .unwrap();
+
+    let joined: Vec<_> = players.iter().map(|player| player.pubkey()).collect();
+
+    // a locked lobby is in play, the creator can't pull it any more
+    assert_error(
+        harness
+            .send(&[instructions::cancel_lobby(&keys, &joined)], &[&creator])
+            .await,
+        ErrorCode::StageInvalid,
+    );
+    assert_error(
+        harness
+            .send(&[instructions::refund_lobby(&keys, &joined)], &[])
+            .await,
+        ErrorCode::DeadlineNotReached,
+    );
+
+    // the referee never settles
+    let lobby: LobbyState = harness.account(&keys.lobby_state()).await;
+    harness.set_time(lobby.deadline).await;
+
+    let winners = vec![players[0].pubkey(), players[1].pubkey()];
+    let expiry = harness.now().await + 60;
+    let message = lobby_message(&keys.lobby_state(), &winners, expiry);
+    assert_error(
+        harness
+            .send(
+                &[
+                    ed25519_attestation(&harness.referee, &message),
+                    instructions::settle_lobby(&keys, winners, &joined, expiry, 0),
+                ],
+                &[],
+            )
+            .await,
+        ErrorCode::DeadlinePassed,
+    );
+
+    harness
+        .send(&[instructions::refund_lobby(&keys, &joined)], &[])
+        .await
+        .unwrap();
+
+    for player in &players {
+        assert_eq!(balance(&harness, &keys, player).await, 50 * TOKEN);
+    }
+    assert!(!harness.exists(&keys.lobby_vault()).await);
+
+    let lobby: LobbyState = harness.account(&keys.lobby_state()).await;
+    assert_eq!(LobbyStage::from(lobby.stage).unwrap(), LobbyStage::Expired);
+}
+
+#[tokio::test]
+async fn lobby_rules_are_enforced() {
+    let (mut harness, creator, keys, players) = setup().await;
+    join_all(&harness, &keys, &players[..1]).await;
+
+    // a later slot so the second join isn't dropped as a duplicate of the first
+    harness.advance(1).await;
+    assert_error(
+        harness
+            .send(
+                &[instructions::join_lobby(&keys, &players[0].pubkey())],
+                &[&players[0]],
+            )
+            .await,
+        ErrorCode::AlreadyJoined,
+    );
+
+    assert_error(
+        harness
+            .send(&[instructions::lock_lobby(&keys)], &[&players[0]])
+            .await,
+        ErrorCode::UnauthorizedCreator,
+    );
+
+    // one player can't fill a two place payout table
+    assert_error(
+        harness
+            .send(&[instructions::lock_lobby(&keys)], &[&creator])
+            .await,
+        ErrorCode::InvalidLobbySize,
+    );
+
+    join_all(&harness, &keys, &players[1..]).await;
+    harness.advance(1).await;
+    harness
+        .send(&[instructions::lock_lobby(&keys)], &[&creator])
+        .await
+        .unwrap();
+
+    // every place needs a different winner
+    let joined: Vec<_> = players.iter().map(|player| player.pubkey()).collect();
+    let winners = vec![players[1].pubkey(), players[1].pubkey()];
+    let expiry = harness.now().await + 60;
+    let message = lobby_message(&keys.lobby_state(), &winners, expiry);
+    assert_error(
+        harness
+            .send(
+                &[
+                    ed25519_attestation(&harness.referee, &message),
+                    instructions::settle_lobby(&keys, winners, &joined, expiry, 0),
+                ],
+                &[],
+            )
+            .await,
+        ErrorCode::InvalidLobbyWinners,
+    );
+
+    // a result signed by one of the players
+    let winners = vec![players[1].pubkey(), players[0].pubkey()];
+    let message = lobby_message(&keys.lobby_state(), &winners, expiry);
+    assert_error(
+        harness
+            .send(
+                &[
+                    ed25519_attestation(&players[1], &message),
+                    instructions::settle_lobby(&keys, winners, &joined, expiry, 0),
+                ],
+                &[],
+            )
+            .await,
+        ErrorCode::UnauthorizedReferee,
+    );
+
+    let lobby: LobbyState = harness.account(&keys.lobby_state()).await;
+    assert_eq!(LobbyStage::from(lobby.stage).unwrap(), LobbyStage::Locked);
+    assert_eq!(lobby.players.len(), 3);
+}
@@ -0,0 +1,262 @@
+use dungeon_nft::math::{self, MathError};
+use proptest::prelude::*;
+
+// a pool side between one unit and a trillion tokens at six decimals
+fn reserve() -> impl Strategy<Value = u64> {
+    1u64..=1_000_000_000_000_000_000
+}
+
+// up to 10%, over the denominators the markets are set up with
+fn fee_rate() -> impl Strategy<Value = (u64, u64)> {
+    prop_oneof![Just(100u64), Just(1_000), Just(10_000)]
+        .prop_flat_map(|denominator| (0..=denominator / 10, Just(denominator)))
+}
+
+fn product(reserve_in: u64, reserve_out: u64) -> u128 {
+    reserve_in as u128 * reserve_out as u128
+}
+
+proptest! {
+    #[test]
+    fn swap_never_shrinks_k(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=1_000_000_000_000_000_000,
+        (fee_numerator, fee_denominator) in fee_rate(),
+    ) {
+        let amount_out =
+            math::quote_out(amount_in, reserve_in, reserve_out, fee_numerator, fee_denominator)
+                .unwrap();
+        prop_assert!(amount_out < reserve_out);
+        prop_assert!(
+            product(reserve_in + amount_in, reserve_out - amount_out)
+                >= product(reserve_in, reserve_out)
+        );
+    }
+
+    #[test]
+    fn k_never_shrinks_over_a_run_of_swaps(
+        reserve_a in reserve(),
+        reserve_b in reserve(),
+        (fee_numerator, fee_denominator) in fee_rate(),
+        swaps in prop::collection::vec((1u64..=1_000_000_000_000, any::<bool>()), 1..32),
+    ) {
+        let (mut reserve_a, mut reserve_b) = (reserve_a, reserve_b);
+        for (amount_in, a_to_b) in swaps {
+            let k = product(reserve_a, reserve_b);
+            let (reserve_in, reserve_out) = if a_to_b {
+                (&mut reserve_a, &mut reserve_b)
+            } else {
+                (&mut reserve_b, &mut reserve_a)
+            };
+            let amount_out = math::quote_out(
+                amount_in,
+                *reserve_in,
+                *reserve_out,
+                fee_numerator,
+                fee_denominator,
+            )
+            .unwrap();
+            *reserve_in += amount_in;
+            *reserve_out -= amount_out;
+            prop_assert!(product(reserve_a, reserve_b) >= k);
+        }
+    }
+
+    #[test]
+    fn quote_in_buys_at_least_the_quoted_amount(
+        reserve_in in 1u64..=1_000_000_000_000_000,
+        reserve_out in 2u64..=1_000_000_000_000_000,
+        out_fraction in 1u64..1_000,
+        (fee_numerator, fee_denominator) in fee_rate(),
+    ) {
+        let amount_out = (reserve_out as u128 * out_fraction as u128 / 1_000).max(1) as u64;
+        prop_assume!(amount_out < reserve_out);
+
+        let amount_in =
+            math::quote_in(amount_out, reserve_in, reserve_out, fee_numerator, fee_denominator)
+                .unwrap();
+        let received =
+            math::quote_out(amount_in, reserve_in, reserve_out, fee_numerator, fee_denominator)
+                .unwrap();
+        prop_assert!(received >= amount_out);
+    }
+
+    #[test]
+    fn fee_is_part_of_the_amount(
+        amount_in in any::<u64>(),
+        (fee_numerator, fee_denominator) in fee_rate(),
+    ) {
+        let fee = math::fee(amount_in, fee_numerator, fee_denominator).unwrap();
+        prop_assert!(fee <= amount_in);
+        prop_assert!(fee as u128 * fee_denominator as u128
+            <= amount_in as u128 * fee_numerator as u128);
+    }
+
+    #[test]
+    fn ratio_deposit_never_dilutes_the_pool(
+        token_reserve in reserve(),
+        sol_reserve in reserve(),
+        token_amount in 1u64..=1_000_000_000_000_000_000,
+    ) {
+        // a deposit worth more than a u64 of sol is refused rather than wrapped
+        let sol_amount = match math::liquidity_ratio(token_amount, token_reserve, sol_reserve) {
+            Ok(sol_amount) => sol_amount,
+            Err(error) => {
+                prop_assert_eq!(error, MathError::Overflow);
+                prop_assert!(
+                    token_amount as u128 * sol_reserve as u128 > (u64::MAX as u128) * token_reserve as u128
+                );
+                return Ok(());
+            }
+        };
+        // the sol per token in the pool doesn't go down
+        prop_assert!(
+            (sol_reserve as u128 + sol_amount as u128) * token_reserve as u128
+                >= sol_reserve as u128 * (token_reserve as u128 + token_amount as u128)
+        );
+    }
+
+    #[test]
+    fn initial_shares_are_the_geometric_mean(
+        token_amount in any::<u64>(),
+        sol_amount in any::<u64>(),
+    ) {
+        let product = product(token_amount, sol_amount);
+        match math::initial_shares(token_amount, sol_amount) {
+            Ok(shares) => {
+                let root = (shares + math::MINIMUM_LIQUIDITY) as u128;
+                prop_assert!(root * root <= product && (root + 1) * (root + 1) > product);
+            }
+            Err(error) => {
+                prop_assert_eq!(error, MathError::InsufficientLiquidity);
+                prop_assert!(product < (math::MINIMUM_LIQUIDITY as u128 + 1).pow(2));
+            }
+        }
+    }
+
+    #[test]
+    fn later_shares_never_dilute_the_supply(
+        token_reserve in reserve(),
+        sol_reserve in reserve(),
+        supply in 1u64..=1_000_000_000_000_000_000,
+        token_amount in 1u64..=1_000_000_000_000_000,
+        sol_amount in 1u64..=1_000_000_000_000_000,
+    ) {
+        // too small a deposit for a whole share is refused
+        let shares = match math::proportional_shares(
+            token_amount,
+            sol_amount,
+            token_reserve,
+            sol_reserve,
+            supply,
+        ) {
+            Ok(shares) => shares,
+            Err(error) => {
+                prop_assert_eq!(error, MathError::ZeroAmount);
+                return Ok(());
+            }
+        };
+        // each share is backed by at least as much of both sides after the deposit
+        prop_assert!(
+            product(token_reserve + token_amount, supply)
+                >= product(token_reserve, supply + shares)
+        );
+        prop_assert!(
+            product(sol_reserve + sol_amount, supply) >= product(sol_reserve, supply + shares)
+        );
+    }
+
+    #[test]
+    fn balanced_deposits_fit_the_maxima_and_the_ratio(
+        token_reserve in reserve(),
+        sol_reserve in reserve(),
+        max_token_amount in 1u64..=1_000_000_000_000_000,
+        max_sol_amount in 1u64..=1_000_000_000_000_000,
+    ) {
+        let (token_amount, sol_amount) = match math::balanced_deposit(
+            max_token_amount,
+            max_sol_amount,
+            token_reserve,
+            sol_reserve,
+        ) {
+            Ok(amounts) => amounts,
+            Err(error) => {
+                // the sol on offer doesn't buy a single token unit
+                prop_assert_eq!(error, MathError::ZeroAmount);
+                prop_assert!(product(max_sol_amount, token_reserve) < sol_reserve as u128);
+                return Ok(());
+            }
+        };
+
+        prop_assert!(token_amount <= max_token_amount && sol_amount <= max_sol_amount);
+        // at least the pool's sol per token
+        prop_assert!(product(sol_amount, token_reserve) >= product(token_amount, sol_reserve));
+        // and the largest such pair, one more token would need more sol than offered
+        prop_assert!(
+            token_amount == max_token_amount
+                || product(token_amount + 1, sol_reserve)
+                    > product(max_sol_amount, token_reserve)
+        );
+    }
+
+    #[test]
+    fn withdrawals_never_pay_more_than_the_share(
+        token_reserve in any::<u64>(),
+        sol_reserve in any::<u64>(),
+        supply in 2u64..=u64::MAX,
+        burnt in any::<u64>(),
+    ) {
+        let lp_amount = burnt % (supply - 1) + 1;
+        let (token_amount, sol_amount) =
+            math::withdrawal_amounts(lp_amount, token_reserve, sol_reserve, supply).unwrap();
+        prop_assert!(product(token_amount, supply) <= product(token_reserve, lp_amount));
+        prop_assert!(product(sol_amount, supply) <= product(sol_reserve, lp_amount));
+    }
+}
+
+#[test]
+fn bad_inputs_are_errors() {
+    assert_eq!(
+        math::quote_out(0, 10, 10, 3, 1_000),
+        Err(MathError::ZeroAmount)
+    );
+    assert_eq!(
+        math::quote_out(5, 0, 10, 3, 1_000),
+        Err(MathError::EmptyPool)
+    );
+    assert_eq!(math::quote_out(5, 10, 10, 3, 0), Err(MathError::InvalidFee));
+    assert_eq!(
+        math::quote_out(5, 10, 10, 1_000, 1_000),
+        Err(MathError::InvalidFee)
+    );
+
+    assert_eq!(
+        math::quote_in(10, 10, 10, 3, 1_000),
+        Err(MathError::InsufficientLiquidity)
+    );
+    assert_eq!(
+        math::quote_in(0, 10, 10, 3, 1_000),
+        Err(MathError::ZeroAmount)
+    );
+    assert_eq!(
+        math::quote_in(u64::MAX - 1, u64::MAX, u64::MAX, 3, 1_000),
+        Err(MathError::Overflow)
+    );
+
+    assert_eq!(math::liquidity_ratio(5, 0, 10), Err(MathError::EmptyPool));
+    assert_eq!(
+        math::liquidity_ratio(u64::MAX, 1, u64::MAX),
+        Err(MathError::Overflow)
+    );
+}
+
+#[test]
+fn quotes_match_the_market_tests() {
+    // the 100 token swap into the 1000 / 1000 pool in tests/amm.rs
+    assert_eq!(
+        math::quote_out(100_000_000, 1_000_000_000, 1_000_000_000, 3, 1_000),
+        Ok(90_661_089)
+    );
+    assert_eq!(math::fee(100_000_000, 3, 1_000), Ok(300_000));
+}
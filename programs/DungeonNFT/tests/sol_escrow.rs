@@ -0,0 +1,194 @@
+mod common;
+
+use common::*;
+use dungeon_nft::error::ErrorCode;
+use dungeon_nft::state::{TransactionState, TreasuryState};
+use dungeon_nft_sdk::{instructions, pda, split_message, Stage};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::Signer;
+
+async fn stage(harness: &Harness, keys: &dungeon_nft_sdk::SolEscrowKeys) -> Stage {
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    Stage::from(state.stage).unwrap()
+}
+
+#[tokio::test]
+async fn winner_takes_the_lamports_minus_the_rake() {
+    let harness = Harness::new().await;
+    let player = harness.create_user().await;
+    let house = harness.house.pubkey();
+
+    let keys = harness
+        .open_sol_escrow(&player, LAMPORTS_PER_SOL, 3 * LAMPORTS_PER_SOL)
+        .await;
+    harness.fund_sol_escrow(&keys, &player).await;
+    assert_eq!(stage(&harness, &keys).await, Stage::FundsDeposited);
+
+    let treasury_before = harness.lamports(&pda::find_sol_treasury_state().0).await;
+    let house_before = harness.lamports(&house).await;
+
+    harness.settle_sol(&keys, &house).await.unwrap();
+    harness
+        .send(&[instructions::sol_finalize_settlement(&keys, 0)], &[])
+        .await
+        .unwrap();
+
+    // 2.5% of the 4 SOL pot
+    let fee = LAMPORTS_PER_SOL / 10;
+    assert_eq!(
+        harness.lamports(&house).await,
+        house_before + 4 * LAMPORTS_PER_SOL - fee
+    );
+    assert_eq!(
+        harness.lamports(&pda::find_sol_treasury_state().0).await,
+        treasury_before + fee
+    );
+    assert_eq!(stage(&harness, &keys).await, Stage::EscrowComplete);
+
+    let treasury: TreasuryState = harness.account(&pda::find_sol_treasury_state().0).await;
+    assert_eq!(treasury.total_fees_collected, fee);
+}
+
+#[tokio::test]
+async fn split_settlement_pays_both_sides() {
+    let harness = Harness::new().await;
+    let player = harness.create_user().await;
+
+    let keys = harness
+        .open_sol_escrow(&player, 2 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL)
+        .await;
+    harness.fund_sol_escrow(&keys, &player).await;
+
+    let player_before = harness.lamports(&keys.player).await;
+    let house_before = harness.lamports(&keys.beneficiary).await;
+
+    let state: TransactionState = harness.account(&keys.transaction_state()).await;
+    let expiry = harness.now().await + 60;
+    let message = split_message(&keys.transaction_state(), 1, 1, state.result_nonce, expiry);
+    harness
+        .send(
+            &[
+                ed25519_attestation(&harness.referee, &message),
+                instructions::sol_split_settlement(&keys, 1, 1, state.result_nonce, expiry),
+                instructions::sol_finalize_settlement(&keys, 0),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // 3.9 SOL after the rake, halved
+    let share = 39 * LAMPORTS_PER_SOL / 20;
+    assert_eq!(harness.lamports(&keys.player).await, player_before + share);
+    assert_eq!(harness.lamports(&keys.beneficiary).await, house_before + share);
+}
+
+#[tokio::test]
+async fn cancel_and_pullback_refund_the_stakes() {
+    let mut harness = Harness::new().await;
+    let player = harness.create_user().await;
+
+    let cancelled = harness
+        .open_sol_escrow(&player, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL)
+        .await;
+    harness
+        .send(
+            &[instructions::sol_deposit_by_player(&cancelled, LAMPORTS_PER_SOL)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+    let player_before = harness.lamports(&player.pubkey()).await;
+
+    harness
+        .send(
+            &[instructions::sol_cancel_transaction(&cancelled, &cancelled.player)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        harness.lamports(&player.pubkey()).await,
+        player_before + LAMPORTS_PER_SOL
+    );
+    assert_eq!(stage(&harness, &cancelled).await, Stage::Cancelled);
+
+    let expired = harness
+        .open_sol_escrow(&player, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL)
+        .await;
+    assert_eq!(expired.session_id, cancelled.session_id + 1);
+    harness.fund_sol_escrow(&expired, &player).await;
+
+    assert_error(
+        harness
+            .send(&[instructions::sol_pullback(&expired)], &[])
+            .await,
+        ErrorCode::DeadlineNotReached,
+    );
+
+    let state: TransactionState = harness.account(&expired.transaction_state()).await;
+    harness.set_time(state.deadline).await;
+
+    let player_before = harness.lamports(&expired.player).await;
+    let house_before = harness.lamports(&expired.beneficiary).await;
+    harness
+        .send(&[instructions::sol_pullback(&expired)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        harness.lamports(&expired.player).await,
+        player_before + LAMPORTS_PER_SOL
+    );
+    assert_eq!(
+        harness.lamports(&expired.beneficiary).await,
+        house_before + LAMPORTS_PER_SOL
+    );
+    assert_eq!(stage(&harness, &expired).await, Stage::Expired);
+}
+
+#[tokio::test]
+async fn rejects_double_deposits_and_foreign_houses() {
+    let harness = Harness::new().await;
+    let player = harness.create_user().await;
+
+    let keys = harness
+        .open_sol_escrow(&player, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL)
+        .await;
+    harness
+        .send(
+            &[instructions::sol_deposit_by_player(&keys, LAMPORTS_PER_SOL)],
+            &[&player],
+        )
+        .await
+        .unwrap();
+
+    assert_error(
+        harness
+            .send(
+                &[instructions::sol_deposit_by_player(&keys, 2 * LAMPORTS_PER_SOL)],
+                &[&player],
+            )
+            .await,
+        ErrorCode::AlreadyDeposited,
+    );
+
+    // only the configured house can take the other side
+    let impostor = harness.create_user().await;
+    let mut foreign = keys;
+    foreign.beneficiary = impostor.pubkey();
+    foreign.session_id += 1;
+    assert_error(
+        harness
+            .send(
+                &[instructions::sol_transaction_setup(
+                    &foreign,
+                    LAMPORTS_PER_SOL,
+                    LAMPORTS_PER_SOL,
+                )],
+                &[&player],
+            )
+            .await,
+        ErrorCode::UnauthorizedHouse,
+    );
+}
@@ -0,0 +1,50 @@
+//! Decoders for the raw account data returned by an RPC node.
+
+use std::fmt;
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+
+pub use dungeon_nft::state::{
+    Leaderboard, LobbyState, MarketState, MintRegistry, PlayerProfile, ProgramConfig,
+    SessionCounter, TransactionState, TreasuryState,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    // shorter than a discriminator, or the discriminator of another type
+    WrongAccountType,
+    // the discriminator matched but the fields didn't, e.g. a legacy layout
+    InvalidData,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::WrongAccountType => write!(f, "not an account of the requested type"),
+            DecodeError::InvalidData => write!(f, "account data doesn't match the current layout"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub type DecodeResult<T> = core::result::Result<T, DecodeError>;
+
+// checks the discriminator before reading the fields, so any of the state types above can be
+// asked for
+pub fn decode<T: AccountDeserialize + Discriminator>(data: &[u8]) -> DecodeResult<T> {
+    if !is_account::<T>(data) {
+        return Err(DecodeError::WrongAccountType);
+    }
+    T::try_deserialize(&mut &data[..]).map_err(|_| DecodeError::InvalidData)
+}
+
+// what every account of the type starts with, for a memcmp filter on a program account scan
+pub fn discriminator<T: Discriminator>() -> [u8; 8] {
+    T::discriminator()
+}
+
+// lets a scan over every program account pick out one type without decoding the rest
+pub fn is_account<T: Discriminator>(data: &[u8]) -> bool {
+    data.len() >= 8 && data[..8] == T::discriminator()
+}
@@ -0,0 +1,238 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::token;
+
+use dungeon_nft::{accounts, instruction};
+
+use super::build;
+use crate::pda;
+
+pub fn config_setup(
+    admin: &Pubkey,
+    house: Pubkey,
+    referee: Pubkey,
+    arbiter: Pubkey,
+    settlement_timeout: i64,
+    dispute_window: i64,
+    fee_bps: u16,
+) -> Instruction {
+    build(
+        accounts::ConfigSetup {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+            program_data: pda::find_program_data().0,
+            system_program: system_program::ID,
+        },
+        instruction::ConfigSetupInstruction {
+            house,
+            referee,
+            arbiter,
+            settlement_timeout,
+            dispute_window,
+            fee_bps,
+        },
+    )
+}
+
+pub fn set_house(admin: &Pubkey, house: Pubkey) -> Instruction {
+    build(
+        accounts::SetHouse {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::SetHouseInstruction { house },
+    )
+}
+
+pub fn set_paused(admin: &Pubkey, paused: bool) -> Instruction {
+    build(
+        accounts::SetPaused {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::SetPausedInstruction { paused },
+    )
+}
+
+pub fn propose_admin(admin: &Pubkey, new_admin: Pubkey) -> Instruction {
+    build(
+        accounts::ProposeAdmin {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::ProposeAdminInstruction { new_admin },
+    )
+}
+
+pub fn accept_admin(new_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAdmin {
+            program_config: pda::find_program_config().0,
+            new_admin: *new_admin,
+        },
+        instruction::AcceptAdminInstruction {},
+    )
+}
+
+// works on any TransactionState or MarketState still on the pre-version layout
+pub fn migrate_account(admin: &Pubkey, account: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            account: *account,
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccountInstruction {},
+    )
+}
+
+pub fn set_referee(admin: &Pubkey, referee: Pubkey) -> Instruction {
+    build(
+        accounts::SetReferee {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::SetRefereeInstruction { referee },
+    )
+}
+
+pub fn set_settlement_timeout(admin: &Pubkey, settlement_timeout: i64) -> Instruction {
+    build(
+        accounts::SetSettlementTimeout {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::SetSettlementTimeoutInstruction { settlement_timeout },
+    )
+}
+
+pub fn set_dispute_policy(admin: &Pubkey, arbiter: Pubkey, dispute_window: i64) -> Instruction {
+    build(
+        accounts::SetDisputePolicy {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::SetDisputePolicyInstruction {
+            arbiter,
+            dispute_window,
+        },
+    )
+}
+
+pub fn set_fee(admin: &Pubkey, fee_bps: u16) -> Instruction {
+    build(
+        accounts::SetFee {
+            program_config: pda::find_program_config().0,
+            admin: *admin,
+        },
+        instruction::SetFeeInstruction { fee_bps },
+    )
+}
+
+pub fn mint_registry_setup(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::MintRegistrySetup {
+            program_config: pda::find_program_config().0,
+            mint_registry: pda::find_mint_registry().0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::MintRegistrySetupInstruction {},
+    )
+}
+
+pub fn register_mint(
+    admin: &Pubkey,
+    mint_of_token: &Pubkey,
+    decimals: u8,
+    min_stake: u64,
+    max_stake: u64,
+) -> Instruction {
+    build(
+        accounts::RegisterMint {
+            program_config: pda::find_program_config().0,
+            mint_registry: pda::find_mint_registry().0,
+            mint_of_token: *mint_of_token,
+            admin: *admin,
+        },
+        instruction::RegisterMintInstruction {
+            decimals,
+            min_stake,
+            max_stake,
+        },
+    )
+}
+
+pub fn deregister_mint(admin: &Pubkey, mint: Pubkey) -> Instruction {
+    build(
+        accounts::DeregisterMint {
+            program_config: pda::find_program_config().0,
+            mint_registry: pda::find_mint_registry().0,
+            admin: *admin,
+        },
+        instruction::DeregisterMintInstruction { mint },
+    )
+}
+
+pub fn treasury_setup(admin: &Pubkey, mint_of_token: &Pubkey) -> Instruction {
+    build(
+        accounts::TreasurySetup {
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_treasury_state(mint_of_token).0,
+            treasury_vault: pda::find_treasury_vault(mint_of_token).0,
+            mint_of_token: *mint_of_token,
+            admin: *admin,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::TreasurySetupInstruction {},
+    )
+}
+
+pub fn withdraw_treasury(
+    admin: &Pubkey,
+    mint_of_token: &Pubkey,
+    destination_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawTreasury {
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_treasury_state(mint_of_token).0,
+            treasury_vault: pda::find_treasury_vault(mint_of_token).0,
+            mint_of_token: *mint_of_token,
+            destination_token_account: *destination_token_account,
+            admin: *admin,
+            token_program: token::ID,
+        },
+        instruction::WithdrawTreasuryInstruction { amount },
+    )
+}
+
+pub fn sol_treasury_setup(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::SolTreasurySetup {
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_sol_treasury_state().0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::SolTreasurySetupInstruction {},
+    )
+}
+
+pub fn sol_withdraw_treasury(admin: &Pubkey, destination: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::SolWithdrawTreasury {
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_sol_treasury_state().0,
+            destination: *destination,
+            admin: *admin,
+        },
+        instruction::SolWithdrawTreasuryInstruction { amount },
+    )
+}
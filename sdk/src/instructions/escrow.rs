@@ -0,0 +1,269 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+
+use dungeon_nft::{accounts, instruction};
+
+use super::build;
+use crate::{pda, EscrowKeys};
+
+// `keys.session_id` has to be the player's `next_session_id` from the session counter,
+// zero for a player that never opened an escrow
+pub fn transaction_setup(keys: &EscrowKeys, player_stake: u64, house_stake: u64) -> Instruction {
+    build(
+        accounts::TransactionSetup {
+            session_counter: pda::find_session_counter(&keys.player).0,
+            player_profile: pda::find_player_profile(&keys.player).0,
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            escrow_account: keys.escrow_account(),
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_registry: pda::find_mint_registry().0,
+            mint_of_token: keys.mint_of_token,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::TransactionSetupInstruction {
+            player_stake,
+            house_stake,
+        },
+    )
+}
+
+pub fn deposit_by_player(keys: &EscrowKeys, player_stake: u64) -> Instruction {
+    build(
+        accounts::DepositByPlayer {
+            transaction_state: keys.transaction_state(),
+            escrow_account: keys.escrow_account(),
+            program_config: pda::find_program_config().0,
+            mint_registry: pda::find_mint_registry().0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+            player_associated_token_account: get_associated_token_address(
+                &keys.player,
+                &keys.mint_of_token,
+            ),
+            token_program: token::ID,
+        },
+        instruction::DepositByPlayerInstruction { player_stake },
+    )
+}
+
+pub fn deposit_by_house(keys: &EscrowKeys, house_stake: u64) -> Instruction {
+    build(
+        accounts::DepositByHouse {
+            transaction_state: keys.transaction_state(),
+            escrow_account: keys.escrow_account(),
+            program_config: pda::find_program_config().0,
+            mint_registry: pda::find_mint_registry().0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+            beneficiary_associated_token_account: get_associated_token_address(
+                &keys.beneficiary,
+                &keys.mint_of_token,
+            ),
+            token_program: token::ID,
+        },
+        instruction::DepositByHouseInstruction { house_stake },
+    )
+}
+
+// the referee's ed25519 instruction over `result_message` has to come right before this one
+pub fn transfer_to_winner(
+    keys: &EscrowKeys,
+    winner: Pubkey,
+    nonce: u64,
+    expiry: i64,
+) -> Instruction {
+    build(
+        accounts::TransferToWinner {
+            transaction_state: keys.transaction_state(),
+            escrow_account: keys.escrow_account(),
+            program_config: pda::find_program_config().0,
+            instructions_sysvar: sysvar::instructions::ID,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+        },
+        instruction::TransferToWinnerInstruction {
+            winner,
+            nonce,
+            expiry,
+        },
+    )
+}
+
+// the referee's ed25519 instruction over `split_message` has to come right before this one
+pub fn split_settlement(
+    keys: &EscrowKeys,
+    player_share: u64,
+    beneficiary_share: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Instruction {
+    build(
+        accounts::SplitSettlement {
+            transaction_state: keys.transaction_state(),
+            escrow_account: keys.escrow_account(),
+            program_config: pda::find_program_config().0,
+            instructions_sysvar: sysvar::instructions::ID,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+        },
+        instruction::SplitSettlementInstruction {
+            player_share,
+            beneficiary_share,
+            nonce,
+            expiry,
+        },
+    )
+}
+
+// takes the state directly since it works on token and lamport escrows alike
+pub fn raise_dispute(transaction_state: &Pubkey, disputer: &Pubkey) -> Instruction {
+    build(
+        accounts::RaiseDispute {
+            transaction_state: *transaction_state,
+            program_config: pda::find_program_config().0,
+            disputer: *disputer,
+        },
+        instruction::RaiseDisputeInstruction {},
+    )
+}
+
+pub fn resolve_dispute(transaction_state: &Pubkey, arbiter: &Pubkey, uphold: bool) -> Instruction {
+    build(
+        accounts::ResolveDispute {
+            transaction_state: *transaction_state,
+            program_config: pda::find_program_config().0,
+            arbiter: *arbiter,
+        },
+        instruction::ResolveDisputeInstruction { uphold },
+    )
+}
+
+// `season` is the config's current season, its board picks up the player's new score
+pub fn finalize_settlement(keys: &EscrowKeys, season: u32) -> Instruction {
+    build(
+        accounts::FinalizeSettlement {
+            transaction_state: keys.transaction_state(),
+            escrow_account: keys.escrow_account(),
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_treasury_state(&keys.mint_of_token).0,
+            treasury_vault: pda::find_treasury_vault(&keys.mint_of_token).0,
+            player_profile: pda::find_player_profile(&keys.player).0,
+            leaderboard: pda::find_leaderboard(season).0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+            player_associated_token_account: get_associated_token_address(
+                &keys.player,
+                &keys.mint_of_token,
+            ),
+            beneficiary_associated_token_account: get_associated_token_address(
+                &keys.beneficiary,
+                &keys.mint_of_token,
+            ),
+            token_program: token::ID,
+        },
+        instruction::FinalizeSettlementInstruction {},
+    )
+}
+
+pub fn pullback(keys: &EscrowKeys) -> Instruction {
+    build(
+        accounts::PullBack {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            escrow_account: keys.escrow_account(),
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+            player_associated_token_account: get_associated_token_address(
+                &keys.player,
+                &keys.mint_of_token,
+            ),
+            beneficiary_associated_token_account: get_associated_token_address(
+                &keys.beneficiary,
+                &keys.mint_of_token,
+            ),
+            token_program: token::ID,
+        },
+        instruction::PullbackInstruction {},
+    )
+}
+
+// for an escrow from before session ids, once migrate_account has brought it up to date
+pub fn legacy_pullback(
+    player: &Pubkey,
+    beneficiary: &Pubkey,
+    mint_of_token: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::LegacyPullBack {
+            transaction_state: pda::find_legacy_transaction_state(
+                player,
+                beneficiary,
+                mint_of_token,
+            )
+            .0,
+            program_config: pda::find_program_config().0,
+            escrow_account: pda::find_legacy_escrow_account(player, beneficiary, mint_of_token).0,
+            player: *player,
+            beneficiary: *beneficiary,
+            mint_of_token: *mint_of_token,
+            player_associated_token_account: get_associated_token_address(player, mint_of_token),
+            beneficiary_associated_token_account: get_associated_token_address(
+                beneficiary,
+                mint_of_token,
+            ),
+            token_program: token::ID,
+        },
+        instruction::LegacyPullbackInstruction {},
+    )
+}
+
+// `authority` is the player or the house
+pub fn cancel_transaction(keys: &EscrowKeys, authority: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelTransaction {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            escrow_account: keys.escrow_account(),
+            authority: *authority,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            mint_of_token: keys.mint_of_token,
+            player_associated_token_account: get_associated_token_address(
+                &keys.player,
+                &keys.mint_of_token,
+            ),
+            beneficiary_associated_token_account: get_associated_token_address(
+                &keys.beneficiary,
+                &keys.mint_of_token,
+            ),
+            token_program: token::ID,
+        },
+        instruction::CancelTransactionInstruction {},
+    )
+}
+
+// closes a terminal escrow of either kind, the rent goes back to the player
+pub fn close_transaction(transaction_state: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseTransaction {
+            transaction_state: *transaction_state,
+            program_config: pda::find_program_config().0,
+            player: *player,
+        },
+        instruction::CloseTransactionInstruction {},
+    )
+}
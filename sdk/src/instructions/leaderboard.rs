@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+
+use dungeon_nft::{accounts, instruction};
+
+use super::build;
+use crate::pda;
+
+pub fn leaderboard_setup(admin: &Pubkey, season: u32, starts_at: i64, ends_at: i64) -> Instruction {
+    build(
+        accounts::LeaderboardSetup {
+            program_config: pda::find_program_config().0,
+            leaderboard: pda::find_leaderboard(season).0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::LeaderboardSetupInstruction {
+            season,
+            starts_at,
+            ends_at,
+        },
+    )
+}
+
+pub fn update_leaderboard(season: u32, player: &Pubkey) -> Instruction {
+    build(
+        accounts::UpdateLeaderboard {
+            leaderboard: pda::find_leaderboard(season).0,
+            player_profile: pda::find_player_profile(player).0,
+        },
+        instruction::UpdateLeaderboardInstruction {},
+    )
+}
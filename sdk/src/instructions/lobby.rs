@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+
+use dungeon_nft::{accounts, instruction};
+
+use super::build;
+use crate::{pda, LobbyKeys};
+
+pub fn lobby_setup(
+    keys: &LobbyKeys,
+    entry_stake: u64,
+    max_players: u8,
+    payout_bps: Vec<u16>,
+) -> Instruction {
+    build(
+        accounts::LobbySetup {
+            lobby_state: keys.lobby_state(),
+            program_config: pda::find_program_config().0,
+            lobby_vault: keys.lobby_vault(),
+            creator: keys.creator,
+            mint_of_token: keys.mint_of_token,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::LobbySetupInstruction {
+            lobby_id: keys.lobby_id,
+            entry_stake,
+            max_players,
+            payout_bps,
+        },
+    )
+}
+
+pub fn join_lobby(keys: &LobbyKeys, player: &Pubkey) -> Instruction {
+    build(
+        accounts::JoinLobby {
+            lobby_state: keys.lobby_state(),
+            program_config: pda::find_program_config().0,
+            lobby_vault: keys.lobby_vault(),
+            player: *player,
+            player_profile: pda::find_player_profile(player).0,
+            player_token_account: get_associated_token_address(player, &keys.mint_of_token),
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        instruction::JoinLobbyInstruction {},
+    )
+}
+
+pub fn lock_lobby(keys: &LobbyKeys) -> Instruction {
+    build(
+        accounts::LockLobby {
+            lobby_state: keys.lobby_state(),
+            program_config: pda::find_program_config().0,
+            creator: keys.creator,
+        },
+        instruction::LockLobbyInstruction {},
+    )
+}
+
+// pays the winners' associated token accounts, in payout order, after the referee's ed25519
+// instruction over `lobby_message`, `players` as for cancel_lobby to update their profiles,
+// `season` is the config's current season, as for finalize_settlement
+pub fn settle_lobby(
+    keys: &LobbyKeys,
+    winners: Vec<Pubkey>,
+    players: &[Pubkey],
+    expiry: i64,
+    season: u32,
+) -> Instruction {
+    let mut ix = build(
+        accounts::SettleLobby {
+            lobby_state: keys.lobby_state(),
+            lobby_vault: keys.lobby_vault(),
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_treasury_state(&keys.mint_of_token).0,
+            treasury_vault: pda::find_treasury_vault(&keys.mint_of_token).0,
+            leaderboard: pda::find_leaderboard(season).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            creator: keys.creator,
+            token_program: token::ID,
+        },
+        instruction::SettleLobbyInstruction {
+            winners: winners.clone(),
+            expiry,
+        },
+    );
+
+    ix.accounts.extend(winners.iter().map(|winner| {
+        AccountMeta::new(
+            get_associated_token_address(winner, &keys.mint_of_token),
+            false,
+        )
+    }));
+    ix.accounts.extend(
+        players
+            .iter()
+            .map(|player| AccountMeta::new(pda::find_player_profile(player).0, false)),
+    );
+
+    ix
+}
+
+// `players` has to be in the order they joined, as stored on the lobby state
+pub fn cancel_lobby(keys: &LobbyKeys, players: &[Pubkey]) -> Instruction {
+    let ix = build(
+        accounts::CancelLobby {
+            lobby_state: keys.lobby_state(),
+            program_config: pda::find_program_config().0,
+            lobby_vault: keys.lobby_vault(),
+            treasury_state: pda::find_treasury_state(&keys.mint_of_token).0,
+            treasury_vault: pda::find_treasury_vault(&keys.mint_of_token).0,
+            creator: keys.creator,
+            token_program: token::ID,
+        },
+        instruction::CancelLobbyInstruction {},
+    );
+
+    with_player_accounts(ix, keys, players)
+}
+
+// anyone can send this once the lobby's deadline has passed, `players` as for cancel_lobby
+pub fn refund_lobby(keys: &LobbyKeys, players: &[Pubkey]) -> Instruction {
+    let ix = build(
+        accounts::RefundLobby {
+            lobby_state: keys.lobby_state(),
+            program_config: pda::find_program_config().0,
+            lobby_vault: keys.lobby_vault(),
+            treasury_state: pda::find_treasury_state(&keys.mint_of_token).0,
+            treasury_vault: pda::find_treasury_vault(&keys.mint_of_token).0,
+            creator: keys.creator,
+            token_program: token::ID,
+        },
+        instruction::RefundLobbyInstruction {},
+    );
+
+    with_player_accounts(ix, keys, players)
+}
+
+fn with_player_accounts(mut ix: Instruction, keys: &LobbyKeys, players: &[Pubkey]) -> Instruction {
+    ix.accounts.extend(players.iter().map(|player| {
+        AccountMeta::new(
+            get_associated_token_address(player, &keys.mint_of_token),
+            false,
+        )
+    }));
+
+    ix
+}
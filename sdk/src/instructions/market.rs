@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::token;
+
+use dungeon_nft::{accounts, instruction};
+
+use super::build;
+use crate::{pda, LiquidityProvider, MarketKeys};
+
+pub fn amm_setup(
+    keys: &MarketKeys,
+    token_mint: &Pubkey,
+    sol_mint: &Pubkey,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Instruction {
+    build(
+        accounts::AMMSetup {
+            token_mint: *token_mint,
+            sol_mint: *sol_mint,
+            market_state: keys.market_state(),
+            program_config: pda::find_program_config().0,
+            token_vault: keys.token_vault(),
+            sol_vault: keys.sol_vault(),
+            lp_mint: keys.lp_mint(),
+            lp_lock: keys.lp_lock(),
+            beneficiary: keys.beneficiary,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::AmmSetupInstruction {
+            fee_numerator,
+            fee_denominator,
+        },
+    )
+}
+
+// only for markets migrated from before LP shares, `token_mint` is the one the token vault holds
+pub fn lp_setup(keys: &MarketKeys, token_mint: &Pubkey) -> Instruction {
+    build(
+        accounts::LpSetup {
+            market_state: keys.market_state(),
+            program_config: pda::find_program_config().0,
+            token_vault: keys.token_vault(),
+            token_mint: *token_mint,
+            lp_mint: keys.lp_mint(),
+            lp_lock: keys.lp_lock(),
+            beneficiary: keys.beneficiary,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::LpSetupInstruction {},
+    )
+}
+
+pub fn add_liquidity(
+    keys: &MarketKeys,
+    provider: &LiquidityProvider,
+    max_token_amount: u64,
+    max_sol_amount: u64,
+) -> Instruction {
+    build(
+        accounts::LiquidityOperation {
+            market_state: keys.market_state(),
+            program_config: pda::find_program_config().0,
+            token_vault: keys.token_vault(),
+            sol_vault: keys.sol_vault(),
+            lp_mint: keys.lp_mint(),
+            lp_lock: keys.lp_lock(),
+            provider_token_ata: provider.token_ata,
+            provider_sol_ata: provider.sol_ata,
+            provider_lp_ata: provider.lp_ata,
+            provider: provider.provider,
+            token_program: token::ID,
+        },
+        instruction::AddLiquidityInstruction {
+            max_token_amount,
+            max_sol_amount,
+        },
+    )
+}
+
+pub fn remove_liquidity(
+    keys: &MarketKeys,
+    provider: &LiquidityProvider,
+    lp_amount: u64,
+    min_token_amount: u64,
+    min_sol_amount: u64,
+) -> Instruction {
+    build(
+        accounts::LiquidityOperation {
+            market_state: keys.market_state(),
+            program_config: pda::find_program_config().0,
+            token_vault: keys.token_vault(),
+            sol_vault: keys.sol_vault(),
+            lp_mint: keys.lp_mint(),
+            lp_lock: keys.lp_lock(),
+            provider_token_ata: provider.token_ata,
+            provider_sol_ata: provider.sol_ata,
+            provider_lp_ata: provider.lp_ata,
+            provider: provider.provider,
+            token_program: token::ID,
+        },
+        instruction::RemoveLiquidityInstruction {
+            lp_amount,
+            min_token_amount,
+            min_sol_amount,
+        },
+    )
+}
+
+// the swap's arguments, as the program takes them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount_in: u64,
+    pub sol_to_token: bool,
+    // the swap fails rather than pay out less, see quote::min_amount_out
+    pub min_amount_out: u64,
+    // unix timestamp the swap has to land by
+    pub deadline: Option<i64>,
+}
+
+pub fn swap_tokens(
+    keys: &MarketKeys,
+    player: &Pubkey,
+    player_token_ata: &Pubkey,
+    player_sol_ata: &Pubkey,
+    params: SwapParams,
+) -> Instruction {
+    build(
+        accounts::SwapTokens {
+            market_state: keys.market_state(),
+            program_config: pda::find_program_config().0,
+            token_vault: keys.token_vault(),
+            sol_vault: keys.sol_vault(),
+            player_token_ata: *player_token_ata,
+            player_sol_ata: *player_sol_ata,
+            player: *player,
+            beneficiary: keys.beneficiary,
+            token_program: token::ID,
+        },
+        instruction::SwapTokensInstruction {
+            amount_in: params.amount_in,
+            sol_to_token: params.sol_to_token,
+            min_amount_out: params.min_amount_out,
+            deadline: params.deadline,
+        },
+    )
+}
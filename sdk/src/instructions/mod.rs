@@ -0,0 +1,32 @@
+//! One builder per `dungeon_nft` instruction. They derive every PDA themselves and only ask for
+//! the keys that can't be derived; the signers still have to sign the transaction.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+
+pub mod admin;
+pub use admin::*;
+
+pub mod escrow;
+pub use escrow::*;
+
+pub mod sol_escrow;
+pub use sol_escrow::*;
+
+pub mod leaderboard;
+pub use leaderboard::*;
+
+pub mod lobby;
+pub use lobby::*;
+
+pub mod market;
+pub use market::*;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: dungeon_nft::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+
+use dungeon_nft::{accounts, instruction};
+
+use super::build;
+use crate::{pda, SolEscrowKeys};
+
+// `keys.session_id` has to be the player's `next_session_id`, the counter is shared with
+// token escrows
+pub fn sol_transaction_setup(
+    keys: &SolEscrowKeys,
+    player_stake: u64,
+    house_stake: u64,
+) -> Instruction {
+    build(
+        accounts::SolTransactionSetup {
+            session_counter: pda::find_session_counter(&keys.player).0,
+            player_profile: pda::find_player_profile(&keys.player).0,
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            system_program: system_program::ID,
+        },
+        instruction::SolTransactionSetupInstruction {
+            player_stake,
+            house_stake,
+        },
+    )
+}
+
+pub fn sol_deposit_by_player(keys: &SolEscrowKeys, player_stake: u64) -> Instruction {
+    build(
+        accounts::SolDepositByPlayer {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            system_program: system_program::ID,
+        },
+        instruction::SolDepositByPlayerInstruction { player_stake },
+    )
+}
+
+pub fn sol_deposit_by_house(keys: &SolEscrowKeys, house_stake: u64) -> Instruction {
+    build(
+        accounts::SolDepositByHouse {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+            system_program: system_program::ID,
+        },
+        instruction::SolDepositByHouseInstruction { house_stake },
+    )
+}
+
+// the referee signs over the state address here, it doubles as the escrow
+pub fn sol_transfer_to_winner(
+    keys: &SolEscrowKeys,
+    winner: Pubkey,
+    nonce: u64,
+    expiry: i64,
+) -> Instruction {
+    build(
+        accounts::SolTransferToWinner {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            instructions_sysvar: sysvar::instructions::ID,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+        },
+        instruction::SolTransferToWinnerInstruction {
+            winner,
+            nonce,
+            expiry,
+        },
+    )
+}
+
+pub fn sol_split_settlement(
+    keys: &SolEscrowKeys,
+    player_share: u64,
+    beneficiary_share: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Instruction {
+    build(
+        accounts::SolSplitSettlement {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            instructions_sysvar: sysvar::instructions::ID,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+        },
+        instruction::SolSplitSettlementInstruction {
+            player_share,
+            beneficiary_share,
+            nonce,
+            expiry,
+        },
+    )
+}
+
+// `season` is the config's current season, as for finalize_settlement
+pub fn sol_finalize_settlement(keys: &SolEscrowKeys, season: u32) -> Instruction {
+    build(
+        accounts::SolFinalizeSettlement {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            treasury_state: pda::find_sol_treasury_state().0,
+            player_profile: pda::find_player_profile(&keys.player).0,
+            leaderboard: pda::find_leaderboard(season).0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+        },
+        instruction::SolFinalizeSettlementInstruction {},
+    )
+}
+
+pub fn sol_pullback(keys: &SolEscrowKeys) -> Instruction {
+    build(
+        accounts::SolPullBack {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+        },
+        instruction::SolPullbackInstruction {},
+    )
+}
+
+// `authority` is the player or the house
+pub fn sol_cancel_transaction(keys: &SolEscrowKeys, authority: &Pubkey) -> Instruction {
+    build(
+        accounts::SolCancelTransaction {
+            transaction_state: keys.transaction_state(),
+            program_config: pda::find_program_config().0,
+            authority: *authority,
+            player: keys.player,
+            beneficiary: keys.beneficiary,
+        },
+        instruction::SolCancelTransactionInstruction {},
+    )
+}
@@ -0,0 +1,118 @@
+//! Client side helpers for the `dungeon_nft` program.
+//!
+//! The seeds, account lists and argument layouts all come from the program crate itself
+//! (built with the `cpi` feature), so they can't drift from what's deployed.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+pub use dungeon_nft::ID;
+pub use dungeon_nft::{error, events, math, state};
+
+// what the referee signs, handy for the game server
+pub use dungeon_nft::utils::{lobby_message, result_message, split_message};
+pub use dungeon_nft::utils::{LobbyStage, Stage};
+
+use anchor_lang::prelude::Pubkey;
+
+// everything that identifies a token escrow
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EscrowKeys {
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub session_id: u64,
+}
+
+impl EscrowKeys {
+    pub fn transaction_state(&self) -> Pubkey {
+        pda::find_transaction_state(
+            &self.player,
+            &self.beneficiary,
+            &self.mint_of_token,
+            self.session_id,
+        )
+        .0
+    }
+
+    pub fn escrow_account(&self) -> Pubkey {
+        pda::find_escrow_account(
+            &self.player,
+            &self.beneficiary,
+            &self.mint_of_token,
+            self.session_id,
+        )
+        .0
+    }
+}
+
+// everything that identifies a lamport escrow, the stakes sit on the state account itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolEscrowKeys {
+    pub player: Pubkey,
+    pub beneficiary: Pubkey,
+    pub session_id: u64,
+}
+
+impl SolEscrowKeys {
+    pub fn transaction_state(&self) -> Pubkey {
+        pda::find_sol_transaction_state(&self.player, &self.beneficiary, self.session_id).0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LobbyKeys {
+    pub creator: Pubkey,
+    pub mint_of_token: Pubkey,
+    pub lobby_id: u64,
+}
+
+impl LobbyKeys {
+    pub fn lobby_state(&self) -> Pubkey {
+        pda::find_lobby_state(&self.creator, &self.mint_of_token, self.lobby_id).0
+    }
+
+    pub fn lobby_vault(&self) -> Pubkey {
+        pda::find_lobby_vault(&self.lobby_state()).0
+    }
+}
+
+// a market is keyed by the house that opened it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MarketKeys {
+    pub beneficiary: Pubkey,
+}
+
+impl MarketKeys {
+    pub fn market_state(&self) -> Pubkey {
+        pda::find_market_state(&self.beneficiary).0
+    }
+
+    pub fn token_vault(&self) -> Pubkey {
+        pda::find_token_vault(&self.market_state(), &self.beneficiary).0
+    }
+
+    pub fn sol_vault(&self) -> Pubkey {
+        pda::find_sol_vault(&self.market_state(), &self.beneficiary).0
+    }
+
+    pub fn lp_mint(&self) -> Pubkey {
+        pda::find_lp_mint(&self.market_state(), &self.beneficiary).0
+    }
+
+    pub fn lp_lock(&self) -> Pubkey {
+        pda::find_lp_lock(&self.market_state(), &self.beneficiary).0
+    }
+}
+
+// whoever adds or removes liquidity, with the token accounts they pay from and are paid into.
+// it doesn't have to be the house that owns the market
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityProvider {
+    pub provider: Pubkey,
+    pub token_ata: Pubkey,
+    pub sol_ata: Pubkey,
+    pub lp_ata: Pubkey,
+}
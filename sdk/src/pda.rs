@@ -0,0 +1,177 @@
+//! The seed derivations of every account the program owns, mirroring the `seeds = [...]`
+//! constraints in `programs/DungeonNFT/src/instructions`.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use dungeon_nft::ID;
+
+// kept by the upgradeable loader, not by the program
+pub fn find_program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+pub fn find_program_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"program-config"], &ID)
+}
+
+pub fn find_mint_registry() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint-registry"], &ID)
+}
+
+pub fn find_session_counter(player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"session-counter", player.as_ref()], &ID)
+}
+
+pub fn find_player_profile(player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"player-profile", player.as_ref()], &ID)
+}
+
+pub fn find_leaderboard(season: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"leaderboard", season.to_le_bytes().as_ref()], &ID)
+}
+
+pub fn find_transaction_state(
+    player: &Pubkey,
+    beneficiary: &Pubkey,
+    mint_of_token: &Pubkey,
+    session_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"transaction-state",
+            player.as_ref(),
+            beneficiary.as_ref(),
+            mint_of_token.as_ref(),
+            session_id.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn find_escrow_account(
+    player: &Pubkey,
+    beneficiary: &Pubkey,
+    mint_of_token: &Pubkey,
+    session_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"escrow-account",
+            player.as_ref(),
+            beneficiary.as_ref(),
+            mint_of_token.as_ref(),
+            session_id.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+}
+
+// escrows opened before session ids, only legacy_pullback still derives these
+pub fn find_legacy_transaction_state(
+    player: &Pubkey,
+    beneficiary: &Pubkey,
+    mint_of_token: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"transaction-state",
+            player.as_ref(),
+            beneficiary.as_ref(),
+            mint_of_token.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn find_legacy_escrow_account(
+    player: &Pubkey,
+    beneficiary: &Pubkey,
+    mint_of_token: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"escrow-account",
+            player.as_ref(),
+            beneficiary.as_ref(),
+            mint_of_token.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn find_sol_transaction_state(
+    player: &Pubkey,
+    beneficiary: &Pubkey,
+    session_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"sol-transaction-state",
+            player.as_ref(),
+            beneficiary.as_ref(),
+            session_id.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn find_treasury_state(mint_of_token: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury-state", mint_of_token.as_ref()], &ID)
+}
+
+pub fn find_treasury_vault(mint_of_token: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury-vault", mint_of_token.as_ref()], &ID)
+}
+
+pub fn find_sol_treasury_state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sol-treasury-state"], &ID)
+}
+
+pub fn find_lobby_state(creator: &Pubkey, mint_of_token: &Pubkey, lobby_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"lobby-state",
+            creator.as_ref(),
+            mint_of_token.as_ref(),
+            lobby_id.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn find_lobby_vault(lobby_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"lobby-vault", lobby_state.as_ref()], &ID)
+}
+
+pub fn find_market_state(beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"market-state", beneficiary.as_ref()], &ID)
+}
+
+pub fn find_token_vault(market_state: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"token-vault", market_state.as_ref(), beneficiary.as_ref()],
+        &ID,
+    )
+}
+
+pub fn find_sol_vault(market_state: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"sol-vault", market_state.as_ref(), beneficiary.as_ref()],
+        &ID,
+    )
+}
+
+pub fn find_lp_mint(market_state: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"lp-mint", market_state.as_ref(), beneficiary.as_ref()],
+        &ID,
+    )
+}
+
+pub fn find_lp_lock(market_state: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"lp-lock", market_state.as_ref(), beneficiary.as_ref()],
+        &ID,
+    )
+}
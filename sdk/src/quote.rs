@@ -0,0 +1,75 @@
+//! Off-chain quotes for the markets, worked out with the same math the program runs.
+
+use dungeon_nft::math::{self, MathResult};
+use dungeon_nft::state::MarketState;
+
+// the vaults in the order a swap in the given direction uses them, paid into first
+fn reserves(token_vault: u64, sol_vault: u64, sol_to_token: bool) -> (u64, u64) {
+    if sol_to_token {
+        (sol_vault, token_vault)
+    } else {
+        (token_vault, sol_vault)
+    }
+}
+
+// what swap_tokens pays out for `amount_in`, given the current vault balances
+pub fn swap_out(
+    market: &MarketState,
+    token_vault: u64,
+    sol_vault: u64,
+    amount_in: u64,
+    sol_to_token: bool,
+) -> MathResult<u64> {
+    let (reserve_in, reserve_out) = reserves(token_vault, sol_vault, sol_to_token);
+    math::quote_out(
+        amount_in,
+        reserve_in,
+        reserve_out,
+        market.fee_numerator,
+        market.fee_denominator,
+    )
+}
+
+// the min_amount_out to pass along with a quote, allowing `slippage_bps` basis points less
+pub fn min_amount_out(amount_out: u64, slippage_bps: u16) -> u64 {
+    let kept = 10_000u128.saturating_sub(u128::from(slippage_bps));
+    (u128::from(amount_out) * kept / 10_000) as u64
+}
+
+// what has to go into swap_tokens to get at least `amount_out` back
+pub fn swap_in(
+    market: &MarketState,
+    token_vault: u64,
+    sol_vault: u64,
+    amount_out: u64,
+    sol_to_token: bool,
+) -> MathResult<u64> {
+    let (reserve_in, reserve_out) = reserves(token_vault, sol_vault, sol_to_token);
+    math::quote_in(
+        amount_out,
+        reserve_in,
+        reserve_out,
+        market.fee_numerator,
+        market.fee_denominator,
+    )
+}
+
+// the token and sol add_liquidity takes out of the two maxima once the pool is seeded
+pub fn deposit(
+    token_vault: u64,
+    sol_vault: u64,
+    max_token_amount: u64,
+    max_sol_amount: u64,
+) -> MathResult<(u64, u64)> {
+    math::balanced_deposit(max_token_amount, max_sol_amount, token_vault, sol_vault)
+}
+
+// the token and sol remove_liquidity pays out for burning `lp_amount` of `lp_supply`
+pub fn withdrawal(
+    token_vault: u64,
+    sol_vault: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> MathResult<(u64, u64)> {
+    math::withdrawal_amounts(lp_amount, token_vault, sol_vault, lp_supply)
+}
@@ -24,9 +24,12 @@ interface State {
     playerTokenAssociatedTokenAccount: anchor.web3.PublicKey,
     playerSolAssociatedTokenAccount: anchor.web3.PublicKey,
 
+    programConfig: anchor.web3.PublicKey,
     marketState: anchor.web3.PublicKey,
     tokenVault: anchor.web3.PublicKey,
     solVault: anchor.web3.PublicKey,
+    lpMint: anchor.web3.PublicKey,
+    lpLock: anchor.web3.PublicKey,
 };
 
 const createUserAssociatedTokenAccount = async (provider: anchor.AnchorProvider, user: anchor.web3.PublicKey, userSigner: anchor.web3.Keypair, mint: anchor.web3.PublicKey, mintAuthority?: anchor.web3.PublicKey, mintAuthoritySigner?: anchor.web3.Keypair): Promise<anchor.web3.PublicKey> => {
@@ -104,10 +107,30 @@ const prereqs_setup_helper = async (provider: anchor.AnchorProvider, program: an
     let playerTokenAssociatedTokenAccount = await createUserAssociatedTokenAccount(provider, player, playerSigner, tokenMint, beneficiary, beneficiarySigner);
     let playerSolAssociatedTokenAccount = await createUserAssociatedTokenAccount(provider, player, playerSigner, solMint);
 
+    let [programConfig,] = await anchor.web3.PublicKey.findProgramAddress([anchor.utils.bytes.utf8.encode("program-config")], program.programId);
+
+    // markets can only be opened by the configured house
+    const existingConfig = await provider.connection.getAccountInfo(programConfig);
+    if (existingConfig === null) {
+        await program.methods.configSetupInstruction(beneficiary, provider.wallet.publicKey, provider.wallet.publicKey, new anchor.BN(60 * 60), new anchor.BN(0), 0).accounts({
+            programConfig: programConfig,
+            admin: provider.wallet.publicKey,
+            programData: (await anchor.web3.PublicKey.findProgramAddress([program.programId.toBuffer()], new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")))[0],
+            systemProgram: anchor.web3.SystemProgram.programId,
+        }).rpc();
+    } else {
+        await program.methods.setHouseInstruction(beneficiary).accounts({
+            programConfig: programConfig,
+            admin: provider.wallet.publicKey,
+        }).rpc();
+    }
+
     let [marketState,] = await anchor.web3.PublicKey.findProgramAddress([anchor.utils.bytes.utf8.encode("market-state"), beneficiary.toBuffer()], program.programId);
 
     let [tokenVault,] = await anchor.web3.PublicKey.findProgramAddress([anchor.utils.bytes.utf8.encode("token-vault"), marketState.toBuffer(), beneficiary.toBuffer()], program.programId);
     let [solVault,] = await anchor.web3.PublicKey.findProgramAddress([anchor.utils.bytes.utf8.encode("sol-vault"), marketState.toBuffer(), beneficiary.toBuffer()], program.programId);
+    let [lpMint,] = await anchor.web3.PublicKey.findProgramAddress([anchor.utils.bytes.utf8.encode("lp-mint"), marketState.toBuffer(), beneficiary.toBuffer()], program.programId);
+    let [lpLock,] = await anchor.web3.PublicKey.findProgramAddress([anchor.utils.bytes.utf8.encode("lp-lock"), marketState.toBuffer(), beneficiary.toBuffer()], program.programId);
 
     return {
         tokenMint: tokenMint,
@@ -123,9 +146,12 @@ const prereqs_setup_helper = async (provider: anchor.AnchorProvider, program: an
         playerTokenAssociatedTokenAccount: playerTokenAssociatedTokenAccount,
         playerSolAssociatedTokenAccount: playerSolAssociatedTokenAccount,
 
+        programConfig: programConfig,
         marketState: marketState,
         tokenVault: tokenVault,
-        solVault: solVault
+        solVault: solVault,
+        lpMint: lpMint,
+        lpLock: lpLock
     };
 }
 
@@ -134,6 +160,7 @@ const initializeMarketHelper = async (state: State, program: anchor.Program<Dung
     let fee_den = new anchor.BN(1000);
 
     const tx = await program.methods.ammSetupInstruction(fee_num, fee_den).accounts({
+        programConfig: state.programConfig,
         tokenMint: state.tokenMint,
         solMint: state.solMint,
 
@@ -142,6 +169,9 @@ const initializeMarketHelper = async (state: State, program: anchor.Program<Dung
         tokenVault: state.tokenVault,
         solVault: state.solVault,
 
+        lpMint: state.lpMint,
+        lpLock: state.lpLock,
+
         beneficiary: state.beneficiary,
 
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -149,6 +179,9 @@ const initializeMarketHelper = async (state: State, program: anchor.Program<Dung
         rent: anchor.web3.SYSVAR_RENT_PUBKEY
     }).signers([state.beneficiarySigner]).rpc();
 
+    const marketState = await program.account.marketState.fetch(state.marketState);
+    assert.equal(marketState.version, 1);
+
     assert.ok(tx);
     console.log(`Initialized a new market with signature: ${tx}`);
 }
@@ -164,13 +197,26 @@ const addLiquidityHelper = async (state: State, provider: anchor.AnchorProvider,
     const tokenAmount = new anchor.BN(50 * 10 ** NUM_OF_DECIMALS);
     const solAmount = new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL);
 
+    // the pool shares are minted into the beneficiary's lp ata
+    const beneficiaryLpAta = await spl.getAssociatedTokenAddress(state.lpMint, state.beneficiary);
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(spl.createAssociatedTokenAccountInstruction(
+        state.beneficiary,
+        beneficiaryLpAta,
+        state.beneficiary,
+        state.lpMint
+    )), [state.beneficiarySigner]);
+
     const tx = await program.methods.addLiquidityInstruction(tokenAmount, solAmount).accounts({
+        programConfig: state.programConfig,
         marketState: state.marketState,
         tokenVault: state.tokenVault,
         solVault: state.solVault,
-        beneficiary: state.beneficiary,
-        beneficiaryTokenAta: state.beneficiaryTokenAssociatedTokenAccount,
-        beneficiarySolAta: state.beneficiarySolAssociatedTokenAccount,
+        lpMint: state.lpMint,
+        lpLock: state.lpLock,
+        provider: state.beneficiary,
+        providerTokenAta: state.beneficiaryTokenAssociatedTokenAccount,
+        providerSolAta: state.beneficiarySolAssociatedTokenAccount,
+        providerLpAta: beneficiaryLpAta,
         tokenProgram: spl.TOKEN_PROGRAM_ID
     }).signers([state.beneficiarySigner]).rpc();
 
@@ -186,6 +232,13 @@ const addLiquidityHelper = async (state: State, provider: anchor.AnchorProvider,
     const [, postAdditionSolVaultBalance] = await readTokenAccount(provider, state.solVault);
     assert.equal(postAdditionSolVaultBalance, 2 * anchor.web3.LAMPORTS_PER_SOL);
 
+    // sqrt(50e9 * 2e9) shares, less the 1000 locked in the market
+    const [, postAdditionBeneficiaryLpBalance] = await readTokenAccount(provider, beneficiaryLpAta);
+    assert.equal(postAdditionBeneficiaryLpBalance, 9999999000);
+
+    const [, postAdditionLpLockBalance] = await readTokenAccount(provider, state.lpLock);
+    assert.equal(postAdditionLpLockBalance, 1000);
+
     assert.ok(tx);
     console.log(`Added Liquidity to the market with signature: ${tx}`);
 }
@@ -199,7 +252,13 @@ const solToTokenHelper = async (state: State, provider: anchor.AnchorProvider, p
 
     const amount_in = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
 
-    const tx = await program.methods.swapTokensInstruction(amount_in, true).accounts({
+    let swapEvent = null;
+    const listener = program.addEventListener("SwapExecuted", (event) => {
+        swapEvent = event;
+    });
+
+    const tx = await program.methods.swapTokensInstruction(amount_in, true, new anchor.BN(16666666666), null).accounts({
+        programConfig: state.programConfig,
         marketState: state.marketState,
         tokenVault: state.tokenVault,
         solVault: state.solVault,
@@ -214,13 +273,20 @@ const solToTokenHelper = async (state: State, provider: anchor.AnchorProvider, p
     assert.equal(postSwapPlayerSolATABalance, 4 * anchor.web3.LAMPORTS_PER_SOL);
 
     const [, postSwapPlayerTokenATABalance] = await readTokenAccount(provider, state.playerTokenAssociatedTokenAccount);
-    assert.equal(postSwapPlayerTokenATABalance, 116666666667);
+    assert.equal(postSwapPlayerTokenATABalance, 116666666666);
 
     const [, postSwapSolVaultBalance] = await readTokenAccount(provider, state.solVault);
     assert.equal(postSwapSolVaultBalance, 3 * anchor.web3.LAMPORTS_PER_SOL);
 
     const [, postSwapTokenVaultBalance] = await readTokenAccount(provider, state.tokenVault);
-    assert.equal(postSwapTokenVaultBalance, 33333333333);
+    assert.equal(postSwapTokenVaultBalance, 33333333334);
+
+    // the event alone is enough to rebuild the vault balances
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    assert.equal(swapEvent.amountOut.toString(), "16666666666");
+    assert.equal(swapEvent.tokenVaultBalance.toString(), postSwapTokenVaultBalance);
+    assert.equal(swapEvent.solVaultBalance.toString(), postSwapSolVaultBalance);
 
     assert.ok(tx);
     console.log(`Swapped Sol to Token with signature: ${tx}`);
@@ -228,14 +294,15 @@ const solToTokenHelper = async (state: State, provider: anchor.AnchorProvider, p
 
 const tokenToSolHelper = async (state: State, provider: anchor.AnchorProvider, program: Program<DungeonNft>) => {
     const [, preSwapPlayerTokenATABalance] = await readTokenAccount(provider, state.playerTokenAssociatedTokenAccount);
-    assert.equal(preSwapPlayerTokenATABalance, 116666666667);
+    assert.equal(preSwapPlayerTokenATABalance, 116666666666);
 
     const [, preSwapPlayerSolATABalance] = await readTokenAccount(provider, state.playerSolAssociatedTokenAccount);
     assert.equal(preSwapPlayerSolATABalance, 4 * anchor.web3.LAMPORTS_PER_SOL);
 
     const amount_in = new anchor.BN(50 * 10 ** NUM_OF_DECIMALS);
 
-    const tx = await program.methods.swapTokensInstruction(amount_in, false).accounts({
+    const tx = await program.methods.swapTokensInstruction(amount_in, false, new anchor.BN(1799999999), null).accounts({
+        programConfig: state.programConfig,
         marketState: state.marketState,
         tokenVault: state.tokenVault,
         solVault: state.solVault,
@@ -247,22 +314,70 @@ const tokenToSolHelper = async (state: State, provider: anchor.AnchorProvider, p
     }).signers([state.playerSigner]).rpc();
 
     const [, postSwapPlayerSolATABalance] = await readTokenAccount(provider, state.playerSolAssociatedTokenAccount);
-    assert.equal(postSwapPlayerSolATABalance, 5800000001);
+    assert.equal(postSwapPlayerSolATABalance, 5799999999);
 
     const [, postSwapPlayerTokenATABalance] = await readTokenAccount(provider, state.playerTokenAssociatedTokenAccount);
-    assert.equal(postSwapPlayerTokenATABalance, 66666666667)
+    assert.equal(postSwapPlayerTokenATABalance, 66666666666)
 
     const [, postSwapSolVaultBalance] = await readTokenAccount(provider, state.solVault);
-    assert.equal(postSwapSolVaultBalance, 1199999999)
+    assert.equal(postSwapSolVaultBalance, 1200000001)
 
     const [, postSwapTokenVaultBalance] = await readTokenAccount(provider, state.tokenVault);
-    assert.equal(postSwapTokenVaultBalance, 83333333333);
+    assert.equal(postSwapTokenVaultBalance, 83333333334);
 
     assert.ok(tx);
     console.log(`Swapped Token to Sol with signature: ${tx}`);
 
 }
 
+const removeLiquidityHelper = async (state: State, provider: anchor.AnchorProvider, program: Program<DungeonNft>) => {
+    const beneficiaryLpAta = await spl.getAssociatedTokenAddress(state.lpMint, state.beneficiary);
+
+    // a tenth of the supply, which is a tenth of what's in each vault
+    const lpAmount = new anchor.BN(10 ** NUM_OF_DECIMALS);
+    const accounts = {
+        programConfig: state.programConfig,
+        marketState: state.marketState,
+        tokenVault: state.tokenVault,
+        solVault: state.solVault,
+        lpMint: state.lpMint,
+        lpLock: state.lpLock,
+        provider: state.beneficiary,
+        providerTokenAta: state.beneficiaryTokenAssociatedTokenAccount,
+        providerSolAta: state.beneficiarySolAssociatedTokenAccount,
+        providerLpAta: beneficiaryLpAta,
+        tokenProgram: spl.TOKEN_PROGRAM_ID
+    };
+
+    try {
+        await program.methods.removeLiquidityInstruction(lpAmount, new anchor.BN(8333333334), new anchor.BN(0))
+            .accounts(accounts).signers([state.beneficiarySigner]).rpc();
+        return assert.fail("Withdrawal below the minimum should be rejected");
+    } catch (e) {
+        assert.equal(e.error.errorCode.code, "SlippageExceeded");
+    }
+
+    const tx = await program.methods.removeLiquidityInstruction(lpAmount, new anchor.BN(8333333333), new anchor.BN(120000000))
+        .accounts(accounts).signers([state.beneficiarySigner]).rpc();
+
+    const [, postRemovalBeneficiaryTokenATABalance] = await readTokenAccount(provider, state.beneficiaryTokenAssociatedTokenAccount);
+    assert.equal(postRemovalBeneficiaryTokenATABalance, 58333333333);
+
+    const [, postRemovalBeneficiarySolATABalance] = await readTokenAccount(provider, state.beneficiarySolAssociatedTokenAccount);
+    assert.equal(postRemovalBeneficiarySolATABalance, 3120000000);
+
+    const [, postRemovalTokenVaultBalance] = await readTokenAccount(provider, state.tokenVault);
+    assert.equal(postRemovalTokenVaultBalance, 75000000001);
+
+    const [, postRemovalSolVaultBalance] = await readTokenAccount(provider, state.solVault);
+    assert.equal(postRemovalSolVaultBalance, 1080000001);
+
+    const [, postRemovalBeneficiaryLpBalance] = await readTokenAccount(provider, beneficiaryLpAta);
+    assert.equal(postRemovalBeneficiaryLpBalance, 8999999000);
+
+    assert.ok(tx);
+    console.log(`Removed liquidity from the market with signature: ${tx}`);
+}
 
 describe("DungeonNFTAMMSolToToken", () => {
     const provider = anchor.AnchorProvider.env();
@@ -291,4 +406,8 @@ describe("DungeonNFTAMMSolToToken", () => {
         await tokenToSolHelper(state, provider, program);
     })
 
+    it('can remove liquidity from the market pool', async () => {
+        await removeLiquidityHelper(state, provider, program);
+    })
+
 })
@@ -14,8 +14,14 @@ interface State {
   player: anchor.web3.PublicKey,
   playerSigner: anchor.web3.Keypair,
   playerAssociatedTokenAccount: anchor.web3.PublicKey,
+  sessionCounter: anchor.web3.PublicKey,
+  playerProfile: anchor.web3.PublicKey,
   transactionState: anchor.web3.PublicKey,
-  escrowAccount: anchor.web3.PublicKey
+  escrowAccount: anchor.web3.PublicKey,
+  programConfig: anchor.web3.PublicKey,
+  treasuryState: anchor.web3.PublicKey,
+  treasuryVault: anchor.web3.PublicKey,
+  mintRegistry: anchor.web3.PublicKey
 }
 
 const createUserAssociatedTokenAccount = async (provider: anchor.AnchorProvider, user: anchor.web3.PublicKey, userSigner: anchor.web3.Keypair, mint: anchor.web3.PublicKey, mintAuthority: anchor.web3.PublicKey, mintAuthoritySigner: anchor.web3.Keypair): Promise<anchor.web3.PublicKey> => {
@@ -76,12 +82,39 @@ const prereqs_setup_helper = async (provider: anchor.AnchorProvider, program: an
   const playerAssociatedTokenAccount = await createUserAssociatedTokenAccount(provider, player, playerSigner, mint, beneficiary, beneficiarySigner);
   const beneficiaryAssociatedTokenAccount = await createUserAssociatedTokenAccount(provider, beneficiary, beneficiarySigner, mint, beneficiary, beneficiarySigner);
 
+  let [sessionCounter,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("session-counter"), player.toBuffer()],
+    program.programId);
+
+  let [playerProfile,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("player-profile"), player.toBuffer()],
+    program.programId);
+
+  // a fresh player always starts at session 0
+  const sessionId = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
+
   let [transactionState,] = await anchor.web3.PublicKey.findProgramAddress(
-    [anchor.utils.bytes.utf8.encode("transaction-state"), player.toBuffer(), beneficiary.toBuffer(), mint.toBuffer()],
+    [anchor.utils.bytes.utf8.encode("transaction-state"), player.toBuffer(), beneficiary.toBuffer(), mint.toBuffer(), sessionId],
     program.programId);
 
   let [escrowAccount,] = await anchor.web3.PublicKey.findProgramAddress(
-    [anchor.utils.bytes.utf8.encode("escrow-account"), player.toBuffer(), beneficiary.toBuffer(), mint.toBuffer()],
+    [anchor.utils.bytes.utf8.encode("escrow-account"), player.toBuffer(), beneficiary.toBuffer(), mint.toBuffer(), sessionId],
+    program.programId);
+
+  let [programConfig,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("program-config")],
+    program.programId);
+
+  let [treasuryState,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("treasury-state"), mint.toBuffer()],
+    program.programId);
+
+  let [treasuryVault,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("treasury-vault"), mint.toBuffer()],
+    program.programId);
+
+  let [mintRegistry,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("mint-registry")],
     program.programId);
 
   return {
@@ -92,17 +125,111 @@ const prereqs_setup_helper = async (provider: anchor.AnchorProvider, program: an
     player: player,
     playerSigner: playerSigner,
     playerAssociatedTokenAccount: playerAssociatedTokenAccount,
+    sessionCounter: sessionCounter,
+    playerProfile: playerProfile,
     transactionState: transactionState,
-    escrowAccount: escrowAccount
+    escrowAccount: escrowAccount,
+    programConfig: programConfig,
+    treasuryState: treasuryState,
+    treasuryVault: treasuryVault,
+    mintRegistry: mintRegistry
+  }
+}
+
+const config_helper = async (state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>, referee: anchor.web3.PublicKey, arbiter: anchor.web3.PublicKey, settlementTimeout: number, disputeWindow: number, feeBps: number): Promise<void> => {
+  const timeout = new anchor.BN(settlementTimeout);
+  const challengeWindow = new anchor.BN(disputeWindow);
+
+  // the config is a singleton, so later suites only rotate the settings
+  const existingConfig = await provider.connection.getAccountInfo(state.programConfig);
+
+  if (existingConfig === null) {
+    await program.methods.configSetupInstruction(state.beneficiary, referee, arbiter, timeout, challengeWindow, feeBps).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+      // only the upgrade authority the program was deployed with can claim the config
+      programData: (await anchor.web3.PublicKey.findProgramAddress([program.programId.toBuffer()], new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")))[0],
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+  } else {
+    // every suite brings its own house, so it has to be swapped in before the escrow opens
+    await program.methods.setHouseInstruction(state.beneficiary).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setRefereeInstruction(referee).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setSettlementTimeoutInstruction(timeout).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setDisputePolicyInstruction(arbiter, challengeWindow).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setFeeInstruction(feeBps).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+  }
+}
+
+const treasury_helper = async (state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>): Promise<void> => {
+  const tx = await program.methods.treasurySetupInstruction().accounts({
+    programConfig: state.programConfig,
+    treasuryState: state.treasuryState,
+    treasuryVault: state.treasuryVault,
+    mintOfToken: state.mint,
+    admin: provider.wallet.publicKey,
+
+    systemProgram: anchor.web3.SystemProgram.programId,
+    tokenProgram: spl.TOKEN_PROGRAM_ID,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY
+  }).rpc();
+
+  assert.ok(tx);
+}
+
+const registry_helper = async (state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>): Promise<void> => {
+  // the registry is a singleton, every suite only adds its own mint
+  const existingRegistry = await provider.connection.getAccountInfo(state.mintRegistry);
+  if (existingRegistry === null) {
+    await program.methods.mintRegistrySetupInstruction().accounts({
+      programConfig: state.programConfig,
+      mintRegistry: state.mintRegistry,
+      admin: provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
   }
+
+  // each side can stake between 1 and 50 tokens
+  const tx = await program.methods.registerMintInstruction(NUM_OF_DECIMALS, new anchor.BN(1 * 10 ** NUM_OF_DECIMALS), new anchor.BN(50 * 10 ** NUM_OF_DECIMALS)).accounts({
+    programConfig: state.programConfig,
+    mintRegistry: state.mintRegistry,
+    mintOfToken: state.mint,
+    admin: provider.wallet.publicKey,
+  }).rpc();
+
+  assert.ok(tx);
 }
 
-const initialize_payment_helper = async (state: State, program: anchor.Program<DungeonNft>): Promise<void> => {
-  const tx = await program.methods.transactionSetupInstruction().accounts({
+// the house gives 3 to 1 odds on the run unless told otherwise
+const initialize_payment_helper = async (state: State, program: anchor.Program<DungeonNft>, playerStake: anchor.BN = new anchor.BN(10 * 10 ** NUM_OF_DECIMALS), houseStake: anchor.BN = new anchor.BN(30 * 10 ** NUM_OF_DECIMALS)): Promise<void> => {
+  const tx = await program.methods.transactionSetupInstruction(playerStake, houseStake).accounts({
+    programConfig: state.programConfig,
+    sessionCounter: state.sessionCounter,
+    playerProfile: state.playerProfile,
     transactionState: state.transactionState,
     escrowAccount: state.escrowAccount,
     player: state.player,
     beneficiary: state.beneficiary,
+    mintRegistry: state.mintRegistry,
     mintOfToken: state.mint,
 
     systemProgram: anchor.web3.SystemProgram.programId,
@@ -114,6 +241,34 @@ const initialize_payment_helper = async (state: State, program: anchor.Program<D
   console.log(`Initialized a new Safe Pay instance with signature: ${tx}`);
 }
 
+const player_deposit_helper = async (state: State, program: anchor.Program<DungeonNft>, playerStake: anchor.BN): Promise<string> => {
+  return await program.methods.depositByPlayerInstruction(playerStake).accounts({
+    transactionState: state.transactionState,
+    escrowAccount: state.escrowAccount,
+    programConfig: state.programConfig,
+    mintRegistry: state.mintRegistry,
+    player: state.player,
+    beneficiary: state.beneficiary,
+    mintOfToken: state.mint,
+    playerAssociatedTokenAccount: state.playerAssociatedTokenAccount,
+    tokenProgram: spl.TOKEN_PROGRAM_ID,
+  }).signers([state.playerSigner]).rpc();
+}
+
+const house_deposit_helper = async (state: State, program: anchor.Program<DungeonNft>, houseStake: anchor.BN): Promise<string> => {
+  return await program.methods.depositByHouseInstruction(houseStake).accounts({
+    transactionState: state.transactionState,
+    escrowAccount: state.escrowAccount,
+    programConfig: state.programConfig,
+    mintRegistry: state.mintRegistry,
+    player: state.player,
+    beneficiary: state.beneficiary,
+    mintOfToken: state.mint,
+    beneficiaryAssociatedTokenAccount: state.beneficiaryAssociatedTokenAccount,
+    tokenProgram: spl.TOKEN_PROGRAM_ID,
+  }).signers([state.beneficiarySigner]).rpc();
+}
+
 const deposit_helper = async(state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>): Promise<void> => {
   const [, preTransactionPlayerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
     assert.equal(preTransactionPlayerBalance, 100 * 10 ** NUM_OF_DECIMALS);
@@ -121,34 +276,136 @@ const deposit_helper = async(state: State, provider: anchor.AnchorProvider, prog
     const [, preTransactionBeneficiaryBalance] = await readTokenAccount(provider, state.beneficiaryAssociatedTokenAccount);
     assert.equal(preTransactionBeneficiaryBalance, 100 * 10 ** NUM_OF_DECIMALS);
 
-    const amount = new anchor.BN(10 * 10 ** NUM_OF_DECIMALS)
+    // the stakes the escrow was opened with
+    const playerStake = new anchor.BN(10 * 10 ** NUM_OF_DECIMALS);
+    const houseStake = new anchor.BN(30 * 10 ** NUM_OF_DECIMALS);
 
-    const tx = await program.methods.depositByBothPartiesInstruction(amount).accounts({
-      transactionState: state.transactionState,
-      escrowAccount: state.escrowAccount,
-      player: state.player,
-      beneficiary: state.beneficiary,
-      mintOfToken: state.mint,
-      playerAssociatedTokenAccount: state.playerAssociatedTokenAccount,
-      beneficiaryAssociatedTokenAccount: state.beneficiaryAssociatedTokenAccount,
+    await player_deposit_helper(state, program, playerStake);
 
-      tokenProgram: spl.TOKEN_PROGRAM_ID,
-    }).signers([state.playerSigner, state.beneficiarySigner]).rpc();
+    // the player alone doesn't fund the escrow
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 1);
+    assert.ok(transactionState.playerDeposited);
+
+    const tx = await house_deposit_helper(state, program, houseStake);
 
     const [, postTransactionPlayerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
     assert.equal(postTransactionPlayerBalance, 90 * 10 ** NUM_OF_DECIMALS);
 
     const [, postTransactionBeneficiaryBalance] = await readTokenAccount(provider, state.beneficiaryAssociatedTokenAccount);
-    assert.equal(postTransactionBeneficiaryBalance, 90 * 10 ** NUM_OF_DECIMALS);
+    assert.equal(postTransactionBeneficiaryBalance, 70 * 10 ** NUM_OF_DECIMALS);
 
     const [, postTransactoinEscrowBalance] = await readTokenAccount(provider, state.escrowAccount);
-    assert.equal(postTransactoinEscrowBalance, 2 * 10 * 10 ** NUM_OF_DECIMALS);
+    assert.equal(postTransactoinEscrowBalance, 40 * 10 ** NUM_OF_DECIMALS);
 
     assert.ok(tx);
     console.log(`Funded the escrow with signature: ${tx}`);
 
 }
 
+const close_transaction_helper = async (state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>): Promise<void> => {
+  const tx = await program.methods.closeTransactionInstruction().accounts({
+    programConfig: state.programConfig,
+    transactionState: state.transactionState,
+    player: state.player
+  }).rpc();
+
+  assert.ok(tx);
+
+  try {
+    await readTokenAccount(provider, state.transactionState);
+    return assert.fail("Account should be deleted");
+  } catch (e) {
+    assert.equal(e.message, "Cannot read properties of null (reading 'data')");
+  }
+}
+
+const settle_helper = async (state: State, program: anchor.Program<DungeonNft>, refereeSigner: anchor.web3.Keypair, winner: anchor.web3.PublicKey): Promise<string> => {
+  const transactionState = await program.account.transactionState.fetch(state.transactionState);
+  const nonce: anchor.BN = transactionState.resultNonce;
+  const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+
+  // escrow | winner | nonce | expiry, as laid out by utils::result_message
+  const message = Buffer.concat([
+    state.escrowAccount.toBuffer(),
+    winner.toBuffer(),
+    nonce.toArrayLike(Buffer, "le", 8),
+    expiry.toArrayLike(Buffer, "le", 8),
+  ]);
+
+  const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+    privateKey: refereeSigner.secretKey,
+    message: message,
+  });
+
+  return await program.methods.transferToWinnerInstruction(winner, nonce, expiry).accounts({
+    transactionState: state.transactionState,
+    escrowAccount: state.escrowAccount,
+    programConfig: state.programConfig,
+    instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    player: state.player,
+    beneficiary: state.beneficiary,
+    mintOfToken: state.mint
+  }).preInstructions([attestation]).rpc();
+}
+
+const split_helper = async (state: State, program: anchor.Program<DungeonNft>, refereeSigner: anchor.web3.Keypair, playerShare: anchor.BN, beneficiaryShare: anchor.BN): Promise<string> => {
+  const transactionState = await program.account.transactionState.fetch(state.transactionState);
+  const nonce: anchor.BN = transactionState.resultNonce;
+  const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+
+  // escrow | player share | beneficiary share | nonce | expiry, as laid out by utils::split_message
+  const message = Buffer.concat([
+    state.escrowAccount.toBuffer(),
+    playerShare.toArrayLike(Buffer, "le", 8),
+    beneficiaryShare.toArrayLike(Buffer, "le", 8),
+    nonce.toArrayLike(Buffer, "le", 8),
+    expiry.toArrayLike(Buffer, "le", 8),
+  ]);
+
+  const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+    privateKey: refereeSigner.secretKey,
+    message: message,
+  });
+
+  return await program.methods.splitSettlementInstruction(playerShare, beneficiaryShare, nonce, expiry).accounts({
+    transactionState: state.transactionState,
+    escrowAccount: state.escrowAccount,
+    programConfig: state.programConfig,
+    instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    player: state.player,
+    beneficiary: state.beneficiary,
+    mintOfToken: state.mint
+  }).preInstructions([attestation]).rpc();
+}
+
+// the board of the config's current season, it only exists once a season was opened
+const leaderboard_helper = async (state: State, program: anchor.Program<DungeonNft>): Promise<anchor.web3.PublicKey> => {
+  const programConfig = await program.account.programConfig.fetch(state.programConfig);
+  const [leaderboard,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("leaderboard"), new anchor.BN(programConfig.season).toArrayLike(Buffer, "le", 4)],
+    program.programId);
+  return leaderboard;
+}
+
+const finalize_helper = async (state: State, program: anchor.Program<DungeonNft>): Promise<string> => {
+  return await program.methods.finalizeSettlementInstruction().accounts({
+    transactionState: state.transactionState,
+    escrowAccount: state.escrowAccount,
+    programConfig: state.programConfig,
+    treasuryState: state.treasuryState,
+    treasuryVault: state.treasuryVault,
+    playerProfile: state.playerProfile,
+    leaderboard: await leaderboard_helper(state, program),
+    player: state.player,
+    beneficiary: state.beneficiary,
+    mintOfToken: state.mint,
+    playerAssociatedTokenAccount: state.playerAssociatedTokenAccount,
+    beneficiaryAssociatedTokenAccount: state.beneficiaryAssociatedTokenAccount,
+    tokenProgram: spl.TOKEN_PROGRAM_ID
+  }).rpc();
+}
+
 describe("DungeonNFTEscrowComplete", () => {
   // Configure the client to use the local cluster.
   const provider = anchor.AnchorProvider.env();
@@ -161,50 +418,215 @@ describe("DungeonNFTEscrowComplete", () => {
   //  const BENEFICIARY_ASSOCIATED_TOKEN_ACCOUNT = new anchor.web3.PublicKey('Ej4u57S8uUDSzbn8G9xFUXnbhBhUbWbWgyESwUEyM9HK');
 
   let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
 
   it('can setup all the prereqs', async () => {
     state = await prereqs_setup_helper(provider, program);
+    // 2.5% of every pot goes to the house
+    await config_helper(state, provider, program, refereeSigner.publicKey, provider.wallet.publicKey, 60 * 60, 0, 250);
+    await treasury_helper(state, provider, program);
+    await registry_helper(state, provider, program);
+  });
+
+  it('cannot open an escrow above the registered stake limit', async () => {
+    try {
+      await initialize_payment_helper(state, program, new anchor.BN(10 * 10 ** NUM_OF_DECIMALS), new anchor.BN(60 * 10 ** NUM_OF_DECIMALS));
+      return assert.fail("Escrow should be rejected");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "StakeOutOfRange");
+    }
   });
 
   it('can initialize a safe payment by the sender', async () => {
     await initialize_payment_helper(state, program);
   });
 
+  it('cannot fund the escrow with a different stake than agreed', async () => {
+    try {
+      await house_deposit_helper(state, program, new anchor.BN(20 * 10 ** NUM_OF_DECIMALS));
+      return assert.fail("Deposit should be rejected");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "StakeMismatch");
+    }
+  });
+
   it('can fund the escrow by the both the parties', async () => {
     await deposit_helper(state, provider, program);
   });
 
+  it('cannot transfer the funds without the referee attestation', async () => {
+    const impostor = new anchor.web3.Keypair;
+
+    try {
+      await settle_helper(state, program, impostor, state.player);
+      return assert.fail("Settlement should be rejected");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "UnauthorizedReferee");
+    }
+  });
+
   it('can transfer the entire funds to the winner', async () => {
 
     let winner: anchor.web3.PublicKey;
     let winnerAssociatedTokenAccount: anchor.web3.PublicKey;
+    let winnerBalance: number;
 
     if (Math.random() < 0.5) {
       winner = state.player;
       winnerAssociatedTokenAccount = state.playerAssociatedTokenAccount;
+      winnerBalance = 90;
     } else {
       winner = state.beneficiary;
       winnerAssociatedTokenAccount = state.beneficiaryAssociatedTokenAccount;
+      winnerBalance = 70;
     }
 
     const [, preTransactionWinnerBalance] = await readTokenAccount(provider, winnerAssociatedTokenAccount);
-    assert.equal(preTransactionWinnerBalance, 90 * 10 ** NUM_OF_DECIMALS);
+    assert.equal(preTransactionWinnerBalance, winnerBalance * 10 ** NUM_OF_DECIMALS);
 
     const [, preTransactoinEscrowBalance] = await readTokenAccount(provider, state.escrowAccount);
-    assert.equal(preTransactoinEscrowBalance, 2 * 10 * 10 ** NUM_OF_DECIMALS);
+    assert.equal(preTransactoinEscrowBalance, 40 * 10 ** NUM_OF_DECIMALS);
+
+    await settle_helper(state, program, refereeSigner, winner);
+
+    // no dispute window is configured, so the verdict pays out right away
+    const tx = await finalize_helper(state, program);
+
+    const [, postTransactionWinnerBalance] = await readTokenAccount(provider, winnerAssociatedTokenAccount);
+    assert.equal(postTransactionWinnerBalance, (winnerBalance + 39) * 10 ** NUM_OF_DECIMALS)
+
+    const [, postTransactionTreasuryBalance] = await readTokenAccount(provider, state.treasuryVault);
+    assert.equal(postTransactionTreasuryBalance, 1 * 10 ** NUM_OF_DECIMALS);
+
+    const treasuryState = await program.account.treasuryState.fetch(state.treasuryState);
+    assert.equal(treasuryState.totalFeesCollected.toString(), (1 * 10 ** NUM_OF_DECIMALS).toString());
+
+    // the player staked 10 and either took home 39 or nothing
+    const playerProfile = await program.account.playerProfile.fetch(state.playerProfile);
+    const playerWon = winner.equals(state.player);
+    assert.equal(playerProfile.gamesPlayed.toNumber(), 1);
+    assert.equal(playerProfile.wins.toNumber(), playerWon ? 1 : 0);
+    assert.equal(playerProfile.losses.toNumber(), playerWon ? 0 : 1);
+    assert.equal(playerProfile.mintStats[0].totalWagered.toString(), (10 * 10 ** NUM_OF_DECIMALS).toString());
+    assert.equal(playerProfile.mintStats[0].netProfit.toString(), ((playerWon ? 29 : -10) * 10 ** NUM_OF_DECIMALS).toString());
+
+    try {
+      await readTokenAccount(provider, state.escrowAccount);
+      return assert.fail("Account should be closed");
+    } catch (e) {
+      assert.equal(e.message, "Cannot read properties of null (reading 'data')");
+    }
+
+    // the outcome stays readable until the rent is reclaimed
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 3);
+
+    await close_transaction_helper(state, provider, program);
+
+    assert.ok(tx);
+    console.log(`Successfully transfered the funds to the winner with signature: ${tx}`);
+  });
+
+});
+
+
+describe("DungeonNFTSplit", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program);
+    await config_helper(state, provider, program, refereeSigner.publicKey, provider.wallet.publicKey, 60 * 60, 0, 250);
+    await treasury_helper(state, provider, program);
+    await registry_helper(state, provider, program);
+  });
+
+  it('can initialize a safe payment by the sender', async () => {
+    await initialize_payment_helper(state, program);
+  });
+
+  it('can fund the escrow by the both the parties', async () => {
+    await deposit_helper(state, provider, program);
+  });
+
+  it('can split the funds between both the parties', async () => {
+    await split_helper(state, program, refereeSigner, new anchor.BN(70), new anchor.BN(30));
+    const tx = await finalize_helper(state, program);
+
+    // 40 in the pot, 1 to the treasury, 70% of the remaining 39 to the player
+    const [, postTransactionPlayerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
+    assert.equal(postTransactionPlayerBalance, "117300000000");
+
+    const [, postTransactionBeneficiaryBalance] = await readTokenAccount(provider, state.beneficiaryAssociatedTokenAccount);
+    assert.equal(postTransactionBeneficiaryBalance, "81700000000");
+
+    const [, postTransactionTreasuryBalance] = await readTokenAccount(provider, state.treasuryVault);
+    assert.equal(postTransactionTreasuryBalance, 1 * 10 ** NUM_OF_DECIMALS);
+
+    try {
+      await readTokenAccount(provider, state.escrowAccount);
+      return assert.fail("Account should be closed");
+    } catch (e) {
+      assert.equal(e.message, "Cannot read properties of null (reading 'data')");
+    }
+
+    assert.ok(tx);
+    console.log(`Successfully split the funds with signature: ${tx}`);
+  });
+
+});
+
+
+describe("DungeonNFTCancel", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program);
+    await config_helper(state, provider, program, provider.wallet.publicKey, provider.wallet.publicKey, 60 * 60, 0, 0);
+    await registry_helper(state, provider, program);
+  });
+
+  it('can initialize a safe payment by the sender', async () => {
+    await initialize_payment_helper(state, program);
+  });
+
+  it('can deposit the player stake while the house has not paid in', async () => {
+    await player_deposit_helper(state, program, new anchor.BN(10 * 10 ** NUM_OF_DECIMALS));
+
+    const [, playerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
+    assert.equal(playerBalance, 90 * 10 ** NUM_OF_DECIMALS);
+  });
 
-    const tx = await program.methods.transferToWinnerInstruction(winner).accounts({
+  it('can cancel a partly funded escrow by the player', async () => {
+    const tx = await program.methods.cancelTransactionInstruction().accounts({
+      programConfig: state.programConfig,
       transactionState: state.transactionState,
       escrowAccount: state.escrowAccount,
+      authority: state.player,
       player: state.player,
       beneficiary: state.beneficiary,
       mintOfToken: state.mint,
-      winnerAssociatedTokenAccount: winnerAssociatedTokenAccount,
+      playerAssociatedTokenAccount: state.playerAssociatedTokenAccount,
+      beneficiaryAssociatedTokenAccount: state.beneficiaryAssociatedTokenAccount,
       tokenProgram: spl.TOKEN_PROGRAM_ID
-    }).rpc();
+    }).signers([state.playerSigner]).rpc();
 
-    const [, postTransactionWinnerBalance] = await readTokenAccount(provider, winnerAssociatedTokenAccount);
-    assert.equal(postTransactionWinnerBalance, 110 * 10 ** NUM_OF_DECIMALS)
+    // the player's stake comes back, the house never paid in
+    const [, playerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
+    assert.equal(playerBalance, 100 * 10 ** NUM_OF_DECIMALS);
+
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 4);
 
     try {
       await readTokenAccount(provider, state.escrowAccount);
@@ -213,15 +635,10 @@ describe("DungeonNFTEscrowComplete", () => {
       assert.equal(e.message, "Cannot read properties of null (reading 'data')");
     }
 
-    try {
-      await readTokenAccount(provider, state.transactionState);
-      return assert.fail("Account should be deleted");
-    } catch (e) {
-      assert.equal(e.message, "Cannot read properties of null (reading 'data')");
-    }
+    await close_transaction_helper(state, provider, program);
 
     assert.ok(tx);
-    console.log(`Successfully transfered the funds to the winner with signature: ${tx}`);
+    console.log(`Successfully cancelled the escrow with signature: ${tx}`);
   });
 
 });
@@ -239,9 +656,14 @@ describe("DungeonNFTPullBack", () => {
   //  const BENEFICIARY_ASSOCIATED_TOKEN_ACCOUNT = new anchor.web3.PublicKey('Ej4u57S8uUDSzbn8G9xFUXnbhBhUbWbWgyESwUEyM9HK');
 
   let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
 
   it('can setup all the prereqs', async () => {
     state = await prereqs_setup_helper(provider, program);
+    // a short timeout so the escrow expires within the test
+    await config_helper(state, provider, program, refereeSigner.publicKey, provider.wallet.publicKey, 1, 0, 0);
+    await treasury_helper(state, provider, program);
+    await registry_helper(state, provider, program);
   });
 
   it('can initialize a safe payment by the sender', async () => {
@@ -253,17 +675,20 @@ describe("DungeonNFTPullBack", () => {
     await deposit_helper(state, provider, program);
   });
 
-  it('can pull back the funds by player', async () => {
+  it('can pull back the funds by anyone after the deadline', async () => {
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
     const [, preTransactionPlayerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
     assert.equal(preTransactionPlayerBalance, 90 * 10 ** NUM_OF_DECIMALS);
 
     const [, preTransactionBeneficiaryBalance] = await readTokenAccount(provider, state.beneficiaryAssociatedTokenAccount);
-    assert.equal(preTransactionBeneficiaryBalance, 90 * 10 ** NUM_OF_DECIMALS);
+    assert.equal(preTransactionBeneficiaryBalance, 70 * 10 ** NUM_OF_DECIMALS);
 
     const [, preTransactoinEscrowBalance] = await readTokenAccount(provider, state.escrowAccount);
-    assert.equal(preTransactoinEscrowBalance, 2 * 10 * 10 ** NUM_OF_DECIMALS);
+    assert.equal(preTransactoinEscrowBalance, 40 * 10 ** NUM_OF_DECIMALS);
 
     const tx = await program.methods.pullbackInstruction().accounts({
+      programConfig: state.programConfig,
       transactionState: state.transactionState,
       escrowAccount: state.escrowAccount,
       player: state.player,
@@ -288,16 +713,227 @@ describe("DungeonNFTPullBack", () => {
       assert.equal(e.message, "Cannot read properties of null (reading 'data')");
     }
 
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 5);
+
+    await close_transaction_helper(state, provider, program);
+
+    assert.ok(tx);
+    console.log(`Successfully pulled back the funds with signature: ${tx}`);
+
+  });
+
+});
+
+describe("DungeonNFTDispute", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
+
+  const season = 1;
+  let leaderboard: anchor.web3.PublicKey;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program);
+    [leaderboard,] = await anchor.web3.PublicKey.findProgramAddress(
+      [anchor.utils.bytes.utf8.encode("leaderboard"), new anchor.BN(season).toArrayLike(Buffer, "le", 4)],
+      program.programId);
+    // an hour to challenge every verdict, the wallet rules on disputes
+    await config_helper(state, provider, program, refereeSigner.publicKey, provider.wallet.publicKey, 60 * 60, 60 * 60, 250);
+    await treasury_helper(state, provider, program);
+    await registry_helper(state, provider, program);
+  });
+
+  // only games settled while the season runs count towards its board
+  it('can open the season by the admin', async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const tx = await program.methods.leaderboardSetupInstruction(season, new anchor.BN(now - 60), new anchor.BN(now + 60 * 60)).accounts({
+      programConfig: state.programConfig,
+      leaderboard: leaderboard,
+      admin: provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    assert.ok(tx);
+  });
+
+  it('can initialize a safe payment by the sender', async () => {
+    await initialize_payment_helper(state, program);
+  });
+
+  it('can fund the escrow by the both the parties', async () => {
+    await deposit_helper(state, provider, program);
+  });
+
+  it('cannot pay out the verdict while the dispute window is open', async () => {
+    await settle_helper(state, program, refereeSigner, state.beneficiary);
+
     try {
-      await readTokenAccount(provider, state.transactionState);
-      return assert.fail("Account should be deleted");
+      await finalize_helper(state, program);
+      return assert.fail("Finalization should be rejected");
     } catch (e) {
-      assert.equal(e.message, "Cannot read properties of null (reading 'data')");
+      assert.equal(e.error.errorCode.code, "ChallengeWindowOpen");
     }
+  });
+
+  it('can dispute the verdict by the losing party', async () => {
+    const tx = await program.methods.raiseDisputeInstruction().accounts({
+      programConfig: state.programConfig,
+      transactionState: state.transactionState,
+      disputer: state.player
+    }).signers([state.playerSigner]).rpc();
+
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 6);
 
     assert.ok(tx);
-    console.log(`Successfully pulled back the funds with signature: ${tx}`);
+  });
+
+  it('can overturn the verdict by the arbiter and pay out', async () => {
+    await program.methods.resolveDisputeInstruction(false).accounts({
+      transactionState: state.transactionState,
+      programConfig: state.programConfig,
+      arbiter: provider.wallet.publicKey
+    }).rpc();
+
+    const tx = await finalize_helper(state, program);
+
+    // the player now takes the pot minus the 1 token rake
+    const [, postTransactionPlayerBalance] = await readTokenAccount(provider, state.playerAssociatedTokenAccount);
+    assert.equal(postTransactionPlayerBalance, 129 * 10 ** NUM_OF_DECIMALS);
+
+    const [, postTransactionBeneficiaryBalance] = await readTokenAccount(provider, state.beneficiaryAssociatedTokenAccount);
+    assert.equal(postTransactionBeneficiaryBalance, 70 * 10 ** NUM_OF_DECIMALS);
+
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 3);
+
+    await close_transaction_helper(state, provider, program);
+
+    assert.ok(tx);
+    console.log(`Successfully settled the disputed escrow with signature: ${tx}`);
+  });
+
+  it('has put the winning player on the season leaderboard when paying out', async () => {
+    const leaderboardState = await program.account.leaderboard.fetch(leaderboard);
+    assert.ok(leaderboardState.entries[0].player.equals(state.player));
+    assert.equal(leaderboardState.entries[0].score.toNumber(), 1);
+  });
+
+  it('can crank the leaderboard without changing a score that is already on it', async () => {
+    const tx = await program.methods.updateLeaderboardInstruction().accounts({
+      leaderboard: leaderboard,
+      playerProfile: state.playerProfile
+    }).rpc();
 
+    const leaderboardState = await program.account.leaderboard.fetch(leaderboard);
+    assert.equal(leaderboardState.entries.length, 1);
+    assert.equal(leaderboardState.entries[0].score.toNumber(), 1);
+
+    assert.ok(tx);
   });
 
-});
\ No newline at end of file
+});
+
+describe("DungeonNFTAdmin", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program);
+    await config_helper(state, provider, program, provider.wallet.publicKey, provider.wallet.publicKey, 60 * 60, 0, 0);
+    await registry_helper(state, provider, program);
+  });
+
+  it('cannot open an escrow while the program is paused', async () => {
+    await program.methods.setPausedInstruction(true).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    try {
+      await initialize_payment_helper(state, program);
+      return assert.fail("Escrow should not open while paused");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "ProgramPaused");
+    }
+
+    await program.methods.setPausedInstruction(false).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await initialize_payment_helper(state, program);
+  });
+
+  it('cannot migrate an escrow that already has the current layout', async () => {
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.version, 1);
+
+    try {
+      await program.methods.migrateAccountInstruction().accounts({
+        account: state.transactionState,
+        programConfig: state.programConfig,
+        admin: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).rpc();
+      return assert.fail("Current layout should not be migrated again");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "AccountAlreadyMigrated");
+    }
+  });
+
+  it('can hand the admin role over in two steps', async () => {
+    const newAdminSigner = new anchor.web3.Keypair;
+
+    await program.methods.proposeAdminInstruction(newAdminSigner.publicKey).accounts({
+      programConfig: state.programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    // nobody but the proposed key can take the role
+    try {
+      await program.methods.acceptAdminInstruction().accounts({
+        programConfig: state.programConfig,
+        newAdmin: state.player,
+      }).signers([state.playerSigner]).rpc();
+      return assert.fail("Only the pending admin can accept");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "UnauthorizedPendingAdmin");
+    }
+
+    await program.methods.acceptAdminInstruction().accounts({
+      programConfig: state.programConfig,
+      newAdmin: newAdminSigner.publicKey,
+    }).signers([newAdminSigner]).rpc();
+
+    let programConfig = await program.account.programConfig.fetch(state.programConfig);
+    assert.ok(programConfig.admin.equals(newAdminSigner.publicKey));
+
+    // hand it back so the other suites keep working
+    await program.methods.proposeAdminInstruction(provider.wallet.publicKey).accounts({
+      programConfig: state.programConfig,
+      admin: newAdminSigner.publicKey,
+    }).signers([newAdminSigner]).rpc();
+
+    const tx = await program.methods.acceptAdminInstruction().accounts({
+      programConfig: state.programConfig,
+      newAdmin: provider.wallet.publicKey,
+    }).rpc();
+
+    programConfig = await program.account.programConfig.fetch(state.programConfig);
+    assert.ok(programConfig.admin.equals(provider.wallet.publicKey));
+    assert.ok(programConfig.pendingAdmin.equals(anchor.web3.PublicKey.default));
+
+    assert.ok(tx);
+  });
+
+});
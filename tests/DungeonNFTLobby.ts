@@ -0,0 +1,345 @@
+import * as anchor from "@project-serum/anchor";
+import * as spl from "@solana/spl-token";
+import { Program } from "@project-serum/anchor";
+import { DungeonNft } from "../target/types/dungeon_nft";
+import assert from "assert";
+
+const NUM_OF_DECIMALS = 9;
+const NUM_OF_PLAYERS = 3;
+
+interface Player {
+  player: anchor.web3.PublicKey,
+  playerSigner: anchor.web3.Keypair,
+  playerAssociatedTokenAccount: anchor.web3.PublicKey,
+  playerProfile: anchor.web3.PublicKey
+}
+
+interface State {
+  mint: anchor.web3.PublicKey,
+  creator: anchor.web3.PublicKey,
+  creatorSigner: anchor.web3.Keypair,
+  players: Player[],
+  lobbyState: anchor.web3.PublicKey,
+  lobbyVault: anchor.web3.PublicKey,
+  programConfig: anchor.web3.PublicKey,
+  treasuryState: anchor.web3.PublicKey,
+  treasuryVault: anchor.web3.PublicKey
+}
+
+const createUserAssociatedTokenAccount = async (provider: anchor.AnchorProvider, user: anchor.web3.PublicKey, userSigner: anchor.web3.Keypair, mint: anchor.web3.PublicKey, mintAuthority: anchor.web3.PublicKey, mintAuthoritySigner: anchor.web3.Keypair): Promise<anchor.web3.PublicKey> => {
+  const userAssociatedTokenAccount = await spl.getAssociatedTokenAddress(
+    mint,
+    user
+  );
+
+  const tx = new anchor.web3.Transaction;
+  const amount = 100;
+
+  tx.add(spl.createAssociatedTokenAccountInstruction(
+    user,
+    userAssociatedTokenAccount,
+    user,
+    mint
+  ));
+
+  tx.add(spl.createMintToInstruction(
+    mint,
+    userAssociatedTokenAccount,
+    mintAuthority,
+    amount * 10 ** NUM_OF_DECIMALS
+  ));
+
+  await provider.sendAndConfirm(tx, [userSigner, mintAuthoritySigner]);
+
+  return userAssociatedTokenAccount;
+}
+
+const createUser = async (provider: anchor.AnchorProvider): Promise<[anchor.web3.Keypair, anchor.web3.PublicKey]> => {
+  const userSigner = new anchor.web3.Keypair;
+  const user = userSigner.publicKey;
+
+  const num_of_sol = 10;
+  let userTx = await provider.connection.requestAirdrop(user, num_of_sol * anchor.web3.LAMPORTS_PER_SOL);
+  await provider.connection.confirmTransaction(userTx);
+
+  return [userSigner, user];
+}
+
+const readTokenAccount = async (provider: anchor.AnchorProvider, accountPublicKey: anchor.web3.PublicKey): Promise<[spl.RawAccount, string]> => {
+
+  const tokenInfoLol = await provider.connection.getAccountInfo(accountPublicKey);
+  const accountInfo: spl.RawAccount = spl.AccountLayout.decode(tokenInfoLol.data);
+
+  const amount = accountInfo.amount;
+  return [accountInfo, amount.toString()];
+}
+
+const prereqs_setup_helper = async (provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>, refereeSigner: anchor.web3.Keypair): Promise<State> => {
+  let [creatorSigner, creator] = await createUser(provider);
+
+  const mint = await spl.createMint(provider.connection, creatorSigner, creator, creator, NUM_OF_DECIMALS);
+
+  let players: Player[] = [];
+  for (let i = 0; i < NUM_OF_PLAYERS; i++) {
+    let [playerSigner, player] = await createUser(provider);
+    const playerAssociatedTokenAccount = await createUserAssociatedTokenAccount(provider, player, playerSigner, mint, creator, creatorSigner);
+    let [playerProfile,] = await anchor.web3.PublicKey.findProgramAddress(
+      [anchor.utils.bytes.utf8.encode("player-profile"), player.toBuffer()],
+      program.programId);
+    players.push({ player, playerSigner, playerAssociatedTokenAccount, playerProfile });
+  }
+
+  const lobbyId = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
+
+  let [lobbyState,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("lobby-state"), creator.toBuffer(), mint.toBuffer(), lobbyId],
+    program.programId);
+
+  let [lobbyVault,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("lobby-vault"), lobbyState.toBuffer()],
+    program.programId);
+
+  let [programConfig,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("program-config")],
+    program.programId);
+
+  let [treasuryState,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("treasury-state"), mint.toBuffer()],
+    program.programId);
+
+  let [treasuryVault,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("treasury-vault"), mint.toBuffer()],
+    program.programId);
+
+  // the config is a singleton, so later suites only rotate the settings
+  const existingConfig = await provider.connection.getAccountInfo(programConfig);
+  if (existingConfig === null) {
+    await program.methods.configSetupInstruction(provider.wallet.publicKey, refereeSigner.publicKey, provider.wallet.publicKey, new anchor.BN(60 * 60), new anchor.BN(0), 0).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+      programData: (await anchor.web3.PublicKey.findProgramAddress([program.programId.toBuffer()], new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")))[0],
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+  } else {
+    await program.methods.setRefereeInstruction(refereeSigner.publicKey).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setFeeInstruction(0).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+  }
+
+  await program.methods.treasurySetupInstruction().accounts({
+    programConfig: programConfig,
+    treasuryState: treasuryState,
+    treasuryVault: treasuryVault,
+    mintOfToken: mint,
+    admin: provider.wallet.publicKey,
+
+    systemProgram: anchor.web3.SystemProgram.programId,
+    tokenProgram: spl.TOKEN_PROGRAM_ID,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY
+  }).rpc();
+
+  return {
+    mint: mint,
+    creator: creator,
+    creatorSigner: creatorSigner,
+    players: players,
+    lobbyState: lobbyState,
+    lobbyVault: lobbyVault,
+    programConfig: programConfig,
+    treasuryState: treasuryState,
+    treasuryVault: treasuryVault
+  }
+}
+
+const open_and_fill_lobby_helper = async (state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>): Promise<void> => {
+  const entryStake = new anchor.BN(10 * 10 ** NUM_OF_DECIMALS);
+
+  let tx = await program.methods.lobbySetupInstruction(new anchor.BN(0), entryStake, NUM_OF_PLAYERS, [6000, 4000]).accounts({
+    lobbyState: state.lobbyState,
+    lobbyVault: state.lobbyVault,
+    creator: state.creator,
+    mintOfToken: state.mint,
+
+    systemProgram: anchor.web3.SystemProgram.programId,
+    tokenProgram: spl.TOKEN_PROGRAM_ID,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY
+  }).signers([state.creatorSigner]).rpc();
+  assert.ok(tx);
+
+  for (const player of state.players) {
+    tx = await program.methods.joinLobbyInstruction().accounts({
+      programConfig: state.programConfig,
+      lobbyState: state.lobbyState,
+      lobbyVault: state.lobbyVault,
+      player: player.player,
+      playerProfile: player.playerProfile,
+      playerTokenAccount: player.playerAssociatedTokenAccount,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: spl.TOKEN_PROGRAM_ID
+    }).signers([player.playerSigner]).rpc();
+    assert.ok(tx);
+  }
+
+  const [, lobbyVaultBalance] = await readTokenAccount(provider, state.lobbyVault);
+  assert.equal(lobbyVaultBalance, NUM_OF_PLAYERS * 10 * 10 ** NUM_OF_DECIMALS);
+}
+
+describe("DungeonNFTLobbySettle", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program, refereeSigner);
+  });
+
+  it('can open a lobby and let every player join', async () => {
+    await open_and_fill_lobby_helper(state, provider, program);
+  });
+
+  it('can lock the lobby by the creator', async () => {
+    const tx = await program.methods.lockLobbyInstruction().accounts({
+      programConfig: state.programConfig,
+      lobbyState: state.lobbyState,
+      creator: state.creator
+    }).signers([state.creatorSigner]).rpc();
+
+    assert.ok(tx);
+  });
+
+  it('can pay out the winners by the payout table', async () => {
+    const first = state.players[2];
+    const second = state.players[0];
+
+    const winners = [first.player, second.player];
+    const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+
+    // lobby | winners in finishing order | expiry, as laid out by utils::lobby_message
+    const message = Buffer.concat([
+      state.lobbyState.toBuffer(),
+      ...winners.map((winner) => winner.toBuffer()),
+      expiry.toArrayLike(Buffer, "le", 8),
+    ]);
+
+    const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: refereeSigner.secretKey,
+      message: message,
+    });
+
+    let settledEvent = null;
+    const listener = program.addEventListener("LobbySettled", (event) => {
+      settledEvent = event;
+    });
+
+    // the board of the config's current season, only written to while that season runs
+    const programConfig = await program.account.programConfig.fetch(state.programConfig);
+    const [leaderboard,] = await anchor.web3.PublicKey.findProgramAddress(
+      [anchor.utils.bytes.utf8.encode("leaderboard"), new anchor.BN(programConfig.season).toArrayLike(Buffer, "le", 4)],
+      program.programId);
+
+    const tx = await program.methods.settleLobbyInstruction(winners, expiry).accounts({
+      lobbyState: state.lobbyState,
+      lobbyVault: state.lobbyVault,
+      programConfig: state.programConfig,
+      treasuryState: state.treasuryState,
+      treasuryVault: state.treasuryVault,
+      leaderboard: leaderboard,
+      instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      creator: state.creator,
+      tokenProgram: spl.TOKEN_PROGRAM_ID
+    }).remainingAccounts([
+      { pubkey: first.playerAssociatedTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: second.playerAssociatedTokenAccount, isWritable: true, isSigner: false },
+      ...state.players.map((player) => ({ pubkey: player.playerProfile, isWritable: true, isSigner: false })),
+    ]).preInstructions([attestation]).rpc();
+
+    // 30 in the pot, 60% to the first place and 40% to the second
+    const [, firstBalance] = await readTokenAccount(provider, first.playerAssociatedTokenAccount);
+    assert.equal(firstBalance, 108 * 10 ** NUM_OF_DECIMALS);
+
+    const [, secondBalance] = await readTokenAccount(provider, second.playerAssociatedTokenAccount);
+    assert.equal(secondBalance, 102 * 10 ** NUM_OF_DECIMALS);
+
+    const [, loserBalance] = await readTokenAccount(provider, state.players[1].playerAssociatedTokenAccount);
+    assert.equal(loserBalance, 90 * 10 ** NUM_OF_DECIMALS);
+
+    const lobbyState = await program.account.lobbyState.fetch(state.lobbyState);
+    assert.equal(lobbyState.stage, 3);
+
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    assert.ok(settledEvent.winners[0].equals(first.player));
+    assert.equal(settledEvent.prizes[0].toString(), (18 * 10 ** NUM_OF_DECIMALS).toString());
+    assert.equal(settledEvent.stage, 3);
+
+    // 18 of the 30 tokens back is a win on a 10 token seat, 12 as well
+    const firstProfile = await program.account.playerProfile.fetch(first.playerProfile);
+    assert.equal(firstProfile.wins.toNumber(), 1);
+    const secondProfile = await program.account.playerProfile.fetch(second.playerProfile);
+    assert.equal(secondProfile.wins.toNumber(), 1);
+    const loserProfile = await program.account.playerProfile.fetch(state.players[1].playerProfile);
+    assert.equal(loserProfile.losses.toNumber(), 1);
+
+    assert.ok(tx);
+    console.log(`Successfully settled the lobby with signature: ${tx}`);
+  });
+
+});
+
+describe("DungeonNFTLobbyCancel", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program, refereeSigner);
+  });
+
+  it('can open a lobby and let every player join', async () => {
+    await open_and_fill_lobby_helper(state, provider, program);
+  });
+
+  it('can cancel the lobby and refund every player', async () => {
+    const tx = await program.methods.cancelLobbyInstruction().accounts({
+      programConfig: state.programConfig,
+      lobbyState: state.lobbyState,
+      lobbyVault: state.lobbyVault,
+      treasuryState: state.treasuryState,
+      treasuryVault: state.treasuryVault,
+      creator: state.creator,
+      tokenProgram: spl.TOKEN_PROGRAM_ID
+    }).remainingAccounts(state.players.map((player) => (
+      { pubkey: player.playerAssociatedTokenAccount, isWritable: true, isSigner: false }
+    ))).signers([state.creatorSigner]).rpc();
+
+    for (const player of state.players) {
+      const [, balance] = await readTokenAccount(provider, player.playerAssociatedTokenAccount);
+      assert.equal(balance, 100 * 10 ** NUM_OF_DECIMALS);
+    }
+
+    try {
+      await readTokenAccount(provider, state.lobbyVault);
+      return assert.fail("Account should be closed");
+    } catch (e) {
+      assert.equal(e.message, "Cannot read properties of null (reading 'data')");
+    }
+
+    assert.ok(tx);
+    console.log(`Successfully cancelled the lobby with signature: ${tx}`);
+  });
+
+});
@@ -0,0 +1,281 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { DungeonNft } from "../target/types/dungeon_nft";
+import assert from "assert";
+
+const LAMPORTS_PER_SOL = anchor.web3.LAMPORTS_PER_SOL;
+
+interface State {
+  beneficiary: anchor.web3.PublicKey,
+  beneficiarySigner: anchor.web3.Keypair,
+  player: anchor.web3.PublicKey,
+  playerSigner: anchor.web3.Keypair,
+  sessionCounter: anchor.web3.PublicKey,
+  playerProfile: anchor.web3.PublicKey,
+  transactionState: anchor.web3.PublicKey,
+  programConfig: anchor.web3.PublicKey,
+  treasuryState: anchor.web3.PublicKey
+}
+
+const createUser = async (provider: anchor.AnchorProvider): Promise<[anchor.web3.Keypair, anchor.web3.PublicKey]> => {
+  const userSigner = new anchor.web3.Keypair;
+  const user = userSigner.publicKey;
+
+  const num_of_sol = 10;
+  let userTx = await provider.connection.requestAirdrop(user, num_of_sol * LAMPORTS_PER_SOL);
+  await provider.connection.confirmTransaction(userTx);
+
+  return [userSigner, user];
+}
+
+const prereqs_setup_helper = async (provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>, refereeSigner: anchor.web3.Keypair): Promise<State> => {
+  let [playerSigner, player] = await createUser(provider);
+  let [beneficiarySigner, beneficiary] = await createUser(provider);
+
+  let [sessionCounter,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("session-counter"), player.toBuffer()],
+    program.programId);
+
+  let [playerProfile,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("player-profile"), player.toBuffer()],
+    program.programId);
+
+  // a fresh player always starts at session 0
+  const sessionId = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
+
+  let [transactionState,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("sol-transaction-state"), player.toBuffer(), beneficiary.toBuffer(), sessionId],
+    program.programId);
+
+  let [programConfig,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("program-config")],
+    program.programId);
+
+  let [treasuryState,] = await anchor.web3.PublicKey.findProgramAddress(
+    [anchor.utils.bytes.utf8.encode("sol-treasury-state")],
+    program.programId);
+
+  // the config and the lamport treasury are singletons, so later suites only rotate the settings
+  const existingConfig = await provider.connection.getAccountInfo(programConfig);
+  if (existingConfig === null) {
+    await program.methods.configSetupInstruction(beneficiary, refereeSigner.publicKey, provider.wallet.publicKey, new anchor.BN(60 * 60), new anchor.BN(0), 250).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+      programData: (await anchor.web3.PublicKey.findProgramAddress([program.programId.toBuffer()], new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")))[0],
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+  } else {
+    await program.methods.setHouseInstruction(beneficiary).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setRefereeInstruction(refereeSigner.publicKey).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setSettlementTimeoutInstruction(new anchor.BN(60 * 60)).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setDisputePolicyInstruction(provider.wallet.publicKey, new anchor.BN(0)).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+
+    await program.methods.setFeeInstruction(250).accounts({
+      programConfig: programConfig,
+      admin: provider.wallet.publicKey,
+    }).rpc();
+  }
+
+  const existingTreasury = await provider.connection.getAccountInfo(treasuryState);
+  if (existingTreasury === null) {
+    await program.methods.solTreasurySetupInstruction().accounts({
+      programConfig: programConfig,
+      treasuryState: treasuryState,
+      admin: provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+  }
+
+  return {
+    beneficiary: beneficiary,
+    beneficiarySigner: beneficiarySigner,
+    player: player,
+    playerSigner: playerSigner,
+    sessionCounter: sessionCounter,
+    playerProfile: playerProfile,
+    transactionState: transactionState,
+    programConfig: programConfig,
+    treasuryState: treasuryState
+  }
+}
+
+// 1 to 3 in favour of the house unless told otherwise
+const initialize_payment_helper = async (state: State, program: anchor.Program<DungeonNft>, playerStake: anchor.BN = new anchor.BN(1 * LAMPORTS_PER_SOL), houseStake: anchor.BN = new anchor.BN(3 * LAMPORTS_PER_SOL)): Promise<string> => {
+  const tx = await program.methods.solTransactionSetupInstruction(playerStake, houseStake).accounts({
+    programConfig: state.programConfig,
+    sessionCounter: state.sessionCounter,
+    playerProfile: state.playerProfile,
+    transactionState: state.transactionState,
+    player: state.player,
+    beneficiary: state.beneficiary,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  }).signers([state.playerSigner]).rpc();
+
+  assert.ok(tx);
+  return tx;
+}
+
+const close_transaction_helper = async (state: State, provider: anchor.AnchorProvider, program: anchor.Program<DungeonNft>): Promise<void> => {
+  const tx = await program.methods.closeTransactionInstruction().accounts({
+    programConfig: state.programConfig,
+    transactionState: state.transactionState,
+    player: state.player
+  }).rpc();
+
+  assert.ok(tx);
+  assert.equal(await provider.connection.getAccountInfo(state.transactionState), null);
+}
+
+describe("DungeonNFTSolComplete", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program, refereeSigner);
+  });
+
+  it('can initialize a lamport escrow by the player', async () => {
+    await initialize_payment_helper(state, program);
+  });
+
+  it('can fund the escrow with lamports by each party on its own', async () => {
+    const preDepositEscrowBalance = await provider.connection.getBalance(state.transactionState);
+
+    // the house pays in first this time, either order works
+    await program.methods.solDepositByHouseInstruction(new anchor.BN(3 * LAMPORTS_PER_SOL)).accounts({
+      transactionState: state.transactionState,
+      programConfig: state.programConfig,
+      player: state.player,
+      beneficiary: state.beneficiary,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).signers([state.beneficiarySigner]).rpc();
+
+    const tx = await program.methods.solDepositByPlayerInstruction(new anchor.BN(1 * LAMPORTS_PER_SOL)).accounts({
+      transactionState: state.transactionState,
+      programConfig: state.programConfig,
+      player: state.player,
+      beneficiary: state.beneficiary,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).signers([state.playerSigner]).rpc();
+
+    const postDepositEscrowBalance = await provider.connection.getBalance(state.transactionState);
+    assert.equal(postDepositEscrowBalance - preDepositEscrowBalance, 4 * LAMPORTS_PER_SOL);
+
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 2);
+
+    assert.ok(tx);
+  });
+
+  it('can pay out the winner in lamports', async () => {
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    const nonce: anchor.BN = transactionState.resultNonce;
+    const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+
+    // the state account doubles as the escrow, so the referee signs over its address
+    const message = Buffer.concat([
+      state.transactionState.toBuffer(),
+      state.beneficiary.toBuffer(),
+      nonce.toArrayLike(Buffer, "le", 8),
+      expiry.toArrayLike(Buffer, "le", 8),
+    ]);
+
+    const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: refereeSigner.secretKey,
+      message: message,
+    });
+
+    await program.methods.solTransferToWinnerInstruction(state.beneficiary, nonce, expiry).accounts({
+      transactionState: state.transactionState,
+      programConfig: state.programConfig,
+      instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      player: state.player,
+      beneficiary: state.beneficiary
+    }).preInstructions([attestation]).rpc();
+
+    const preTransactionBeneficiaryBalance = await provider.connection.getBalance(state.beneficiary);
+    const preTransactionTreasuryBalance = await provider.connection.getBalance(state.treasuryState);
+
+    const tx = await program.methods.solFinalizeSettlementInstruction().accounts({
+      transactionState: state.transactionState,
+      programConfig: state.programConfig,
+      treasuryState: state.treasuryState,
+      playerProfile: state.playerProfile,
+      player: state.player,
+      beneficiary: state.beneficiary
+    }).rpc();
+
+    // 4 SOL in the pot, 2.5% of it to the treasury
+    const postTransactionBeneficiaryBalance = await provider.connection.getBalance(state.beneficiary);
+    assert.equal(postTransactionBeneficiaryBalance - preTransactionBeneficiaryBalance, 3.9 * LAMPORTS_PER_SOL);
+
+    const postTransactionTreasuryBalance = await provider.connection.getBalance(state.treasuryState);
+    assert.equal(postTransactionTreasuryBalance - preTransactionTreasuryBalance, 0.1 * LAMPORTS_PER_SOL);
+
+    const settledState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(settledState.stage, 3);
+
+    await close_transaction_helper(state, provider, program);
+
+    assert.ok(tx);
+    console.log(`Successfully paid out the lamport escrow with signature: ${tx}`);
+  });
+
+});
+
+describe("DungeonNFTSolCancel", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DungeonNft as Program<DungeonNft>;
+
+  let state: State;
+  const refereeSigner = new anchor.web3.Keypair;
+
+  it('can setup all the prereqs', async () => {
+    state = await prereqs_setup_helper(provider, program, refereeSigner);
+  });
+
+  it('can initialize a lamport escrow by the player', async () => {
+    await initialize_payment_helper(state, program);
+  });
+
+  it('can cancel an unfunded lamport escrow by the player', async () => {
+    const tx = await program.methods.solCancelTransactionInstruction().accounts({
+      programConfig: state.programConfig,
+      transactionState: state.transactionState,
+      authority: state.player,
+      player: state.player,
+      beneficiary: state.beneficiary
+    }).signers([state.playerSigner]).rpc();
+
+    const transactionState = await program.account.transactionState.fetch(state.transactionState);
+    assert.equal(transactionState.stage, 4);
+
+    await close_transaction_helper(state, provider, program);
+
+    assert.ok(tx);
+    console.log(`Successfully cancelled the lamport escrow with signature: ${tx}`);
+  });
+
+});